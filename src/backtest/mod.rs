@@ -0,0 +1,710 @@
+pub mod sweep;
+
+use crate::cache::{is_full_book, ladder_from_ws};
+use crate::clock::ReplayClock;
+use crate::config::PositionSizing;
+use crate::domain::order::Side;
+use crate::domain::{ArbitrageOpportunity, Market, Outcome, TokenPrice};
+use crate::execution::orderbook::Ladder;
+use crate::execution::paper::{match_ladder, PaperConfig};
+use crate::execution::sizing::{Sizer, SizingInput, SizingLimits};
+use crate::journal::TradeJournal;
+use crate::monitor::MarketSnapshot;
+use crate::pricing::joint::{JointModel, JointParams};
+use crate::recorder::{Record, RecordEvent};
+use crate::scheduler::period_start;
+use crate::strategy::dedup::OpportunityFilter;
+use crate::strategy::fees::{fee_per_share, FeeRates};
+use crate::strategy::{ArbitrageDetector, DetectorParams};
+use chrono::{DateTime, Utc};
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::env;
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+// ==================================================
+// CONFIG
+// ==================================================
+
+#[derive(Debug, Clone)]
+pub struct BacktestConfig {
+    pub detector: DetectorParams,
+    pub sizing: PositionSizing,
+    /// MIN_TRADE_SIZE / MAX_TRADE_SIZE, as live
+    pub limits: SizingLimits,
+    pub starting_usdc: Decimal,
+    /// Time per order submission; a hedged entry takes two
+    pub latency: Duration,
+    pub queue_fraction: Decimal,
+    pub unwind_slippage: Decimal,
+    pub cooldown: Duration,
+    pub max_entries_per_period: u32,
+    /// Joint model settings; it learns as the recording's periods resolve
+    pub joint: JointParams,
+}
+
+impl BacktestConfig {
+    /// Same knobs as the live bot: detector, sizing and opportunity filter
+    /// env vars, plus the PAPER_* fill model.
+    pub fn from_env(min_profit_threshold: f64) -> Self {
+        let paper = PaperConfig::from_env();
+        let (cooldown, max_entries_per_period) = OpportunityFilter::settings_from_env();
+
+        // Read UNWIND_SLIPPAGE from env (default: 0.02)
+        let unwind_slippage = env::var("UNWIND_SLIPPAGE")
+            .ok()
+            .and_then(|v| v.parse::<Decimal>().ok())
+            .unwrap_or(dec!(0.02));
+
+        Self {
+            detector: DetectorParams::from_env(min_profit_threshold),
+            sizing: PositionSizing::from_env(),
+            limits: SizingLimits::from_env(),
+            starting_usdc: paper.starting_usdc,
+            latency: paper.latency,
+            queue_fraction: paper.queue_fraction,
+            unwind_slippage,
+            cooldown,
+            max_entries_per_period,
+            joint: JointParams::from_env(),
+        }
+    }
+}
+
+// ==================================================
+// REPORT
+// ==================================================
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PairStats {
+    pub entries: u32,
+    pub settled: u32,
+    pub wins: u32,
+    pub pnl: f64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BacktestReport {
+    pub periods: u32,
+    pub snapshots: u64,
+    pub opportunities: u64,
+    pub suppressed: u64,
+    /// Entries sent (at least the first leg was submitted)
+    pub entries: u32,
+    /// Entries whose first leg got nothing
+    pub unfilled: u32,
+    /// Entries where the hedge leg came up short and was unwound
+    pub unwound: u32,
+    /// Fills priced off the snapshot quote because no depth was recorded
+    pub quote_only_fills: u32,
+    pub requested_shares: f64,
+    pub filled_shares: f64,
+    pub settled: u32,
+    pub wins: u32,
+    /// Positions whose markets never resolved in the recording
+    pub unsettled: u32,
+    pub pnl: f64,
+    pub final_usdc: f64,
+    pub max_drawdown: f64,
+    pub per_pair: BTreeMap<String, PairStats>,
+    /// Realized PnL by the period each trade was entered in
+    pub period_pnl: BTreeMap<u64, f64>,
+}
+
+impl BacktestReport {
+    pub fn win_rate(&self) -> f64 {
+        if self.settled == 0 {
+            return 0.0;
+        }
+        self.wins as f64 / self.settled as f64
+    }
+
+    pub fn fill_rate(&self) -> f64 {
+        if self.requested_shares <= 0.0 {
+            return 0.0;
+        }
+        self.filled_shares / self.requested_shares
+    }
+
+    /// Append a later, separately replayed stretch: counts and PnL add up,
+    /// USDC carries the PnL over and drawdown is the worst of the two.
+    pub fn merge(&mut self, other: &BacktestReport) {
+        self.periods += other.periods;
+        self.snapshots += other.snapshots;
+        self.opportunities += other.opportunities;
+        self.suppressed += other.suppressed;
+        self.entries += other.entries;
+        self.unfilled += other.unfilled;
+        self.unwound += other.unwound;
+        self.quote_only_fills += other.quote_only_fills;
+        self.requested_shares += other.requested_shares;
+        self.filled_shares += other.filled_shares;
+        self.settled += other.settled;
+        self.wins += other.wins;
+        self.unsettled += other.unsettled;
+        self.pnl += other.pnl;
+        self.final_usdc += other.pnl;
+        self.max_drawdown = self.max_drawdown.max(other.max_drawdown);
+
+        for (pair, stats) in &other.per_pair {
+            let merged = self.per_pair.entry(pair.clone()).or_default();
+            merged.entries += stats.entries;
+            merged.settled += stats.settled;
+            merged.wins += stats.wins;
+            merged.pnl += stats.pnl;
+        }
+        for (period, pnl) in &other.period_pnl {
+            *self.period_pnl.entry(*period).or_default() += pnl;
+        }
+    }
+}
+
+impl fmt::Display for BacktestReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "📊 Backtest report")?;
+        writeln!(f, "   Periods:          {}", self.periods)?;
+        writeln!(f, "   Snapshots:        {}", self.snapshots)?;
+        writeln!(
+            f,
+            "   Opportunities:    {} ({} suppressed)",
+            self.opportunities, self.suppressed
+        )?;
+        writeln!(
+            f,
+            "   Entries:          {} ({} unfilled, {} unwound)",
+            self.entries, self.unfilled, self.unwound
+        )?;
+        writeln!(
+            f,
+            "   Fill rate:        {:.1}% ({:.0}/{:.0} shares, {} quote-only fills)",
+            self.fill_rate() * 100.0,
+            self.filled_shares,
+            self.requested_shares,
+            self.quote_only_fills
+        )?;
+        writeln!(
+            f,
+            "   Settled:          {} ({} unsettled)",
+            self.settled, self.unsettled
+        )?;
+        writeln!(f, "   Win rate:         {:.1}%", self.win_rate() * 100.0)?;
+        writeln!(f, "   PnL:              ${:.2}", self.pnl)?;
+        writeln!(f, "   Final USDC:       ${:.2}", self.final_usdc)?;
+        writeln!(f, "   Max drawdown:     ${:.2}", self.max_drawdown)?;
+
+        for (pair, s) in &self.per_pair {
+            writeln!(
+                f,
+                "   {:<18} entries={} settled={} wins={} pnl=${:.2}",
+                pair, s.entries, s.settled, s.wins, s.pnl
+            )?;
+        }
+        Ok(())
+    }
+}
+
+// ==================================================
+// SIMULATION
+// ==================================================
+
+#[derive(Debug, Clone)]
+struct SimTrade {
+    pair: String,
+    period: u64,
+    opportunity: ArbitrageOpportunity,
+    requested: Decimal,
+    eth_filled: Decimal,
+    btc_filled: Decimal,
+    /// USDC paid for both legs
+    cost: Decimal,
+    /// USDC received back from unwinding
+    proceeds: Decimal,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Stage {
+    EthLeg,
+    BtcLeg,
+    Unwind,
+}
+
+struct InFlight {
+    due_ms: u64,
+    stage: Stage,
+    trade: SimTrade,
+}
+
+struct Backtester<'a> {
+    config: &'a BacktestConfig,
+    detector: ArbitrageDetector,
+    filter: OpportunityFilter,
+    sizer: Sizer,
+    fees: FeeRates,
+    clock: Arc<ReplayClock>,
+    joint: JointModel,
+    report: BacktestReport,
+
+    books: HashMap<String, Ladder>,
+    quotes: HashMap<String, TokenPrice>,
+    /// token id → "ETH UP" etc. for per-pair stats
+    labels: HashMap<String, String>,
+    resolutions: HashMap<String, String>,
+    /// (ETH, BTC) condition ids per period and each resolved direction
+    periods: Vec<(String, String)>,
+    outcomes: HashMap<String, Outcome>,
+    /// Current period close, for snapshots recorded without one
+    ends_at: Option<DateTime<Utc>>,
+
+    usdc: Decimal,
+    equity_peak: Decimal,
+    queue: VecDeque<(String, ArbitrageOpportunity)>,
+    in_flight: Option<InFlight>,
+    open: Vec<SimTrade>,
+}
+
+/// Replay `records` (sorted by time) through the detector, opportunity
+/// filter and position sizing, and simulate fills against recorded depth.
+pub fn run(records: &[Record], config: &BacktestConfig) -> BacktestReport {
+    run_after(records, config, &[])
+}
+
+/// `run`, with the joint model first taught every resolved period in
+/// `history` — the live bot starts from what it has already seen.
+pub fn run_after(
+    records: &[Record],
+    config: &BacktestConfig,
+    history: &[Record],
+) -> BacktestReport {
+    let fees = FeeRates::new();
+    let clock = Arc::new(ReplayClock::default());
+    let joint = JointModel::new(config.joint.clone());
+    joint.observe_records(history);
+    let mut bt = Backtester {
+        config,
+        detector: ArbitrageDetector::with_params(&config.detector)
+            .with_fees(fees.clone())
+            .with_joint(joint.clone())
+            .with_clock(clock.clone()),
+        filter: OpportunityFilter::new(
+            config.cooldown,
+            config.max_entries_per_period,
+            Arc::new(TradeJournal::discard()),
+        ),
+        sizer: Sizer::from_config(&config.sizing, config.limits.clone()),
+        fees,
+        clock,
+        joint,
+        report: BacktestReport::default(),
+        books: HashMap::new(),
+        quotes: HashMap::new(),
+        labels: HashMap::new(),
+        resolutions: HashMap::new(),
+        periods: Vec::new(),
+        outcomes: HashMap::new(),
+        ends_at: None,
+        usdc: config.starting_usdc,
+        equity_peak: config.starting_usdc,
+        queue: VecDeque::new(),
+        in_flight: None,
+        open: Vec::new(),
+    };
+
+    for record in records {
+        bt.clock.set(record.ts_ms);
+        bt.advance(record.ts_ms);
+        bt.apply(record);
+    }
+    bt.advance(u64::MAX);
+
+    bt.finish()
+}
+
+impl Backtester<'_> {
+    fn latency_ms(&self) -> u64 {
+        self.config.latency.as_millis() as u64
+    }
+
+    /// Run every order stage due at or before `now_ms`.
+    fn advance(&mut self, now_ms: u64) {
+        while let Some(flight) = self.in_flight.take() {
+            if flight.due_ms > now_ms {
+                self.in_flight = Some(flight);
+                return;
+            }
+            self.step(flight);
+        }
+    }
+
+    fn apply(&mut self, record: &Record) {
+        match &record.event {
+            RecordEvent::MarketMeta {
+                ends_at,
+                eth_market,
+                btc_market,
+                token_params,
+                ..
+            } => {
+                self.report.periods += 1;
+                self.ends_at = Some(*ends_at);
+                self.fees.load(token_params);
+                self.periods.push((
+                    eth_market.condition_id.clone(),
+                    btc_market.condition_id.clone(),
+                ));
+                self.label_market("ETH", eth_market);
+                self.label_market("BTC", btc_market);
+            }
+            RecordEvent::WsDelta { token_id, payload } => {
+                let (bids, asks) = ladder_from_ws(payload);
+                if is_full_book(payload) {
+                    self.books.insert(token_id.clone(), (bids, asks));
+                } else if let Some((book_bids, book_asks)) = self.books.get(token_id) {
+                    // A price change only carries size-1 placeholders: keep the
+                    // last full ladder while its touch still matches, else fall
+                    // back to the snapshot quote
+                    let moved = bids.first().map(|l| l.0) != book_bids.first().map(|l| l.0)
+                        || asks.first().map(|l| l.0) != book_asks.first().map(|l| l.0);
+                    if moved {
+                        self.books.remove(token_id);
+                    }
+                }
+            }
+            RecordEvent::Snapshot {
+                eth_market,
+                btc_market,
+            } => {
+                self.report.snapshots += 1;
+
+                for token in [
+                    &eth_market.up_token,
+                    &eth_market.down_token,
+                    &btc_market.up_token,
+                    &btc_market.down_token,
+                ]
+                .into_iter()
+                .flatten()
+                {
+                    self.quotes.insert(token.token_id.clone(), token.clone());
+                }
+
+                // The live monitor waits for a trade before the next snapshot
+                if self.in_flight.is_none() && self.queue.is_empty() {
+                    let mut snapshot = MarketSnapshot {
+                        eth_market: (**eth_market).clone(),
+                        btc_market: (**btc_market).clone(),
+                        timestamp: Instant::now(),
+                    };
+                    for market in [&mut snapshot.eth_market, &mut snapshot.btc_market] {
+                        market.end_time = market.end_time.or(self.ends_at);
+                    }
+                    self.on_snapshot(&snapshot, record.ts_ms);
+                }
+            }
+            RecordEvent::Resolution {
+                condition_id,
+                winner_token_id,
+                winner_outcome,
+            } => {
+                self.resolutions
+                    .insert(condition_id.clone(), winner_token_id.clone());
+                if let Some(outcome) = Outcome::from_label(winner_outcome) {
+                    self.outcomes.insert(condition_id.clone(), outcome);
+                }
+                self.learn_joint();
+                self.settle();
+            }
+        }
+    }
+
+    /// Feed every fully resolved period to the joint model, as the live
+    /// bot does once both markets publish a winner.
+    fn learn_joint(&self) {
+        for (eth, btc) in &self.periods {
+            if let (Some(&e), Some(&b)) = (self.outcomes.get(eth), self.outcomes.get(btc)) {
+                self.joint.observe(eth, e, b);
+            }
+        }
+    }
+
+    fn label_market(&mut self, asset: &str, market: &Market) {
+        if let Ok(tokens) = market.up_down_tokens() {
+            for t in [tokens.up, tokens.down] {
+                self.labels
+                    .insert(t.token_id, format!("{} {}", asset, t.outcome.as_str()));
+            }
+        }
+    }
+
+    fn pair_label(&self, o: &ArbitrageOpportunity) -> String {
+        let label = |t: &str| self.labels.get(t).cloned().unwrap_or_else(|| "?".into());
+        format!(
+            "{} + {}",
+            label(&o.eth_up_token_id),
+            label(&o.btc_down_token_id)
+        )
+    }
+
+    // ==================================================
+    // ENTRY
+    // ==================================================
+
+    fn on_snapshot(&mut self, snapshot: &MarketSnapshot, now_ms: u64) {
+        for o in self.detector.detect_opportunities(snapshot) {
+            self.report.opportunities += 1;
+
+            if self.filter.admit_at(&o, now_ms).is_err() {
+                self.report.suppressed += 1;
+                continue;
+            }
+
+            let pair = self.pair_label(&o);
+            self.queue.push_back((pair, o));
+        }
+
+        self.start_next(now_ms);
+    }
+
+    /// Size and send the next queued entry, as the trader would at `now_ms`.
+    fn start_next(&mut self, now_ms: u64) {
+        while let Some((pair, o)) = self.queue.pop_front() {
+            // Same sizing as the trader, against recorded top-of-book depth
+            let shares = self.sizer.size(&SizingInput {
+                opportunity: &o,
+                balance: self.usdc.to_f64().unwrap_or(0.0),
+                reserved: 0.0,
+                eth_depth: o.eth_depth.and_then(|d| d.to_f64()),
+                btc_depth: o.btc_depth.and_then(|d| d.to_f64()),
+            });
+            if shares.is_empty() {
+                continue;
+            }
+
+            self.report.entries += 1;
+            self.report
+                .per_pair
+                .entry(pair.clone())
+                .or_default()
+                .entries += 1;
+
+            let requested = Decimal::from_f64(shares.eth).unwrap_or_default();
+            self.report.requested_shares += shares.eth + shares.btc;
+
+            self.in_flight = Some(InFlight {
+                due_ms: now_ms.saturating_add(self.latency_ms()),
+                stage: Stage::EthLeg,
+                trade: SimTrade {
+                    pair,
+                    period: period_start(now_ms / 1000),
+                    opportunity: o,
+                    requested,
+                    eth_filled: Decimal::ZERO,
+                    btc_filled: Decimal::ZERO,
+                    cost: Decimal::ZERO,
+                    proceeds: Decimal::ZERO,
+                },
+            });
+            return;
+        }
+    }
+
+    fn step(&mut self, flight: InFlight) {
+        let InFlight {
+            due_ms,
+            stage,
+            mut trade,
+        } = flight;
+        let o = trade.opportunity.clone();
+
+        let next = match stage {
+            Stage::EthLeg => {
+                let (filled, notional) = self.fill(
+                    &o.eth_up_token_id,
+                    Side::Buy,
+                    o.eth_up_price,
+                    trade.requested,
+                );
+                trade.eth_filled = filled;
+                trade.cost += notional;
+
+                if filled > Decimal::ZERO {
+                    Some(Stage::BtcLeg)
+                } else {
+                    self.report.unfilled += 1;
+                    None
+                }
+            }
+            Stage::BtcLeg => {
+                // Hedge exactly what the first leg got
+                let (filled, notional) = self.fill(
+                    &o.btc_down_token_id,
+                    Side::Buy,
+                    o.btc_down_price,
+                    trade.eth_filled,
+                );
+                trade.btc_filled = filled;
+                trade.cost += notional;
+
+                (filled < trade.eth_filled).then_some(Stage::Unwind)
+            }
+            Stage::Unwind => {
+                let excess = trade.eth_filled - trade.btc_filled;
+                let limit = (o.eth_up_price - self.config.unwind_slippage).max(dec!(0.01));
+                let (filled, notional) = self.fill(&o.eth_up_token_id, Side::Sell, limit, excess);
+                trade.eth_filled -= filled;
+                trade.proceeds += notional;
+                self.report.unwound += 1;
+                None
+            }
+        };
+
+        match next {
+            Some(stage) => {
+                self.in_flight = Some(InFlight {
+                    due_ms: due_ms.saturating_add(self.latency_ms()),
+                    stage,
+                    trade,
+                });
+            }
+            None => {
+                self.report.filled_shares += (trade.eth_filled + trade.btc_filled)
+                    .to_f64()
+                    .unwrap_or(0.0);
+                if trade.eth_filled > Decimal::ZERO || trade.btc_filled > Decimal::ZERO {
+                    self.open.push(trade);
+                }
+                self.settle();
+                self.start_next(due_ms);
+            }
+        }
+    }
+
+    /// Fill against recorded depth, or the last snapshot quote when no
+    /// ladder was recorded for the token. Moves USDC and returns
+    /// (filled, USDC paid or received including the taker fee).
+    fn fill(
+        &mut self,
+        token_id: &str,
+        side: Side,
+        limit: Decimal,
+        size: Decimal,
+    ) -> (Decimal, Decimal) {
+        let ladder = self.books.get(token_id).map(|(bids, asks)| match side {
+            Side::Buy => asks,
+            Side::Sell => bids,
+        });
+
+        let (filled, notional) = match ladder.filter(|l| !l.is_empty()) {
+            Some(ladder) => match_ladder(ladder, &side, limit, size, self.config.queue_fraction),
+            None => {
+                let quote = self.quotes.get(token_id);
+                let price = match side {
+                    Side::Buy => quote.and_then(|q| q.ask).filter(|p| *p <= limit),
+                    Side::Sell => quote.and_then(|q| q.bid).filter(|p| *p >= limit),
+                };
+                match price {
+                    Some(p) => {
+                        self.report.quote_only_fills += 1;
+                        (size, size * p)
+                    }
+                    None => (Decimal::ZERO, Decimal::ZERO),
+                }
+            }
+        };
+
+        let fee = if filled > Decimal::ZERO {
+            fee_per_share(notional / filled, self.fees.taker_bps(token_id)) * filled
+        } else {
+            Decimal::ZERO
+        };
+        let notional = match side {
+            Side::Buy => notional + fee,
+            Side::Sell => notional - fee,
+        };
+
+        // Never spend USDC we do not have
+        let (filled, notional) = match side {
+            Side::Buy if notional > self.usdc && filled > Decimal::ZERO => {
+                let scaled = (filled * self.usdc / notional).floor();
+                (scaled, notional * scaled / filled)
+            }
+            _ => (filled, notional),
+        };
+
+        match side {
+            Side::Buy => self.usdc -= notional,
+            Side::Sell => self.usdc += notional,
+        }
+
+        (filled, notional)
+    }
+
+    // ==================================================
+    // SETTLEMENT
+    // ==================================================
+
+    /// Pay out every open trade whose markets have both resolved.
+    fn settle(&mut self) {
+        let (ready, open): (Vec<SimTrade>, Vec<SimTrade>) =
+            std::mem::take(&mut self.open).into_iter().partition(|t| {
+                self.resolutions
+                    .contains_key(&t.opportunity.eth_condition_id)
+                    && self
+                        .resolutions
+                        .contains_key(&t.opportunity.btc_condition_id)
+            });
+        self.open = open;
+
+        for trade in ready {
+            let o = &trade.opportunity;
+            let won = |cond: &str, token: &str| {
+                self.resolutions.get(cond).map(String::as_str) == Some(token)
+            };
+
+            let mut payout = Decimal::ZERO;
+            if won(&o.eth_condition_id, &o.eth_up_token_id) {
+                payout += trade.eth_filled;
+            }
+            if won(&o.btc_condition_id, &o.btc_down_token_id) {
+                payout += trade.btc_filled;
+            }
+
+            let pnl = payout + trade.proceeds - trade.cost;
+            let pnl_f = pnl.to_f64().unwrap_or(0.0);
+            self.usdc += payout;
+
+            self.report.settled += 1;
+            self.report.pnl += pnl_f;
+
+            *self.report.period_pnl.entry(trade.period).or_default() += pnl_f;
+
+            let stats = self.report.per_pair.entry(trade.pair.clone()).or_default();
+            stats.settled += 1;
+            stats.pnl += pnl_f;
+            if pnl > Decimal::ZERO {
+                self.report.wins += 1;
+                stats.wins += 1;
+            }
+
+            // Drawdown on realized equity (open positions carried at cost)
+            let equity = self.usdc + self.open_cost();
+            self.equity_peak = self.equity_peak.max(equity);
+            let drawdown = (self.equity_peak - equity).to_f64().unwrap_or(0.0);
+            self.report.max_drawdown = self.report.max_drawdown.max(drawdown);
+        }
+    }
+
+    fn open_cost(&self) -> Decimal {
+        self.open.iter().map(|t| t.cost - t.proceeds).sum()
+    }
+
+    fn finish(mut self) -> BacktestReport {
+        self.report.unsettled = self.open.len() as u32;
+        self.report.final_usdc = self.usdc.to_f64().unwrap_or(0.0);
+        self.report
+    }
+}
@@ -0,0 +1,348 @@
+use crate::backtest::{run_after, BacktestConfig, BacktestReport};
+use crate::recorder::{Record, RecordEvent};
+use crate::scheduler::period_start;
+use crate::strategy::DetectorParams;
+use anyhow::{anyhow, Result};
+use std::collections::BTreeSet;
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::thread;
+
+// ==================================================
+// GRID
+// ==================================================
+
+/// Candidate values for each detector threshold.
+#[derive(Debug, Clone)]
+pub struct SweepGrid {
+    pub max_sum: Vec<f64>,
+    pub min_reasonable_price: Vec<f64>,
+    pub max_reasonable_price: Vec<f64>,
+    pub min_total_cost: Vec<f64>,
+}
+
+impl SweepGrid {
+    /// Comma-separated SWEEP_<VAR> lists, e.g. SWEEP_ARBITRAGE_MAX_SUM=0.97,0.98
+    pub fn from_env() -> Self {
+        fn list(key: &str, default: &[f64]) -> Vec<f64> {
+            env::var(key)
+                .ok()
+                .map(|v| {
+                    v.split(',')
+                        .filter_map(|x| x.trim().parse::<f64>().ok())
+                        .collect::<Vec<_>>()
+                })
+                .filter(|v| !v.is_empty())
+                .unwrap_or_else(|| default.to_vec())
+        }
+
+        Self {
+            max_sum: list("SWEEP_ARBITRAGE_MAX_SUM", &[0.96, 0.97, 0.98, 0.99]),
+            min_reasonable_price: list("SWEEP_MIN_REASONABLE_PRICE", &[0.05, 0.10, 0.15, 0.20]),
+            max_reasonable_price: list("SWEEP_MAX_REASONABLE_PRICE", &[0.90, 0.95, 0.98]),
+            min_total_cost: list("SWEEP_MIN_TOTAL_COST", &[0.30, 0.50, 0.70]),
+        }
+    }
+
+    /// Every combination, with the remaining fields taken from `base`.
+    /// Combinations where the min price is not below the max are skipped.
+    pub fn combinations(&self, base: &DetectorParams) -> Vec<DetectorParams> {
+        let mut out = Vec::new();
+        for &max_sum in &self.max_sum {
+            for &min_reasonable_price in &self.min_reasonable_price {
+                for &max_reasonable_price in &self.max_reasonable_price {
+                    if min_reasonable_price >= max_reasonable_price {
+                        continue;
+                    }
+                    for &min_total_cost in &self.min_total_cost {
+                        out.push(DetectorParams {
+                            max_sum,
+                            min_reasonable_price,
+                            max_reasonable_price,
+                            min_total_cost,
+                            ..base.clone()
+                        });
+                    }
+                }
+            }
+        }
+        out
+    }
+}
+
+// ==================================================
+// SCORING
+// ==================================================
+
+#[derive(Debug, Clone)]
+pub struct SweepResult {
+    pub params: DetectorParams,
+    pub report: BacktestReport,
+    pub score: f64,
+}
+
+/// Risk-adjusted PnL: mean per-period PnL over its standard deviation,
+/// scaled by √periods (a per-period Sharpe ratio). Sets with fewer than
+/// `min_trades` settled trades score −∞ so thin samples never win.
+pub fn score(report: &BacktestReport, min_trades: u32) -> f64 {
+    if report.settled < min_trades || report.period_pnl.is_empty() {
+        return f64::NEG_INFINITY;
+    }
+
+    let pnls: Vec<f64> = report.period_pnl.values().copied().collect();
+    let n = pnls.len() as f64;
+    let mean = pnls.iter().sum::<f64>() / n;
+    let var = pnls.iter().map(|p| (p - mean).powi(2)).sum::<f64>() / n;
+
+    // A single period (or identical ones) has no spread; don't divide by 0
+    mean / var.sqrt().max(0.01) * n.sqrt()
+}
+
+/// Backtest every parameter set over `records` on all cores, best first.
+///
+/// Fails if a worker panicked rather than rank what the others returned.
+pub fn run_grid(
+    records: &[Record],
+    base: &BacktestConfig,
+    combos: &[DetectorParams],
+    min_trades: u32,
+) -> Result<Vec<SweepResult>> {
+    let reports = grid_reports(records, &[], base, combos)?;
+    Ok(rank(combos, reports, min_trades))
+}
+
+/// Score each set's report and sort best first.
+fn rank(
+    combos: &[DetectorParams],
+    reports: Vec<BacktestReport>,
+    min_trades: u32,
+) -> Vec<SweepResult> {
+    let mut results: Vec<SweepResult> = combos
+        .iter()
+        .zip(reports)
+        .map(|(params, report)| SweepResult {
+            params: params.clone(),
+            score: score(&report, min_trades),
+            report,
+        })
+        .collect();
+
+    results.sort_by(|a, b| b.score.total_cmp(&a.score));
+    results
+}
+
+/// One report per parameter set over `records`, in `combos` order, with the
+/// joint model warmed on `history` first.
+fn grid_reports(
+    records: &[Record],
+    history: &[Record],
+    base: &BacktestConfig,
+    combos: &[DetectorParams],
+) -> Result<Vec<BacktestReport>> {
+    let workers = thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(4)
+        .max(1);
+    let chunk = combos.len().div_ceil(workers).max(1);
+
+    let (reports, lost) = thread::scope(|scope| {
+        let handles: Vec<_> = combos
+            .chunks(chunk)
+            .map(|part| {
+                let handle = scope.spawn(move || {
+                    part.iter()
+                        .map(|params| {
+                            let config = BacktestConfig {
+                                detector: params.clone(),
+                                ..base.clone()
+                            };
+                            run_after(records, &config, history)
+                        })
+                        .collect::<Vec<_>>()
+                });
+                (part.len(), handle)
+            })
+            .collect();
+
+        let mut reports = Vec::new();
+        let mut lost = 0;
+        for (points, handle) in handles {
+            match handle.join() {
+                Ok(part) => reports.extend(part),
+                Err(_) => lost += points,
+            }
+        }
+        (reports, lost)
+    });
+
+    if lost > 0 {
+        return Err(anyhow!(
+            "Sweep worker panicked: {} of {} parameter sets lost",
+            lost,
+            combos.len()
+        ));
+    }
+
+    Ok(reports)
+}
+
+// ==================================================
+// WALK-FORWARD
+// ==================================================
+
+/// Split the recorded periods into `parts` contiguous (first, last) blocks.
+pub fn period_blocks(records: &[Record], parts: usize) -> Vec<(u64, u64)> {
+    let periods: Vec<u64> = records
+        .iter()
+        .filter(|r| !matches!(r.event, RecordEvent::Resolution { .. }))
+        .map(|r| period_start(r.ts_ms / 1000))
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+
+    let per_part = periods.len().div_ceil(parts.max(1)).max(1);
+
+    periods
+        .chunks(per_part)
+        .map(|block| (block[0], block[block.len() - 1]))
+        .collect()
+}
+
+/// Records from periods `first..=last`.
+///
+/// Resolutions are outcomes, not signals, so all of them are kept —
+/// otherwise trades near the window edge could never settle.
+pub fn window(records: &[Record], first: u64, last: u64) -> Vec<Record> {
+    records
+        .iter()
+        .filter(|r| {
+            matches!(r.event, RecordEvent::Resolution { .. })
+                || (first..=last).contains(&period_start(r.ts_ms / 1000))
+        })
+        .cloned()
+        .collect()
+}
+
+#[derive(Debug, Clone)]
+pub struct FoldResult {
+    pub fold: usize,
+    /// Best set on the expanding training window
+    pub chosen: DetectorParams,
+    pub train_score: f64,
+    /// The chosen set on the next, unseen block
+    pub test: BacktestReport,
+    pub test_score: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct WalkForward {
+    pub folds: Vec<FoldResult>,
+    /// Every set ranked on its test blocks stitched together; the head is
+    /// the exported winner
+    pub ranking: Vec<SweepResult>,
+}
+
+impl WalkForward {
+    /// Out-of-sample PnL of the walk-forward selections
+    pub fn oos_pnl(&self) -> f64 {
+        self.folds.iter().map(|f| f.test.pnl).sum()
+    }
+}
+
+/// Anchored walk-forward: for fold k, pick the best set on blocks 0..=k
+/// and score it on block k+1, with the joint model warmed on the blocks
+/// before it. The final ranking only uses test blocks, so no set is
+/// chosen on data it was fitted to.
+pub fn walk_forward(
+    records: &[Record],
+    base: &BacktestConfig,
+    grid: &SweepGrid,
+    folds: usize,
+    min_trades: u32,
+) -> Result<WalkForward> {
+    let combos = grid.combinations(&base.detector);
+    if combos.is_empty() {
+        return Err(anyhow!("Sweep grid is empty"));
+    }
+
+    let blocks = period_blocks(records, folds + 1);
+    if blocks.len() < 2 {
+        return Err(anyhow!(
+            "Walk-forward needs at least 2 recorded periods, found {}",
+            blocks.len()
+        ));
+    }
+
+    let mut fold_results = Vec::new();
+    let mut out_of_sample: Vec<BacktestReport> = Vec::new();
+
+    for k in 0..blocks.len() - 1 {
+        let train = window(records, blocks[0].0, blocks[k].1);
+        let test = window(records, blocks[k + 1].0, blocks[k + 1].1);
+
+        let trained = grid_reports(&train, &[], base, &combos)?;
+        let tested = grid_reports(&test, &train, base, &combos)?;
+
+        let best = trained
+            .iter()
+            .map(|r| score(r, min_trades))
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+        if let Some((i, train_score)) = best {
+            fold_results.push(FoldResult {
+                fold: k + 1,
+                chosen: combos[i].clone(),
+                train_score,
+                test: tested[i].clone(),
+                // Test blocks are short; no trade minimum there
+                test_score: score(&tested[i], 0),
+            });
+        }
+
+        if out_of_sample.is_empty() {
+            out_of_sample = tested;
+        } else {
+            for (all, fold) in out_of_sample.iter_mut().zip(&tested) {
+                all.merge(fold);
+            }
+        }
+    }
+
+    Ok(WalkForward {
+        folds: fold_results,
+        ranking: rank(&combos, out_of_sample, min_trades),
+    })
+}
+
+// ==================================================
+// PROFILE EXPORT
+// ==================================================
+
+/// Write the winning set as a dotenv profile the bot can load as-is.
+pub fn write_profile(path: impl AsRef<Path>, best: &SweepResult, wf: &WalkForward) -> Result<()> {
+    let p = &best.params;
+    let profile = format!(
+        "# Generated by backtest --sweep\n\
+         # out-of-sample score={:.3} pnl=${:.2} max_drawdown=${:.2} win_rate={:.1}% trades={}\n\
+         # walk-forward: {} fold(s), selections' pnl=${:.2}\n\
+         ARBITRAGE_MAX_SUM={}\n\
+         MIN_REASONABLE_PRICE={}\n\
+         MAX_REASONABLE_PRICE={}\n\
+         MIN_TOTAL_COST={}\n",
+        best.score,
+        best.report.pnl,
+        best.report.max_drawdown,
+        best.report.win_rate() * 100.0,
+        best.report.settled,
+        wf.folds.len(),
+        wf.oos_pnl(),
+        p.max_sum,
+        p.min_reasonable_price,
+        p.max_reasonable_price,
+        p.min_total_cost,
+    );
+
+    fs::write(path, profile)?;
+    Ok(())
+}
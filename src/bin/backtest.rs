@@ -0,0 +1,114 @@
+use polymarket_15m_arbitrage_bot::*;
+
+use anyhow::{anyhow, Result};
+use backtest::sweep::{walk_forward, write_profile, SweepGrid};
+use backtest::BacktestConfig;
+use config::Config;
+use std::path::PathBuf;
+
+/// Usage: backtest [--sweep] [RECORD_DIR]
+///
+/// Replays a recorder directory through the live detector and sizing with
+/// the PAPER_* fill model and prints the report. With --sweep, searches the
+/// SWEEP_* grid walk-forward and exports the best out-of-sample set to
+/// SWEEP_PROFILE.
+fn main() -> Result<()> {
+    dotenv::dotenv().ok();
+
+    // Per-tick detector logging would drown the report
+    if std::env::var("RUST_LOG").is_err() {
+        std::env::set_var("RUST_LOG", "warn");
+    }
+    env_logger::init();
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let sweep = args.iter().any(|a| a == "--sweep");
+
+    let dir = args
+        .iter()
+        .find(|a| !a.starts_with("--"))
+        .cloned()
+        .or_else(|| std::env::var("RECORD_DIR").ok())
+        .ok_or_else(|| anyhow!("usage: backtest [--sweep] <record dir> (or set RECORD_DIR)"))?;
+
+    let config = Config::load(&PathBuf::from("config.json"))?;
+    let bt_config = BacktestConfig::from_env(config.trading.min_profit_threshold);
+
+    println!("📂 Loading recordings from {}", dir);
+    let records = recorder::read_dir(&dir)?;
+    println!("   {} records", records.len());
+
+    if !sweep {
+        let report = backtest::run(&records, &bt_config);
+        println!("\n{}", report);
+        return Ok(());
+    }
+
+    // Read SWEEP_FOLDS from env (default: 4)
+    let folds = std::env::var("SWEEP_FOLDS")
+        .ok()
+        .and_then(|v| v.parse::<usize>().ok())
+        .unwrap_or(4);
+
+    // Read SWEEP_MIN_TRADES from env (default: 5)
+    let min_trades = std::env::var("SWEEP_MIN_TRADES")
+        .ok()
+        .and_then(|v| v.parse::<u32>().ok())
+        .unwrap_or(5);
+
+    let grid = SweepGrid::from_env();
+    println!(
+        "🔬 Sweeping {} parameter sets, {} walk-forward fold(s)",
+        grid.combinations(&bt_config.detector).len(),
+        folds
+    );
+
+    let wf = walk_forward(&records, &bt_config, &grid, folds, min_trades)?;
+
+    println!("\n🚶 Walk-forward (train → next block)");
+    for f in &wf.folds {
+        println!(
+            "   fold {}: max_sum={} min_px={} max_px={} min_cost={} | train score {:.3} | test pnl ${:.2} score {:.3}",
+            f.fold,
+            f.chosen.max_sum,
+            f.chosen.min_reasonable_price,
+            f.chosen.max_reasonable_price,
+            f.chosen.min_total_cost,
+            f.train_score,
+            f.test.pnl,
+            f.test_score
+        );
+    }
+    println!("   Selections' out-of-sample PnL: ${:.2}", wf.oos_pnl());
+
+    println!("\n🏆 Top parameter sets (out-of-sample, all test blocks)");
+    for (i, r) in wf.ranking.iter().take(10).enumerate() {
+        println!(
+            "   {:>2}. score {:>8.3} | pnl ${:>8.2} | dd ${:>7.2} | trades {:>3} | max_sum={} min_px={} max_px={} min_cost={}",
+            i + 1,
+            r.score,
+            r.report.pnl,
+            r.report.max_drawdown,
+            r.report.settled,
+            r.params.max_sum,
+            r.params.min_reasonable_price,
+            r.params.max_reasonable_price,
+            r.params.min_total_cost
+        );
+    }
+
+    match wf.ranking.first().filter(|r| r.score.is_finite()) {
+        Some(best) => {
+            let path =
+                std::env::var("SWEEP_PROFILE").unwrap_or_else(|_| "sweep-profile.env".to_string());
+            write_profile(&path, best, &wf)?;
+            println!("\n💾 Winning profile written to {}", path);
+        }
+        None => println!(
+            "\n⚠️  No parameter set reached {} settled trades — nothing exported",
+            min_trades
+        ),
+    }
+
+    Ok(())
+}
@@ -238,7 +238,7 @@ async fn main() -> Result<()> {
 // ==================================================
 
 fn check_env_vars() -> Diagnostic {
-    let required = vec![
+    let required = [
         "RPC_URL",
         "PRIVATE_KEY",
         "PROXY_WALLET",
@@ -322,7 +322,7 @@ async fn check_eip712_signing(signer: &LocalWallet) -> Diagnostic {
 
     let test_message = H256::random();
 
-    match signer.sign_message(&test_message.as_bytes()).await {
+    match signer.sign_message(test_message.as_bytes()).await {
         Ok(_) => Diagnostic {
             name: "EIP-712 Signing".to_string(),
             status: DiagStatus::Pass,
@@ -56,31 +56,14 @@ async fn main() -> Result<()> {
     println!("✅ Found ETH Market: {}", eth_market.slug);
     println!("✅ Found BTC Market: {}\n", btc_market.slug);
 
-    // Get token IDs from clob_token_ids field (JSON array string)
-    let eth_token_ids_str = eth_market
-        .clob_token_ids
-        .as_ref()
-        .expect("ETH clob_token_ids missing");
-    let btc_token_ids_str = btc_market
-        .clob_token_ids
-        .as_ref()
-        .expect("BTC clob_token_ids missing");
-
-    // Parse as JSON array
-    let eth_token_ids: Vec<String> =
-        serde_json::from_str(eth_token_ids_str).expect("Failed to parse ETH token IDs as JSON");
-    let btc_token_ids: Vec<String> =
-        serde_json::from_str(btc_token_ids_str).expect("Failed to parse BTC token IDs as JSON");
-
-    if eth_token_ids.len() < 2 || btc_token_ids.len() < 2 {
-        panic!("Expected 2 tokens per market (UP and DOWN)");
-    }
-
-    // First token is typically UP (Yes), second is DOWN (No)
-    let eth_up = &eth_token_ids[0];
-    let eth_down = &eth_token_ids[1];
-    let btc_up = &btc_token_ids[0];
-    let btc_down = &btc_token_ids[1];
+    // Resolve UP/DOWN from the outcome labels, not array position
+    let eth_tokens = eth_market.up_down_tokens()?;
+    let btc_tokens = btc_market.up_down_tokens()?;
+
+    let eth_up = &eth_tokens.up.token_id;
+    let eth_down = &eth_tokens.down.token_id;
+    let btc_up = &btc_tokens.up.token_id;
+    let btc_down = &btc_tokens.down.token_id;
 
     println!("\nToken Mapping:");
     println!("  ETH UP:   {}", eth_up);
@@ -84,9 +84,8 @@ async fn main() -> Result<()> {
     }
 
     // Try removing 0x prefix if present
-    if token_id.starts_with("0x") {
+    if let Some(without_prefix) = token_id.strip_prefix("0x") {
         println!("\n--- Token ID has 0x prefix ---");
-        let without_prefix = &token_id[2..];
         println!("Without prefix: {}", without_prefix);
         println!(
             "Try running with: cargo run --bin token_diagnostic {}",
@@ -4,11 +4,33 @@ use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
+/// (price, size) levels, best first
+pub type Levels = Vec<(Decimal, Decimal)>;
+
 #[derive(Debug, Clone)]
 pub struct CachedOrderbook {
     pub bids: Vec<(Decimal, Decimal)>, // (price, size)
     pub asks: Vec<(Decimal, Decimal)>, // (price, size)
     pub last_update_ms: u128,
+    /// Exchange-side book timestamp and hash, when the update carried them
+    pub exchange_ts_ms: Option<u64>,
+    pub hash: Option<String>,
+    /// Sizes are real: the last update carried the whole ladder. A price
+    /// change only carries the top of book, sized 1 as a placeholder.
+    pub full_depth: bool,
+}
+
+impl CachedOrderbook {
+    /// Shares offered at `price`, if the ladder is a full book
+    pub fn ask_depth_at(&self, price: Decimal) -> Option<Decimal> {
+        if !self.full_depth {
+            return None;
+        }
+        self.asks
+            .iter()
+            .find(|&&(p, _)| p == price)
+            .map(|&(_, size)| size)
+    }
 }
 
 #[derive(Clone)]
@@ -16,6 +38,12 @@ pub struct PriceCache {
     inner: Arc<RwLock<HashMap<String, CachedOrderbook>>>,
 }
 
+impl Default for PriceCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl PriceCache {
     pub fn new() -> Self {
         Self {
@@ -28,6 +56,18 @@ impl PriceCache {
         token_id: &str,
         bids: Vec<(Decimal, Decimal)>,
         asks: Vec<(Decimal, Decimal)>,
+    ) {
+        self.insert(token_id, bids, asks, None, None, true).await;
+    }
+
+    async fn insert(
+        &self,
+        token_id: &str,
+        bids: Vec<(Decimal, Decimal)>,
+        asks: Vec<(Decimal, Decimal)>,
+        exchange_ts_ms: Option<u64>,
+        hash: Option<String>,
+        full_depth: bool,
     ) {
         let mut map = self.inner.write().await;
         map.insert(
@@ -36,6 +76,9 @@ impl PriceCache {
                 bids,
                 asks,
                 last_update_ms: now_ms(),
+                exchange_ts_ms,
+                hash,
+                full_depth,
             },
         );
     }
@@ -45,7 +88,7 @@ impl PriceCache {
     }
 }
 
-fn now_ms() -> u128 {
+pub fn now_ms() -> u128 {
     std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
         .unwrap()
@@ -53,19 +96,118 @@ fn now_ms() -> u128 {
 }
 
 impl PriceCache {
+    /// Apply a WS price message. A full book replaces the ladder; a price
+    /// change keeps the last full ladder while its touch still matches, and
+    /// only replaces it with placeholders once the touch moved.
     pub async fn update_from_price_ws(&self, token_id: &str, v: &Value) {
-        let bid = v
-            .get("bid")
-            .and_then(|v| v.as_str())
-            .and_then(|s| s.parse::<Decimal>().ok());
-        let ask = v
-            .get("ask")
-            .and_then(|v| v.as_str())
-            .and_then(|s| s.parse::<Decimal>().ok());
-
-        let bids = bid.map(|b| vec![(b, Decimal::ONE)]).unwrap_or_default();
-        let asks = ask.map(|a| vec![(a, Decimal::ONE)]).unwrap_or_default();
-
-        self.update(token_id, bids, asks).await;
+        let (bids, asks) = ladder_from_ws(v);
+        let (exchange_ts_ms, hash) = book_stamp(v);
+
+        if !is_full_book(v) {
+            let mut map = self.inner.write().await;
+            if let Some(book) = map.get_mut(token_id) {
+                let same_touch = bids.first().map(|l| l.0) == book.bids.first().map(|l| l.0)
+                    && asks.first().map(|l| l.0) == book.asks.first().map(|l| l.0);
+                if book.full_depth && same_touch {
+                    book.last_update_ms = now_ms();
+                    book.exchange_ts_ms = exchange_ts_ms.or(book.exchange_ts_ms);
+                    return;
+                }
+            }
+        }
+
+        self.insert(token_id, bids, asks, exchange_ts_ms, hash, is_full_book(v))
+            .await;
+    }
+}
+
+/// Exchange timestamp (ms) and book hash of a WS message, when present
+pub fn book_stamp(v: &Value) -> (Option<u64>, Option<String>) {
+    let ts = v.get("timestamp").and_then(|t| match t {
+        Value::String(s) => s.parse().ok(),
+        Value::Number(n) => n.as_u64(),
+        _ => None,
+    });
+    let hash = v.get("hash").and_then(|h| h.as_str()).map(str::to_string);
+    (ts, hash)
+}
+
+/// Whether a WS price message carries the whole ladder
+pub fn is_full_book(v: &Value) -> bool {
+    v.get("bids").is_some() && v.get("asks").is_some()
+}
+
+/// Sorted (bids, asks) from a WS price message. A full book message carries
+/// the whole ladder; a price change only the top of book.
+pub fn ladder_from_ws(v: &Value) -> (Levels, Levels) {
+    if let (Some(bids), Some(asks)) = (v.get("bids"), v.get("asks")) {
+        let mut bids = parse_levels(bids);
+        let mut asks = parse_levels(asks);
+        bids.sort_by_key(|l| std::cmp::Reverse(l.0));
+        asks.sort_by_key(|l| l.0);
+        return (bids, asks);
+    }
+
+    let bid = v
+        .get("bid")
+        .and_then(|v| v.as_str())
+        .and_then(|s| s.parse::<Decimal>().ok());
+    let ask = v
+        .get("ask")
+        .and_then(|v| v.as_str())
+        .and_then(|s| s.parse::<Decimal>().ok());
+
+    let bids = bid.map(|b| vec![(b, Decimal::ONE)]).unwrap_or_default();
+    let asks = ask.map(|a| vec![(a, Decimal::ONE)]).unwrap_or_default();
+    (bids, asks)
+}
+
+fn parse_levels(v: &Value) -> Vec<(Decimal, Decimal)> {
+    let num = |v: Option<&Value>| -> Option<Decimal> {
+        match v? {
+            Value::String(s) => s.parse().ok(),
+            Value::Number(n) => n.to_string().parse().ok(),
+            _ => None,
+        }
+    };
+
+    v.as_array()
+        .map(|levels| {
+            levels
+                .iter()
+                .filter_map(|l| Some((num(l.get("price"))?, num(l.get("size"))?)))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn price_changes_keep_the_full_ladder_until_the_touch_moves() {
+        let cache = PriceCache::new();
+        let book = json!({
+            "bids": [{"price": "0.44", "size": "300"}],
+            "asks": [{"price": "0.46", "size": "250"}, {"price": "0.47", "size": "900"}],
+        });
+        cache.update_from_price_ws("tok", &book).await;
+
+        cache
+            .update_from_price_ws("tok", &json!({"bid": "0.44", "ask": "0.46"}))
+            .await;
+        let kept = cache.get("tok").await.unwrap();
+        assert!(kept.full_depth);
+        assert_eq!(kept.asks, vec![(dec!(0.46), dec!(250)), (dec!(0.47), dec!(900))]);
+
+        cache
+            .update_from_price_ws("tok", &json!({"bid": "0.45", "ask": "0.47"}))
+            .await;
+        let moved = cache.get("tok").await.unwrap();
+        assert!(!moved.full_depth);
+        assert_eq!(moved.asks, vec![(dec!(0.47), Decimal::ONE)]);
     }
 }
@@ -1,20 +1,20 @@
 use crate::domain::order::Side;
 use crate::domain::*;
 use crate::execution::clob_client::ClobClient;
+use crate::http::HttpClient;
 use anyhow::{Context, Result};
 use base64::{engine::general_purpose, Engine as _};
 use hmac::{Hmac, Mac};
-use reqwest::Client;
 use rust_decimal::Decimal;
-use serde_json::Value;
 use sha2::Sha256;
 use std::sync::Arc;
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::time::{SystemTime, UNIX_EPOCH};
 type HmacSha256 = Hmac<Sha256>;
 
 #[derive(Clone)]
 pub struct PolymarketClient {
-    client: Client,
+    /// Shared with the CLOB client: one pool, one set of rate limits
+    client: HttpClient,
 
     pub gamma_url: String,
     pub clob_url: String,
@@ -48,10 +48,7 @@ impl PolymarketClient {
         read_only: bool,
         clob_client: Arc<ClobClient>,
     ) -> Self {
-        let client = Client::builder()
-            .timeout(Duration::from_secs(10))
-            .build()
-            .expect("HTTP client");
+        let client = clob_client.http.clone();
 
         Self {
             client,
@@ -72,6 +69,11 @@ impl PolymarketClient {
         self.clob_client.clone()
     }
 
+    /// The rate-limited HTTP client every Polymarket call goes through
+    pub fn http(&self) -> &HttpClient {
+        &self.client
+    }
+
     // ==================================================
     // BUILD + SIGN ORDER (🔥 THIS WAS MISSING)
     // ==================================================
@@ -146,16 +148,15 @@ impl PolymarketClient {
 
         let signature = self.sign_request("POST", path, &body, &timestamp);
 
-        let response = self
+        let request = self
             .client
             .post(&url)
             .header("POLY-API-KEY", &self.api_key)
             .header("POLY-API-SIGNATURE", signature)
             .header("POLY-API-TIMESTAMP", &timestamp)
             .header("POLY-API-PASSPHRASE", &self.api_passphrase)
-            .json(&payload)
-            .send()
-            .await?;
+            .json(&payload);
+        let response = self.client.send(request).await?;
 
         if !response.status().is_success() {
             let err = response.text().await.unwrap_or_default();
@@ -194,13 +195,70 @@ impl PolymarketClient {
         let balance_f64 = balance.as_u128() as f64 / 1_000_000.0;
         Ok(Decimal::try_from(balance_f64).unwrap_or_default())
     }
+    // ==================================================
+    // MARKET PARAMETERS (CLOB)
+    // ==================================================
+    pub async fn get_tick_size(&self, token_id: &str) -> Result<Decimal> {
+        let url = format!("{}/tick-size?token_id={}", self.clob_url, token_id);
+
+        let json: serde_json::Value = self
+            .client
+            .send(self.client.get(&url))
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        json["minimum_tick_size"]
+            .as_f64()
+            .and_then(Decimal::from_f64_retain)
+            .or_else(|| json["minimum_tick_size"].as_str()?.parse().ok())
+            .context("Tick size missing in response")
+    }
+
+    pub async fn get_fee_rate_bps(&self, token_id: &str) -> Result<u32> {
+        let url = format!("{}/fee-rate?token_id={}", self.clob_url, token_id);
+
+        let json: serde_json::Value = self
+            .client
+            .send(self.client.get(&url))
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        json["base_fee"]
+            .as_u64()
+            .map(|bps| bps as u32)
+            .context("Fee rate missing in response")
+    }
+
+    // ==================================================
+    // MARKET DETAILS / RESOLUTION (CLOB)
+    // ==================================================
+    pub async fn get_market_details(&self, condition_id: &str) -> Result<MarketDetails> {
+        let url = format!("{}/markets/{}", self.clob_url, condition_id);
+
+        Ok(self
+            .client
+            .send(self.client.get(&url))
+            .await?
+            .error_for_status()?
+            .json()
+            .await?)
+    }
+
     // ==================================================
     // GET MARKET BY SLUG (Gamma)
     // ==================================================
     pub async fn get_market_by_slug(&self, slug: &str) -> Result<Market> {
         let url = format!("{}/events/slug/{}", self.gamma_url, slug);
 
-        let response = self.client.get(&url).send().await?.error_for_status()?; // 👈 better error handling
+        let response = self
+            .client
+            .send(self.client.get(&url))
+            .await?
+            .error_for_status()?; // 👈 better error handling
 
         let json: serde_json::Value = response.json().await?;
 
@@ -0,0 +1,55 @@
+use chrono::{DateTime, Utc};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// Source of "now" for everything that windows or stamps by time, so a
+/// replayed session sees recorded time instead of the wall clock.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+
+    fn now_ms(&self) -> u64 {
+        self.now().timestamp_millis().max(0) as u64
+    }
+}
+
+/// The wall clock
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+pub fn system() -> Arc<dyn Clock> {
+    Arc::new(SystemClock)
+}
+
+/// A clock moved forward by the replay driver. Never goes backwards.
+#[derive(Default)]
+pub struct ReplayClock {
+    ms: AtomicU64,
+}
+
+impl ReplayClock {
+    pub fn new(start_ms: u64) -> Self {
+        Self {
+            ms: AtomicU64::new(start_ms),
+        }
+    }
+
+    pub fn set(&self, ms: u64) {
+        self.ms.fetch_max(ms, Ordering::Relaxed);
+    }
+}
+
+impl Clock for ReplayClock {
+    fn now(&self) -> DateTime<Utc> {
+        let ms = self.ms.load(Ordering::Relaxed);
+        DateTime::from_timestamp_millis(ms as i64).unwrap_or_default()
+    }
+
+    fn now_ms(&self) -> u64 {
+        self.ms.load(Ordering::Relaxed)
+    }
+}
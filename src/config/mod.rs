@@ -14,6 +14,7 @@ pub enum TradeMode {
     Percentage,
     Dynamic,
     Free,
+    Kelly,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -28,6 +29,20 @@ pub struct PositionSizing {
 
     // DYNAMIC: max risk % of balance
     pub max_risk_percent: Option<f64>,
+
+    // KELLY: multiple of the growth-optimal stake (0.25 = quarter Kelly)
+    #[serde(default)]
+    pub kelly_fraction: Option<f64>,
+
+    // KELLY: cap on the stake as a fraction of balance
+    #[serde(default)]
+    pub kelly_max_fraction: Option<f64>,
+
+    // KELLY: hard USDC bounds; stakes below the minimum are skipped
+    #[serde(default)]
+    pub min_notional: Option<f64>,
+    #[serde(default)]
+    pub max_notional: Option<f64>,
 }
 
 impl PositionSizing {
@@ -47,6 +62,10 @@ impl PositionSizing {
                 ),
                 percentage: None,
                 max_risk_percent: None,
+                kelly_fraction: None,
+                kelly_max_fraction: None,
+                min_notional: None,
+                max_notional: None,
             },
 
             "DYNAMIC" => Self {
@@ -59,6 +78,41 @@ impl PositionSizing {
                         .parse()
                         .expect("Invalid MAX_RISK_PERCENT"),
                 ),
+                kelly_fraction: None,
+                kelly_max_fraction: None,
+                min_notional: None,
+                max_notional: None,
+            },
+
+            "KELLY" => Self {
+                mode: TradeMode::Kelly,
+                fixed_usdc: None,
+                percentage: None,
+                max_risk_percent: None,
+                kelly_fraction: Some(
+                    env::var("KELLY_FRACTION")
+                        .unwrap_or_else(|_| "0.25".to_string())
+                        .parse()
+                        .expect("Invalid KELLY_FRACTION"),
+                ),
+                kelly_max_fraction: Some(
+                    env::var("KELLY_MAX_FRACTION")
+                        .unwrap_or_else(|_| "0.10".to_string())
+                        .parse()
+                        .expect("Invalid KELLY_MAX_FRACTION"),
+                ),
+                min_notional: Some(
+                    env::var("KELLY_MIN_USDC")
+                        .unwrap_or_else(|_| "1".to_string())
+                        .parse()
+                        .expect("Invalid KELLY_MIN_USDC"),
+                ),
+                max_notional: Some(
+                    env::var("KELLY_MAX_USDC")
+                        .unwrap_or_else(|_| "100".to_string())
+                        .parse()
+                        .expect("Invalid KELLY_MAX_USDC"),
+                ),
             },
 
             "FREE" => Self {
@@ -66,6 +120,10 @@ impl PositionSizing {
                 fixed_usdc: None,
                 percentage: None,
                 max_risk_percent: None,
+                kelly_fraction: None,
+                kelly_max_fraction: None,
+                min_notional: None,
+                max_notional: None,
             },
 
             // DEFAULT = PERCENTAGE
@@ -79,6 +137,10 @@ impl PositionSizing {
                         .expect("Invalid PERCENTAGE_PER_TRADE"),
                 ),
                 max_risk_percent: None,
+                kelly_fraction: None,
+                kelly_max_fraction: None,
+                min_notional: None,
+                max_notional: None,
             },
         }
     }
@@ -172,6 +234,10 @@ impl Default for Config {
                     fixed_usdc: None,
                     percentage: Some(10.0),
                     max_risk_percent: None,
+                    kelly_fraction: None,
+                    kelly_max_fraction: None,
+                    min_notional: None,
+                    max_notional: None,
                 },
                 eth_condition_id: None,
                 btc_condition_id: None,
@@ -1,3 +1,4 @@
+use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
 use serde::{Deserialize, Serialize};
@@ -29,6 +30,118 @@ pub struct Market {
     pub outcomes: Option<String>,
 }
 
+impl Market {
+    /// Market close time parsed from `end_date_iso` (either casing).
+    ///
+    /// Returns `None` when the field is missing or date-only.
+    pub fn end_time(&self) -> Option<DateTime<Utc>> {
+        [&self.end_date_iso, &self.end_date_iso_alt]
+            .into_iter()
+            .flatten()
+            .find_map(|s| DateTime::parse_from_rfc3339(s).ok())
+            .map(|t| t.with_timezone(&Utc))
+    }
+
+    /// Zip `outcomes` with `clobTokenIds` into typed UP/DOWN tokens.
+    ///
+    /// Fails if either field is missing or the labels are not exactly one
+    /// "Up" and one "Down" — never guess the side from array position.
+    pub fn up_down_tokens(&self) -> anyhow::Result<UpDownTokens> {
+        let outcomes_str = self
+            .outcomes
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("{} missing outcomes", self.slug))?;
+        let token_ids_str = self
+            .clob_token_ids
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("{} missing clob_token_ids", self.slug))?;
+
+        let outcomes: Vec<String> = serde_json::from_str(outcomes_str)
+            .map_err(|e| anyhow::anyhow!("Failed to parse {} outcomes: {}", self.slug, e))?;
+        let token_ids: Vec<String> = serde_json::from_str(token_ids_str)
+            .map_err(|e| anyhow::anyhow!("Failed to parse {} token IDs: {}", self.slug, e))?;
+
+        if outcomes.len() != 2 || token_ids.len() != 2 {
+            anyhow::bail!(
+                "{} expected 2 outcomes and 2 tokens, got {:?} / {} tokens",
+                self.slug,
+                outcomes,
+                token_ids.len()
+            );
+        }
+
+        let mut up = None;
+        let mut down = None;
+
+        for (label, token_id) in outcomes.iter().zip(token_ids) {
+            let outcome = Outcome::from_label(label).ok_or_else(|| {
+                anyhow::anyhow!("{} has unexpected outcome label {:?}", self.slug, label)
+            })?;
+            let token = OutcomeToken { outcome, token_id };
+
+            let slot = match outcome {
+                Outcome::Up => &mut up,
+                Outcome::Down => &mut down,
+            };
+            if slot.replace(token).is_some() {
+                anyhow::bail!("{} has duplicate outcome label {:?}", self.slug, label);
+            }
+        }
+
+        match (up, down) {
+            (Some(up), Some(down)) => Ok(UpDownTokens { up, down }),
+            _ => anyhow::bail!(
+                "{} outcomes are not an Up/Down pair: {:?}",
+                self.slug,
+                outcomes
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Outcome {
+    Up,
+    Down,
+}
+
+impl Outcome {
+    /// Parse a Gamma outcome label ("Up" / "Down", case-insensitive)
+    pub fn from_label(label: &str) -> Option<Self> {
+        match label.trim().to_ascii_lowercase().as_str() {
+            "up" => Some(Outcome::Up),
+            "down" => Some(Outcome::Down),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Outcome::Up => "UP",
+            Outcome::Down => "DOWN",
+        }
+    }
+
+    pub fn opposite(&self) -> Self {
+        match self {
+            Outcome::Up => Outcome::Down,
+            Outcome::Down => Outcome::Up,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutcomeToken {
+    pub outcome: Outcome,
+    pub token_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpDownTokens {
+    pub up: OutcomeToken,
+    pub down: OutcomeToken,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Token {
     #[serde(rename = "tokenId")]
@@ -57,11 +170,22 @@ pub struct OrderBookEntry {
 // PRICES
 // ==================================================
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TokenPrice {
     pub token_id: String,
     pub bid: Option<Decimal>,
     pub ask: Option<Decimal>,
+    /// Shares offered at `ask`
+    #[serde(default)]
+    pub ask_size: Option<Decimal>,
+    /// When we read this quote (epoch ms)
+    #[serde(default)]
+    pub observed_at_ms: Option<u64>,
+    /// Exchange book timestamp (epoch ms) and hash, where available
+    #[serde(default)]
+    pub book_ts_ms: Option<u64>,
+    #[serde(default)]
+    pub book_hash: Option<String>,
 }
 
 impl TokenPrice {
@@ -75,6 +199,24 @@ impl TokenPrice {
         self.bid.unwrap_or(dec!(0))
     }
 
+    /// Which exchange book this quote came from: hash and timestamp,
+    /// where the feed reports them
+    pub fn book_version(&self) -> Option<String> {
+        match (&self.book_hash, self.book_ts_ms) {
+            (None, None) => None,
+            (hash, ts) => Some(format!(
+                "{}@{}",
+                hash.as_deref().unwrap_or("?"),
+                ts.map_or("?".to_string(), |t| t.to_string())
+            )),
+        }
+    }
+
+    /// Age of the quote at `now_ms`, if we know when it was read
+    pub fn age_ms(&self, now_ms: u64) -> Option<u64> {
+        self.observed_at_ms.map(|t| now_ms.saturating_sub(t))
+    }
+
     /// Mid price (for diagnostics)
     pub fn mid_price(&self) -> Option<Decimal> {
         match (self.bid, self.ask) {
@@ -111,28 +253,89 @@ pub struct OrderResponse {
 // MARKET SNAPSHOT
 // ==================================================
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MarketData {
     pub condition_id: String,
     pub market_name: String,
     pub up_token: Option<TokenPrice>,
     pub down_token: Option<TokenPrice>,
+    /// Market close, when known
+    #[serde(default)]
+    pub end_time: Option<DateTime<Utc>>,
 }
 
 // ==================================================
 // ARBITRAGE
 // ==================================================
 pub mod order;
+pub mod time;
 #[derive(Debug, Clone)]
 pub struct ArbitrageOpportunity {
     pub eth_up_price: Decimal,
     pub btc_down_price: Decimal,
     pub total_cost: Decimal,
+    /// Taker fees on both legs per unit, already netted out of expected_profit
+    pub fees: Decimal,
+    /// Expected payout − cost − fees; payout is 1 without a joint model
     pub expected_profit: Decimal,
     pub eth_up_token_id: String,
     pub btc_down_token_id: String,
     pub eth_condition_id: String,
     pub btc_condition_id: String,
+    /// Phase of the window when detected, and when the earlier market closes
+    pub phase: time::TradingPhase,
+    pub ends_at: Option<DateTime<Utc>>,
+    /// Model payout per unit (both legs' fair probabilities), with a spot feed
+    pub fair_value: Option<Decimal>,
+    /// Variance of the pair's payout under the joint model
+    pub payout_variance: Option<Decimal>,
+    /// Chance of each payout under the joint model
+    pub payout_odds: Option<PayoutOdds>,
+    /// Shares quoted at each leg's ask, when the book is known
+    pub eth_depth: Option<Decimal>,
+    pub btc_depth: Option<Decimal>,
+    /// When the snapshot it was found in was taken
+    pub observed_at: Option<std::time::Instant>,
+    /// Exchange book version of each leg's quote (see TokenPrice::book_version)
+    pub eth_book: Option<String>,
+    pub btc_book: Option<String>,
+}
+
+#[cfg(test)]
+impl ArbitrageOpportunity {
+    /// Test fixture: ETH UP + BTC DOWN at these asks, fee-free, in the open
+    /// phase, with every optional field empty
+    pub(crate) fn at(eth_up_price: Decimal, btc_down_price: Decimal) -> Self {
+        Self {
+            eth_up_price,
+            btc_down_price,
+            total_cost: eth_up_price + btc_down_price,
+            fees: Decimal::ZERO,
+            expected_profit: Decimal::ONE - eth_up_price - btc_down_price,
+            eth_up_token_id: "eth".into(),
+            btc_down_token_id: "btc".into(),
+            eth_condition_id: "eth-cond".into(),
+            btc_condition_id: "btc-cond".into(),
+            phase: time::TradingPhase::Open,
+            ends_at: None,
+            fair_value: None,
+            payout_variance: None,
+            payout_odds: None,
+            eth_depth: None,
+            btc_depth: None,
+            observed_at: None,
+            eth_book: None,
+            btc_book: None,
+        }
+    }
+}
+
+/// Chance a pair pays 2 (both legs win), 1 (one does) or 0 per unit
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PayoutOdds {
+    pub both: f64,
+    pub one: f64,
+    pub neither: f64,
 }
 
 // ==================================================
@@ -256,3 +459,71 @@ pub struct Balance {
     pub asset: String,
     pub balance: rust_decimal::Decimal,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn market(outcomes: Option<&str>, token_ids: Option<&str>) -> Market {
+        Market {
+            condition_id: "cond".into(),
+            market_id: None,
+            name: None,
+            question: "ETH up or down?".into(),
+            slug: "eth-updown-15m".into(),
+            resolution_source: None,
+            end_date_iso: None,
+            end_date_iso_alt: None,
+            active: true,
+            closed: false,
+            tokens: None,
+            clob_token_ids: token_ids.map(String::from),
+            outcomes: outcomes.map(String::from),
+        }
+    }
+
+    #[test]
+    fn up_down_tokens_follow_outcome_labels() {
+        let ids = Some(r#"["111", "222"]"#);
+
+        let tokens = market(Some(r#"["Up", "Down"]"#), ids)
+            .up_down_tokens()
+            .unwrap();
+        assert_eq!(
+            (tokens.up.token_id.as_str(), tokens.down.token_id.as_str()),
+            ("111", "222")
+        );
+
+        // Reversed labels swap the sides, whatever the array order
+        let tokens = market(Some(r#"["down", "UP"]"#), ids)
+            .up_down_tokens()
+            .unwrap();
+        assert_eq!(
+            (tokens.up.token_id.as_str(), tokens.down.token_id.as_str()),
+            ("222", "111")
+        );
+        assert_eq!(tokens.up.outcome, Outcome::Up);
+    }
+
+    #[test]
+    fn up_down_tokens_reject_missing_or_unknown_outcomes() {
+        let ids = Some(r#"["111", "222"]"#);
+
+        // (name, outcomes, token ids)
+        let cases = [
+            ("no outcomes", None, ids),
+            ("no token ids", Some(r#"["Up", "Down"]"#), None),
+            ("one outcome", Some(r#"["Up"]"#), ids),
+            ("yes/no labels", Some(r#"["Yes", "No"]"#), ids),
+            ("duplicate label", Some(r#"["Up", "Up"]"#), ids),
+            ("unparseable", Some("Up,Down"), ids),
+        ];
+        for (name, outcomes, token_ids) in cases {
+            assert!(
+                market(outcomes, token_ids).up_down_tokens().is_err(),
+                "{}",
+                name
+            );
+        }
+    }
+}
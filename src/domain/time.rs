@@ -1,4 +1,6 @@
 use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
 
 pub fn time_remaining(end: DateTime<Utc>) -> String {
     let now = Utc::now();
@@ -13,3 +15,42 @@ pub fn time_remaining(end: DateTime<Utc>) -> String {
 
     format!("{:02}m {:02}s", mins, secs)
 }
+
+/// Whole seconds from `now` until `end`; negative once it has passed
+pub fn seconds_remaining(end: DateTime<Utc>, now: DateTime<Utc>) -> i64 {
+    (end - now).num_seconds()
+}
+
+/// Where in its 15-minute window a market is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TradingPhase {
+    /// Just opened, books still settling — no entries
+    Opening,
+    Open,
+    /// Close to expiry — entries need a bigger edge
+    Closing,
+    /// Hard stop before close — no entries
+    NoTrade,
+}
+
+impl TradingPhase {
+    pub fn allows_entry(&self) -> bool {
+        matches!(self, TradingPhase::Open | TradingPhase::Closing)
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TradingPhase::Opening => "opening",
+            TradingPhase::Open => "open",
+            TradingPhase::Closing => "closing",
+            TradingPhase::NoTrade => "no_trade",
+        }
+    }
+}
+
+impl fmt::Display for TradingPhase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
@@ -0,0 +1,246 @@
+use crate::client::PolymarketClient;
+use crate::execution::clob_client::{ClobClient, OrderType, PostedOrder, MAX_BATCH_ORDERS};
+use crate::execution::errors::ClobError;
+use crate::execution::latency::SubmitLatency;
+use crate::risk::OrderIntent;
+use crate::wallet::balance::BalanceTracker;
+use crate::wallet::signer::ClobOrder;
+use anyhow::Result;
+use async_trait::async_trait;
+use ethers::types::Signature;
+use futures_util::future::join_all;
+use rust_decimal::Decimal;
+use std::sync::Arc;
+
+// ==================================================
+// FILL REPORT
+// ==================================================
+
+#[derive(Debug, Clone)]
+pub struct FillReport {
+    pub order_id: Option<String>,
+    /// Shares matched, as the venue reported them
+    pub filled_size: Decimal,
+    pub avg_price: Decimal,
+    pub latency: SubmitLatency,
+}
+
+/// Where a resting order stands
+#[derive(Debug, Clone)]
+pub struct OrderStatus {
+    pub order_id: String,
+    /// Shares matched so far
+    pub matched: Decimal,
+    /// Still on the book
+    pub live: bool,
+}
+
+// ==================================================
+// BACKEND INTERFACE
+// ==================================================
+
+/// Where signed orders go. The trader never knows whether it is real.
+#[async_trait]
+pub trait ExecutionBackend: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    async fn usdc_balance(&self) -> Result<Decimal>;
+
+    /// Balance + allowance gate before a trade (raw USDC, 6 decimals)
+    async fn ensure_trading_ready(&self, required_usdc: u128) -> Result<()>;
+
+    /// Take liquidity: whatever doesn't match at once is cancelled, so the
+    /// report is final
+    async fn submit(
+        &self,
+        intent: &OrderIntent,
+        order: ClobOrder,
+        sig: Signature,
+    ) -> Result<FillReport>;
+
+    /// Submit several orders at once, results in submission order. Err only
+    /// when the batch as a whole never reached the venue.
+    async fn submit_batch(
+        &self,
+        orders: Vec<(&OrderIntent, ClobOrder, Signature)>,
+    ) -> Result<Vec<Result<FillReport>>> {
+        let fills = orders
+            .into_iter()
+            .map(|(intent, order, sig)| self.submit(intent, order, sig));
+        Ok(join_all(fills).await)
+    }
+
+    /// Post a GTC order that rests until filled or cancelled. None when
+    /// nothing was placed (read-only).
+    async fn rest(
+        &self,
+        intent: &OrderIntent,
+        order: ClobOrder,
+        sig: Signature,
+    ) -> Result<Option<String>>;
+
+    async fn order_status(&self, order_id: &str) -> Result<OrderStatus>;
+
+    async fn cancel(&self, order_id: &str) -> Result<()>;
+
+    async fn cancel_all(&self) -> Result<()>;
+}
+
+// ==================================================
+// LIVE (CLOB)
+// ==================================================
+
+pub struct LiveBackend {
+    api: Arc<PolymarketClient>,
+    clob: Arc<ClobClient>,
+    proxy_wallet: String,
+    balances: Option<BalanceTracker>,
+}
+
+impl LiveBackend {
+    pub fn new(api: Arc<PolymarketClient>, clob: Arc<ClobClient>, proxy_wallet: String) -> Self {
+        Self {
+            api,
+            clob,
+            proxy_wallet,
+            balances: None,
+        }
+    }
+
+    /// Read USDC from a log-following tracker instead of an RPC call
+    pub fn with_balances(mut self, balances: BalanceTracker) -> Self {
+        self.balances = Some(balances);
+        self
+    }
+
+    /// Report what a taker order matched: from the response when it says,
+    /// from the order itself when it doesn't. Nothing matched is a NoMatch;
+    /// an order we can't read back is an unknown outcome, never a guess.
+    async fn fill_report(
+        &self,
+        intent: &OrderIntent,
+        posted: PostedOrder,
+        latency: SubmitLatency,
+    ) -> Result<FillReport> {
+        let filled_size = match (posted.matched, &posted.order_id) {
+            (Some(matched), _) => matched,
+            (None, Some(order_id)) => match self.clob.get_order(order_id).await {
+                Ok(status) => status.matched,
+                Err(e) => {
+                    return Err(ClobError::Transport(format!(
+                        "order {} placed but its fill could not be read: {}",
+                        order_id, e
+                    ))
+                    .into())
+                }
+            },
+            // Read-only: nothing was placed
+            (None, None) => Decimal::ZERO,
+        };
+        if let (true, Some(order_id)) = (filled_size.is_zero(), &posted.order_id) {
+            return Err(ClobError::NoMatch(format!("order {} matched nothing", order_id)).into());
+        }
+
+        Ok(FillReport {
+            avg_price: posted.avg_price().unwrap_or(intent.price),
+            order_id: posted.order_id,
+            filled_size,
+            latency,
+        })
+    }
+}
+
+#[async_trait]
+impl ExecutionBackend for LiveBackend {
+    fn name(&self) -> &'static str {
+        "live"
+    }
+
+    async fn usdc_balance(&self) -> Result<Decimal> {
+        match &self.balances {
+            Some(tracker) if tracker.block() > 0 => Ok(tracker.usdc()),
+            _ => self.api.get_usdc_balance().await,
+        }
+    }
+
+    async fn ensure_trading_ready(&self, required_usdc: u128) -> Result<()> {
+        self.clob.ensure_trading_ready(required_usdc).await
+    }
+
+    async fn submit(
+        &self,
+        intent: &OrderIntent,
+        order: ClobOrder,
+        sig: Signature,
+    ) -> Result<FillReport> {
+        let (posted, latency) = self
+            .clob
+            .post_order(order, sig, &self.proxy_wallet, Some(OrderType::Fak))
+            .await?;
+
+        self.fill_report(intent, posted, latency).await
+    }
+
+    /// One `/orders` request per [`MAX_BATCH_ORDERS`]
+    async fn submit_batch(
+        &self,
+        orders: Vec<(&OrderIntent, ClobOrder, Signature)>,
+    ) -> Result<Vec<Result<FillReport>>> {
+        let mut reports = Vec::with_capacity(orders.len());
+
+        let mut orders = orders.into_iter().peekable();
+        while orders.peek().is_some() {
+            let chunk: Vec<_> = orders.by_ref().take(MAX_BATCH_ORDERS).collect();
+            let intents: Vec<&OrderIntent> = chunk.iter().map(|(intent, _, _)| *intent).collect();
+            let signed = chunk
+                .into_iter()
+                .map(|(_, order, sig)| (order, sig, Some(OrderType::Fak)))
+                .collect();
+
+            let (results, latency) = match self.clob.post_orders(signed, &self.proxy_wallet).await {
+                Ok(posted) => posted,
+                Err(e) if reports.is_empty() => return Err(e),
+                // Earlier chunks are already out: fail only this one's orders
+                Err(e) => {
+                    reports.extend(intents.iter().map(|_| Err(ClobError::copy(&e))));
+                    continue;
+                }
+            };
+
+            for (intent, result) in intents.into_iter().zip(results) {
+                let report = match result {
+                    Ok(posted) => self.fill_report(intent, posted, latency).await,
+                    Err(e) => Err(e),
+                };
+                reports.push(report);
+            }
+        }
+
+        Ok(reports)
+    }
+
+    async fn rest(
+        &self,
+        _intent: &OrderIntent,
+        order: ClobOrder,
+        sig: Signature,
+    ) -> Result<Option<String>> {
+        let (posted, _) = self
+            .clob
+            .post_order(order, sig, &self.proxy_wallet, Some(OrderType::Gtc))
+            .await?;
+        Ok(posted.order_id)
+    }
+
+    async fn order_status(&self, order_id: &str) -> Result<OrderStatus> {
+        self.clob.get_order(order_id).await
+    }
+
+    async fn cancel(&self, order_id: &str) -> Result<()> {
+        self.clob.cancel_order(order_id).await
+    }
+
+    async fn cancel_all(&self) -> Result<()> {
+        self.clob.cancel_all().await
+    }
+}
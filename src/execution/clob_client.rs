@@ -1,14 +1,17 @@
+use crate::execution::backend::OrderStatus;
+use crate::execution::errors::ClobError;
+use crate::execution::latency::SubmitLatency;
 use anyhow::{anyhow, Result};
 use ethers::prelude::*;
 use ethers::types::{Address, U256};
-use hmac::{Hmac, Mac};
 use log::{info, warn};
-use reqwest::Client;
+use crate::http::HttpClient;
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
-use sha2::Sha256;
 use std::str::FromStr;
 use std::sync::Arc;
-use std::time::{SystemTime, UNIX_EPOCH};
+use tokio_util::sync::CancellationToken;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 // ==================================================
 // CONSTANTS (Polygon / Polymarket)
@@ -20,13 +23,146 @@ const USDC_ADDRESS: &str = "0x2791Bca1f2de4661ED88A30C99A7a9449Aa84174";
 const MIN_ALLOWANCE: u128 = 1_000_000; // $1 (6 decimals)
 const CLOB_API_URL: &str = "https://clob.polymarket.com";
 
+/// Most signed orders the CLOB accepts in one `/orders` request
+pub const MAX_BATCH_ORDERS: usize = 15;
+
+// ==================================================
+// ORDER TYPES
+// ==================================================
+
+/// How long a posted order may stay on the book
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderType {
+    /// Good till cancelled: rests until filled or cancelled
+    Gtc,
+    /// Fill or kill: all at once or nothing
+    Fok,
+    /// Fill and kill: whatever crosses now, the rest is cancelled
+    Fak,
+}
+
+impl OrderType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OrderType::Gtc => "GTC",
+            OrderType::Fok => "FOK",
+            OrderType::Fak => "FAK",
+        }
+    }
+}
+
+/// What the CLOB said about an order it accepted
+#[derive(Debug, Clone, Default)]
+pub struct PostedOrder {
+    pub order_id: Option<String>,
+    /// "matched", "live", "delayed" or "unmatched"
+    pub status: Option<String>,
+    /// Shares matched on arrival, when the response says
+    pub matched: Option<Decimal>,
+    /// USDC those shares traded for
+    pub notional: Option<Decimal>,
+}
+
+impl PostedOrder {
+    /// Read an order's entry in a `/order` or `/orders` response. The
+    /// maker side gives up `makingAmount` for `takingAmount`: USDC for
+    /// shares on a BUY (`side` 0), shares for USDC on a SELL.
+    fn from_response(v: &serde_json::Value, side: u8) -> Self {
+        let text = |key: &str| v.get(key).and_then(|x| x.as_str()).map(str::to_string);
+        let amount = |key: &str| -> Option<Decimal> {
+            match v.get(key)? {
+                serde_json::Value::String(s) => s.parse().ok(),
+                serde_json::Value::Number(n) => n.to_string().parse().ok(),
+                _ => None,
+            }
+        };
+
+        let (making, taking) = (amount("makingAmount"), amount("takingAmount"));
+        let (shares, usdc) = if side == 0 { (taking, making) } else { (making, taking) };
+        let status = text("status").map(|s| s.to_ascii_lowercase());
+
+        let matched = match status.as_deref() {
+            Some("unmatched") => Some(Decimal::ZERO),
+            Some("matched") => shares,
+            // Resting or still queued: only a positive amount is a fill
+            _ => shares.filter(|s| *s > Decimal::ZERO),
+        };
+
+        Self {
+            order_id: text("orderID").or_else(|| text("order_id")).filter(|id| !id.is_empty()),
+            status,
+            matched,
+            notional: usdc,
+        }
+    }
+
+    /// Volume-weighted price of what matched
+    pub fn avg_price(&self) -> Option<Decimal> {
+        match (self.matched, self.notional) {
+            (Some(shares), Some(usdc)) if shares > Decimal::ZERO => Some(usdc / shares),
+            _ => None,
+        }
+    }
+}
+
+/// Polymarket CLOB API order format
+#[derive(Serialize, Debug)]
+struct ClobOrderPayload {
+    salt: String,
+    maker: String,
+    signer: String,
+    taker: String,
+    
+    #[serde(rename = "tokenId")]
+    token_id: String,
+    
+    #[serde(rename = "makerAmount")]
+    maker_amount: String,
+    
+    #[serde(rename = "takerAmount")]
+    taker_amount: String,
+    
+    side: String,
+    
+    #[serde(rename = "feeRateBps")]
+    fee_rate_bps: String,
+    
+    nonce: String,
+    expiration: String,
+    signature: String,
+    
+    #[serde(rename = "signatureType")]
+    signature_type: u8,
+
+    #[serde(rename = "orderType", skip_serializing_if = "Option::is_none")]
+    order_type: Option<&'static str>,
+}
+
+/// One entry of a `/orders` batch
+#[derive(Serialize, Debug)]
+struct BatchOrderPayload {
+    order: ClobOrderPayload,
+    owner: String,
+    #[serde(rename = "orderType")]
+    order_type: &'static str,
+}
+
+/// Per-order result of a `/orders` batch, in submission order
+#[derive(Deserialize, Debug)]
+struct BatchOrderResponse {
+    #[serde(default)]
+    success: bool,
+    #[serde(default, alias = "errorMsg")]
+    error_msg: Option<String>,
+}
+
 // ==================================================
 // CLIENT (WITH API CREDENTIALS)
 // ==================================================
 
 #[derive(Clone)]
 pub struct ClobClient {
-    pub http: Client,
+    pub http: HttpClient,
     provider: Arc<SignerMiddleware<Provider<Http>, LocalWallet>>,
     proxy_wallet: Address,
     read_only: bool,
@@ -65,8 +201,10 @@ impl ClobClient {
             warn!("⚠️  READ-ONLY MODE ENABLED - No real orders will be submitted");
         }
 
+        let http = HttpClient::new()?;
+
         Ok(Self {
-            http: Client::new(),
+            http,
             provider: signer,
             proxy_wallet: Address::from_str(proxy_wallet)?,
             read_only,
@@ -208,11 +346,6 @@ impl ClobClient {
         body: &str,
     ) -> String {
         let message = format!("{}{}{}{}", timestamp, method, path, body);
-        eprintln!("DEBUG HMAC - Timestamp: {}", timestamp);
-        eprintln!("DEBUG HMAC - Method: {}", method);
-        eprintln!("DEBUG HMAC - Path: {}", path);
-        eprintln!("DEBUG HMAC - Body length: {}", body.len());
-        eprintln!("DEBUG HMAC - Message: {}", message);
         use base64::{Engine as _, engine::general_purpose};
         use hmac::{Hmac, Mac};
         use sha2::Sha256;
@@ -229,7 +362,26 @@ impl ClobClient {
         let result = mac.finalize();
         let code_bytes = result.into_bytes();
 
-        general_purpose::URL_SAFE.encode(&code_bytes)
+        general_purpose::URL_SAFE.encode(code_bytes)
+    }
+
+    /// L2 auth headers for an API key request
+    fn with_auth(
+        &self,
+        req: reqwest::RequestBuilder,
+        method: &str,
+        path: &str,
+        body: &str,
+    ) -> Result<reqwest::RequestBuilder> {
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        let signature = self.generate_hmac_signature(timestamp, method, path, body);
+
+        Ok(req
+            .header("POLY-ADDRESS", &self.eoa_address)
+            .header("POLY-API-KEY", &self.api_key)
+            .header("POLY-SIGNATURE", signature)
+            .header("POLY-TIMESTAMP", timestamp.to_string())
+            .header("POLY-PASSPHRASE", &self.api_passphrase))
     }
 
     // ==================================================
@@ -241,7 +393,22 @@ impl ClobClient {
         order: crate::wallet::signer::ClobOrder,
         sig: Signature,
         proxy: &str,
-    ) -> Result<()> {
+    ) -> Result<Option<String>> {
+        let (posted, _) = self.post_order(order, sig, proxy, None).await?;
+        Ok(posted.order_id)
+    }
+
+    /// Post a signed order. `order_type` None leaves it to the CLOB default.
+    /// Returns what the CLOB said about it and how long the round trip took.
+    pub async fn post_order(
+        &self,
+        order: crate::wallet::signer::ClobOrder,
+        sig: Signature,
+        proxy: &str,
+        order_type: Option<OrderType>,
+    ) -> Result<(PostedOrder, SubmitLatency)> {
+        let started = Instant::now();
+
         if self.read_only {
             info!("📝 [READ-ONLY] Would submit order:");
             info!("   Token: 0x{}", hex::encode(order.token_id.as_bytes()));
@@ -251,140 +418,308 @@ impl ClobClient {
                 order.maker_amount.as_u128() as f64 / 1_000_000.0
             );
             info!("   Taker Amount: {:.6}", order.taker_amount.as_u128() as f64 / 1_000_000.0);
-            return Ok(());
-        }
-
-        // Polymarket CLOB API order format
-        #[derive(Serialize, Debug)]
-        struct ClobOrderPayload {
-            salt: String,
-            maker: String,
-            signer: String,
-            taker: String,
-            
-            #[serde(rename = "tokenId")]
-            token_id: String,
-            
-            #[serde(rename = "makerAmount")]
-            maker_amount: String,
-            
-            #[serde(rename = "takerAmount")]
-            taker_amount: String,
-            
-            side: String,
-            
-            #[serde(rename = "feeRateBps")]
-            fee_rate_bps: String,
-            
-            nonce: String,
-            expiration: String,
-            signature: String,
-            
-            #[serde(rename = "signatureType")]
-            signature_type: u8,
+            return Ok((PostedOrder::default(), SubmitLatency::default()));
         }
 
-        // Generate random salt
-        use ::rand::Rng;  // Use external rand crate explicitly
-        let salt = ::rand::random::<u64>().to_string();
-        
-        // Use the amounts from the order (already calculated)
-        let maker_amount = format!("{}", order.maker_amount.as_u128());
-        let taker_amount = format!("{}", order.taker_amount.as_u128());
-        
-        let payload = ClobOrderPayload {
-            salt,
-            maker: proxy.to_string(),
-            signer: self.eoa_address.clone(),
-            taker: "0x0000000000000000000000000000000000000000".to_string(),
-            token_id: format!("{:#x}", order.token_id),
-            maker_amount,
-            taker_amount,
-            side: if order.side == 0 { "BUY" } else { "SELL" }.to_string(),
-            fee_rate_bps: "0".to_string(),
-            nonce: order.nonce.to_string(),
-            expiration: order.expiration.to_string(),
-            signature: {
-                // Manually construct signature bytes: r (32) + s (32) + v (1) = 65 bytes
-                let mut sig_bytes = [0u8; 65];
-                sig.r.to_big_endian(&mut sig_bytes[0..32]);
-                sig.s.to_big_endian(&mut sig_bytes[32..64]);
-                sig_bytes[64] = sig.v as u8;
-                format!("0x{}", hex::encode(&sig_bytes))
-            },
-            signature_type: 0,  // 0 = EOA (MetaMask)
-        };
+        let payload = order_payload(&order, &sig, proxy, &self.eoa_address, order_type);
 
         info!("📤 Submitting order to CLOB API...");
         info!("   Token: {}", &payload.token_id[..16]);
         info!("   {} maker={} taker={}", payload.side, payload.maker_amount, payload.taker_amount);
 
-        // Generate authentication headers
-        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
-
         let path = "/order";
         let body = serde_json::to_string(&payload)?;
-        let signature = self.generate_hmac_signature(timestamp, "POST", path, &body);
-
         let url = format!("{}{}", CLOB_API_URL, path);
 
         // Make authenticated request
         let resp = self
-            .http
-            .post(&url)
-            .header("POLY-ADDRESS", &self.eoa_address)
-            .header("POLY-API-KEY", &self.api_key)
-            .header("POLY-SIGNATURE", &signature)
-            .header("POLY-TIMESTAMP", timestamp.to_string())
-            .header("POLY-PASSPHRASE", &self.api_passphrase)
+            .with_auth(self.http.post(&url), "POST", path, &body)?
             .header("Content-Type", "application/json")
             .body(body)
             .timeout(std::time::Duration::from_secs(10));
-        
-        // Debug: print headers being sent
-        eprintln!("=== RUST HEADERS ===");
-        eprintln!("POLY-ADDRESS: {}", self.eoa_address);
-        eprintln!("POLY-API-KEY: {}", self.api_key);
-        eprintln!("POLY-SIGNATURE: {}", signature);
-        eprintln!("POLY-TIMESTAMP: {}", timestamp);
-        eprintln!("POLY-PASSPHRASE: {}", self.api_passphrase);
-        
-        let resp = resp.send().await?;
+        let resp = self.http.send(resp).await.map_err(ClobError::from_send)?;
+        let sent = started.elapsed();
 
         let status = resp.status();
-        let body = resp.text().await?;
+        let body = resp.text().await.map_err(|e| ClobError::Transport(e.to_string()))?;
 
         if !status.is_success() {
             warn!("❌ Order rejected by CLOB API");
             warn!("   Status: {}", status);
             warn!("   Response: {}", body);
-            return Err(anyhow!("Order rejected: {} - {}", status, body));
+            return Err(ClobError::from_response(status.as_u16(), &body).into());
         }
 
-        // Parse response to get order ID
-        #[derive(Deserialize)]
-        struct OrderResponse {
-            
-            order_id: Option<String>,
-            success: Option<bool>,
+        // Parse response to get order ID and what matched
+        let posted = serde_json::from_str::<serde_json::Value>(&body)
+            .map(|v| PostedOrder::from_response(&v, order.side))
+            .unwrap_or_default();
+
+        match &posted.order_id {
+            Some(order_id) => info!(
+                "✅ Order submitted! ID: {} ({}, matched {:?})",
+                order_id,
+                posted.status.as_deref().unwrap_or("?"),
+                posted.matched
+            ),
+            None => info!("✅ Order submitted! {}", body),
+        }
+
+        let latency = SubmitLatency {
+            send: sent,
+            ack: started.elapsed() - sent,
+        };
+        Ok((posted, latency))
+    }
+
+    /// Submit up to [`MAX_BATCH_ORDERS`] signed orders in one authenticated
+    /// request. The request succeeds or fails as a whole; each order is then
+    /// accepted or rejected on its own, reported in submission order.
+    pub async fn post_orders(
+        &self,
+        orders: Vec<(crate::wallet::signer::ClobOrder, Signature, Option<OrderType>)>,
+        proxy: &str,
+    ) -> Result<(Vec<Result<PostedOrder>>, SubmitLatency)> {
+        if orders.len() > MAX_BATCH_ORDERS {
+            return Err(anyhow!(
+                "Batch of {} orders exceeds the limit of {}",
+                orders.len(),
+                MAX_BATCH_ORDERS
+            ));
+        }
+
+        let started = Instant::now();
+        let count = orders.len();
+
+        if self.read_only {
+            info!("📝 [READ-ONLY] Would submit a batch of {} orders:", count);
+            for (order, _, _) in &orders {
+                info!(
+                    "   {} 0x{} maker={:.6} taker={:.6}",
+                    if order.side == 0 { "BUY" } else { "SELL" },
+                    hex::encode(order.token_id.as_bytes()),
+                    order.maker_amount.as_u128() as f64 / 1_000_000.0,
+                    order.taker_amount.as_u128() as f64 / 1_000_000.0
+                );
+            }
+            let posted = (0..count).map(|_| Ok(PostedOrder::default())).collect();
+            return Ok((posted, SubmitLatency::default()));
+        }
+
+        let payload: Vec<BatchOrderPayload> = orders
+            .iter()
+            .map(|(order, sig, order_type)| BatchOrderPayload {
+                order: order_payload(order, sig, proxy, &self.eoa_address, None),
+                owner: self.api_key.clone(),
+                order_type: order_type.unwrap_or(OrderType::Gtc).as_str(),
+            })
+            .collect();
+
+        info!("📤 Submitting batch of {} orders to CLOB API...", count);
+        for entry in &payload {
+            info!(
+                "   {} {} maker={} taker={}",
+                entry.order.side,
+                &entry.order.token_id[..16],
+                entry.order.maker_amount,
+                entry.order.taker_amount
+            );
         }
 
-        match serde_json::from_str::<OrderResponse>(&body) {
-            Ok(resp) => {
-                if let Some(order_id) = resp.order_id {
-                    info!("✅ Order submitted! ID: {}", order_id);
+        let path = "/orders";
+        let body = serde_json::to_string(&payload)?;
+        let url = format!("{}{}", CLOB_API_URL, path);
+
+        let req = self
+            .with_auth(self.http.post(&url), "POST", path, &body)?
+            .header("Content-Type", "application/json")
+            .body(body)
+            .timeout(std::time::Duration::from_secs(10));
+        let resp = self.http.send(req).await.map_err(ClobError::from_send)?;
+        let sent = started.elapsed();
+
+        let status = resp.status();
+        let body = resp.text().await.map_err(|e| ClobError::Transport(e.to_string()))?;
+
+        if !status.is_success() {
+            warn!("❌ Order batch rejected by CLOB API");
+            warn!("   Status: {}", status);
+            warn!("   Response: {}", body);
+            return Err(ClobError::from_response(status.as_u16(), &body).into());
+        }
+
+        let responses: Vec<serde_json::Value> = serde_json::from_str(&body)
+            .map_err(|e| {
+                ClobError::Transport(format!("unreadable batch response ({}): {}", e, body))
+            })?;
+
+        let mut responses = responses.into_iter();
+        let results: Vec<Result<PostedOrder>> = orders
+            .iter()
+            .enumerate()
+            .map(|(i, (order, _, _))| {
+                let Some(v) = responses.next() else {
+                    let missing = format!("no result for batch order {}", i + 1);
+                    return Err(ClobError::Transport(missing).into());
+                };
+                let r: BatchOrderResponse = serde_json::from_value(v.clone())
+                    .unwrap_or(BatchOrderResponse { success: false, error_msg: None });
+
+                if r.success {
+                    let posted = PostedOrder::from_response(&v, order.side);
+                    info!(
+                        "✅ Batch order {} submitted! ID: {:?} ({}, matched {:?})",
+                        i + 1,
+                        posted.order_id,
+                        posted.status.as_deref().unwrap_or("?"),
+                        posted.matched
+                    );
+                    Ok(posted)
                 } else {
-                    info!("✅ Order submitted! {}", body);
+                    let reason = r.error_msg.unwrap_or_default();
+                    warn!("❌ Batch order {} rejected: {}", i + 1, reason);
+                    Err(ClobError::from_message(&reason).into())
                 }
-            }
-            Err(_) => {
-                info!("✅ Order submitted! {}", body);
-            }
+            })
+            .collect();
+
+        let latency = SubmitLatency {
+            send: sent,
+            ack: started.elapsed() - sent,
+        };
+        Ok((results, latency))
+    }
+
+    // ==================================================
+    // ORDER LIFECYCLE
+    // ==================================================
+
+    /// Where a posted order stands: matched size and whether it still rests
+    pub async fn get_order(&self, order_id: &str) -> Result<OrderStatus> {
+        let path = format!("/data/order/{}", order_id);
+        let url = format!("{}{}", CLOB_API_URL, path);
+
+        let req = self
+            .with_auth(self.http.get(&url), "GET", &path, "")?
+            .timeout(std::time::Duration::from_secs(10));
+        let resp = self.http.send(req).await?;
+
+        let status = resp.status();
+        let body = resp.text().await?;
+
+        if !status.is_success() {
+            return Err(ClobError::from_response(status.as_u16(), &body).into());
+        }
+
+        #[derive(Deserialize)]
+        struct OpenOrder {
+            status: String,
+            size_matched: String,
+        }
+
+        let open: OpenOrder = serde_json::from_str(&body)
+            .map_err(|e| anyhow!("Unexpected order response ({}): {}", e, body))?;
+
+        Ok(OrderStatus {
+            order_id: order_id.to_string(),
+            matched: open.size_matched.parse().unwrap_or_default(),
+            live: open.status.to_ascii_uppercase().contains("LIVE"),
+        })
+    }
+
+    /// Cancel one resting order
+    pub async fn cancel_order(&self, order_id: &str) -> Result<()> {
+        if self.read_only {
+            info!("📝 [READ-ONLY] Would cancel order {}", order_id);
+            return Ok(());
+        }
+
+        let path = "/order";
+        let body = serde_json::json!({ "orderID": order_id }).to_string();
+        let url = format!("{}{}", CLOB_API_URL, path);
+
+        let req = self
+            .with_auth(self.http.delete(&url), "DELETE", path, &body)?
+            .header("Content-Type", "application/json")
+            .body(body)
+            .timeout(std::time::Duration::from_secs(10));
+        let resp = self.http.send(req).await?;
+
+        let status = resp.status();
+        let body = resp.text().await?;
+
+        if !status.is_success() {
+            return Err(ClobError::from_response(status.as_u16(), &body).into());
+        }
+
+        info!("🧹 Cancelled order {}", order_id);
+        Ok(())
+    }
+
+    // ==================================================
+    // CANCEL ALL RESTING ORDERS
+    // ==================================================
+
+    pub async fn cancel_all(&self) -> Result<()> {
+        if self.read_only {
+            info!("📝 [READ-ONLY] Would cancel all resting orders");
+            return Ok(());
+        }
+
+        let path = "/cancel-all";
+        let url = format!("{}{}", CLOB_API_URL, path);
+
+        let req = self
+            .with_auth(self.http.delete(&url), "DELETE", path, "")?
+            .timeout(Duration::from_secs(10));
+        let resp = self.http.send(req).await?;
+
+        let status = resp.status();
+        let body = resp.text().await?;
+
+        if !status.is_success() {
+            return Err(ClobError::from_response(status.as_u16(), &body).into());
         }
 
+        info!("🧹 Cancelled all resting orders: {}", body);
         Ok(())
     }
 
+    // ==================================================
+    // CONNECTION WARM-UP
+    // ==================================================
+
+    /// Round trip to the CLOB over the pooled connection
+    pub async fn ping(&self) -> Result<Duration> {
+        let started = Instant::now();
+        let req = self
+            .http
+            .get(&format!("{}/time", CLOB_API_URL))
+            .timeout(Duration::from_secs(5));
+        self.http.send(req).await?.error_for_status()?;
+        Ok(started.elapsed())
+    }
+
+    /// Ping every `interval` so the pooled connection is open when an
+    /// order needs it.
+    pub async fn keep_warm(&self, interval: Duration, cancel: CancellationToken) {
+        match self.ping().await {
+            Ok(rtt) => info!("🔥 CLOB connection warm ({:.1}ms)", rtt.as_secs_f64() * 1000.0),
+            Err(e) => warn!("⚠️  CLOB warm-up ping failed: {}", e),
+        }
+
+        loop {
+            tokio::select! {
+                _ = cancel.cancelled() => break,
+                _ = tokio::time::sleep(interval) => {}
+            }
+
+            if let Err(e) = self.ping().await {
+                warn!("⚠️  CLOB keep-alive ping failed: {}", e);
+            }
+        }
+    }
+
     // ==================================================
     // STUBS FOR FUTURE
     // ==================================================
@@ -433,6 +768,44 @@ abigen!(
     ]"#
 );
 
+/// Wire format of a signed order. Every field is the one that was signed,
+/// so the CLOB's order id is `order.order_id()`.
+fn order_payload(
+    order: &crate::wallet::signer::ClobOrder,
+    sig: &Signature,
+    maker: &str,
+    signer: &str,
+    order_type: Option<OrderType>,
+) -> ClobOrderPayload {
+    // Use the amounts from the order (already calculated)
+    let maker_amount = format!("{}", order.maker_amount.as_u128());
+    let taker_amount = format!("{}", order.taker_amount.as_u128());
+
+    ClobOrderPayload {
+        salt: order.salt.to_string(),
+        maker: maker.to_string(),
+        signer: signer.to_string(),
+        taker: "0x0000000000000000000000000000000000000000".to_string(),
+        token_id: format!("{:#x}", order.token_id),
+        maker_amount,
+        taker_amount,
+        side: if order.side == 0 { "BUY" } else { "SELL" }.to_string(),
+        fee_rate_bps: order.fee_rate_bps.to_string(),
+        nonce: order.nonce.to_string(),
+        expiration: order.expiration.to_string(),
+        signature: {
+            // Manually construct signature bytes: r (32) + s (32) + v (1) = 65 bytes
+            let mut sig_bytes = [0u8; 65];
+            sig.r.to_big_endian(&mut sig_bytes[0..32]);
+            sig.s.to_big_endian(&mut sig_bytes[32..64]);
+            sig_bytes[64] = sig.v as u8;
+            format!("0x{}", hex::encode(sig_bytes))
+        },
+        signature_type: 0, // 0 = EOA (MetaMask)
+        order_type: order_type.map(|t| t.as_str()),
+    }
+}
+
 abigen!(
     CTFContract,
     r#"[
@@ -440,3 +813,89 @@ abigen!(
         function setApprovalForAll(address,bool)
     ]"#
 );
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn reads_matched_amounts() {
+        let posted = |body: &str, side: u8| {
+            PostedOrder::from_response(&serde_json::from_str(body).unwrap(), side)
+        };
+
+        let buy = posted(
+            r#"{"success":true,"orderID":"0x1","status":"matched",
+                "makingAmount":"4.5","takingAmount":"10"}"#,
+            0,
+        );
+        assert_eq!(buy.order_id.as_deref(), Some("0x1"));
+        assert_eq!(buy.matched, Some(dec!(10)));
+        assert_eq!(buy.avg_price(), Some(dec!(0.45)));
+
+        let sell = posted(
+            r#"{"orderID":"0x2","status":"matched","makingAmount":"10","takingAmount":"4.4"}"#,
+            1,
+        );
+        assert_eq!(sell.matched, Some(dec!(10)));
+        assert_eq!(sell.avg_price(), Some(dec!(0.44)));
+
+        let killed = posted(r#"{"orderID":"0x3","status":"unmatched"}"#, 0);
+        assert_eq!(killed.matched, Some(Decimal::ZERO));
+
+        // Resting or delayed without a fill: ask the order itself
+        let resting = posted(
+            r#"{"orderID":"0x4","status":"live","makingAmount":"","takingAmount":""}"#,
+            0,
+        );
+        assert_eq!(resting.matched, None);
+        let delayed = posted(r#"{"orderID":"0x5","status":"delayed"}"#, 0);
+        assert_eq!(delayed.matched, None);
+    }
+
+    #[test]
+    fn payload_carries_the_signed_order() {
+        use crate::wallet::signer::ClobOrder;
+
+        let order = ClobOrder {
+            salt: U256::from(123_456_789u64),
+            maker: Address::repeat_byte(1),
+            signer: Address::repeat_byte(2),
+            taker: Address::zero(),
+            token_id: H256::repeat_byte(7),
+            maker_amount: U256::from(4_500_000),
+            taker_amount: U256::from(10_000_000),
+            side: 0,
+            fee_rate_bps: U256::from(100),
+            nonce: U256::from(9),
+            expiration: U256::from(1_800_000_000u64),
+        };
+        let sig = Signature {
+            r: U256::one(),
+            s: U256::one(),
+            v: 27,
+        };
+        let maker = format!("{:?}", order.maker);
+        let signer = format!("{:?}", order.signer);
+
+        let p = order_payload(&order, &sig, &maker, &signer, Some(OrderType::Fak));
+        assert_eq!(p.salt, order.salt.to_string());
+
+        // The order the CLOB rebuilds from the payload hashes to our id
+        let sent = ClobOrder {
+            salt: U256::from_dec_str(&p.salt).unwrap(),
+            maker: p.maker.parse().unwrap(),
+            signer: p.signer.parse().unwrap(),
+            taker: p.taker.parse().unwrap(),
+            token_id: p.token_id.parse().unwrap(),
+            maker_amount: U256::from_dec_str(&p.maker_amount).unwrap(),
+            taker_amount: U256::from_dec_str(&p.taker_amount).unwrap(),
+            side: if p.side == "BUY" { 0 } else { 1 },
+            fee_rate_bps: U256::from_dec_str(&p.fee_rate_bps).unwrap(),
+            nonce: U256::from_dec_str(&p.nonce).unwrap(),
+            expiration: U256::from_dec_str(&p.expiration).unwrap(),
+        };
+        assert_eq!(sent.order_id(), order.order_id());
+    }
+}
@@ -1,28 +1,348 @@
+use crate::http::Throttled;
 use std::fmt;
+use std::time::Duration;
 
-#[derive(Debug)]
-pub enum ExecutionError {
-    Rejected(String),
-    PartialFill { filled: f64, remaining: f64 },
-    RetryLimitExceeded,
-    InvalidSize,
+// ==================================================
+// CLOB ERRORS
+// ==================================================
+
+/// Why the CLOB refused a request, parsed from its status and error payload
+#[derive(Debug, Clone, PartialEq)]
+pub enum ClobError {
+    /// Not enough USDC (or shares, for a sell), or the allowance is missing
+    InsufficientBalance(String),
+    /// Price is not on the market's tick grid
+    InvalidTick(String),
+    /// Size under the market minimum or amounts that don't add up
+    InvalidSize(String),
+    /// Market closed, resolved, paused or unknown to the CLOB
+    MarketClosed(String),
+    /// The order's EIP-712 signature does not check out
+    InvalidSignature(String),
+    /// API key, passphrase or HMAC rejected
+    Unauthorized(String),
+    /// A FOK/FAK order found nothing to match at its price
+    NoMatch(String),
+    /// Our own limiter held the request back: it was never sent
+    RateLimited { retry_after: Option<Duration> },
+    /// The exchange turned the request away before matching (425, 429, 503)
+    Unavailable { status: u16, message: String },
+    /// A gateway gave up on the exchange (502, 504): the order may still
+    /// have reached the matching engine
+    Gateway { status: u16, message: String },
+    /// No usable response: the order may or may not have reached the book
+    Transport(String),
+    /// Anything else the CLOB turned down
+    Rejected {
+        status: Option<u16>,
+        message: String,
+    },
+}
+
+impl ClobError {
+    /// Classify a non-success response. The CLOB reports `{"error": ..}`,
+    /// batch entries `{"errorMsg": ..}`; anything else is taken verbatim.
+    pub fn from_response(status: u16, body: &str) -> Self {
+        let message = serde_json::from_str::<serde_json::Value>(body)
+            .ok()
+            .and_then(|v| {
+                ["error", "errorMsg", "message"]
+                    .iter()
+                    .find_map(|k| v.get(*k)?.as_str().map(str::to_string))
+            })
+            .unwrap_or_else(|| body.trim().to_string());
+
+        match status {
+            425 | 429 | 503 => ClobError::Unavailable { status, message },
+            502 | 504 => ClobError::Gateway { status, message },
+            401 | 403 if !message.to_ascii_lowercase().contains("signature") => {
+                ClobError::Unauthorized(message)
+            }
+            _ => Self::classify(Some(status), message),
+        }
+    }
+
+    /// Classify a per-order rejection inside an accepted batch
+    pub fn from_message(message: &str) -> Self {
+        Self::classify(None, message.to_string())
+    }
+
+    /// The request never got an answer. Our limiter refusing to send is a
+    /// rate limit; everything else leaves the outcome unknown.
+    pub fn from_send(e: anyhow::Error) -> Self {
+        match e.downcast_ref::<Throttled>() {
+            Some(t) => ClobError::RateLimited {
+                retry_after: Some(t.wait),
+            },
+            None => ClobError::Transport(e.to_string()),
+        }
+    }
+
+    fn classify(status: Option<u16>, message: String) -> Self {
+        let m = message.to_ascii_lowercase();
+
+        if m.contains("not enough balance") || m.contains("allowance") || m.contains("insufficient")
+        {
+            ClobError::InsufficientBalance(message)
+        } else if m.contains("tick") {
+            ClobError::InvalidTick(message)
+        } else if m.contains("min size")
+            || m.contains("minimum size")
+            || m.contains("lower than the minimum")
+            || m.contains("invalid amount")
+        {
+            ClobError::InvalidSize(message)
+        } else if m.contains("closed")
+            || m.contains("not accepting")
+            || m.contains("does not exist")
+            || m.contains("market not found")
+            || m.contains("paused")
+        {
+            ClobError::MarketClosed(message)
+        } else if m.contains("signature") {
+            ClobError::InvalidSignature(message)
+        } else if m.contains("api key") || m.contains("unauthorized") {
+            ClobError::Unauthorized(message)
+        } else if m.contains("fully filled or killed")
+            || m.contains("couldn't be fully filled")
+            || m.contains("no orders found to match")
+            || m.contains("no match")
+        {
+            ClobError::NoMatch(message)
+        } else {
+            ClobError::Rejected { status, message }
+        }
+    }
+
+    /// Nothing was placed and the same request may succeed if sent again:
+    /// our limiter never sent it, or the exchange said "too early" (425)
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            ClobError::RateLimited { .. } | ClobError::Unavailable { status: 425, .. }
+        )
+    }
+
+    /// The order may be on the book despite the error
+    pub fn outcome_unknown(&self) -> bool {
+        matches!(self, ClobError::Gateway { .. } | ClobError::Transport(_))
+    }
+
+    /// Every further order fails the same way until someone intervenes
+    pub fn is_fatal(&self) -> bool {
+        matches!(
+            self,
+            ClobError::InvalidSignature(_) | ClobError::Unauthorized(_)
+        )
+    }
+
+    /// Stable name for logs and the journal
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ClobError::InsufficientBalance(_) => "insufficient_balance",
+            ClobError::InvalidTick(_) => "invalid_tick",
+            ClobError::InvalidSize(_) => "invalid_size",
+            ClobError::MarketClosed(_) => "market_closed",
+            ClobError::InvalidSignature(_) => "invalid_signature",
+            ClobError::Unauthorized(_) => "unauthorized",
+            ClobError::NoMatch(_) => "no_match",
+            ClobError::RateLimited { .. } => "rate_limited",
+            ClobError::Unavailable { .. } => "unavailable",
+            ClobError::Gateway { .. } => "gateway",
+            ClobError::Transport(_) => "transport",
+            ClobError::Rejected { .. } => "rejected",
+        }
+    }
+
+    /// The CLOB error inside `e`, if it came from the exchange
+    pub fn find(e: &anyhow::Error) -> Option<&ClobError> {
+        e.chain().find_map(|c| c.downcast_ref::<ClobError>())
+    }
+
+    /// A copy of `e` for each order one failed request took down, typed
+    /// when it came from the exchange
+    pub fn copy(e: &anyhow::Error) -> anyhow::Error {
+        match Self::find(e) {
+            Some(clob) => clob.clone().into(),
+            None => anyhow::anyhow!("{}", e),
+        }
+    }
 }
 
-impl fmt::Display for ExecutionError {
+impl fmt::Display for ClobError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            ExecutionError::Rejected(msg) => write!(f, "Order rejected: {}", msg),
-            ExecutionError::PartialFill { filled, remaining } => {
-                write!(
-                    f,
-                    "Partial fill: filled {}, remaining {}",
-                    filled, remaining
-                )
+            ClobError::InsufficientBalance(msg) => write!(f, "Insufficient balance: {}", msg),
+            ClobError::InvalidTick(msg) => write!(f, "Invalid tick: {}", msg),
+            ClobError::InvalidSize(msg) => write!(f, "Invalid size: {}", msg),
+            ClobError::MarketClosed(msg) => write!(f, "Market closed: {}", msg),
+            ClobError::InvalidSignature(msg) => write!(f, "Invalid signature: {}", msg),
+            ClobError::Unauthorized(msg) => write!(f, "Unauthorized: {}", msg),
+            ClobError::NoMatch(msg) => write!(f, "No match: {}", msg),
+            ClobError::RateLimited { retry_after } => match retry_after {
+                Some(wait) => write!(f, "Rate limited for {}ms", wait.as_millis()),
+                None => write!(f, "Rate limited"),
+            },
+            ClobError::Unavailable { status, message } => {
+                write!(f, "CLOB unavailable ({}): {}", status, message)
+            }
+            ClobError::Gateway { status, message } => {
+                write!(f, "CLOB gateway error ({}): {}", status, message)
             }
-            ExecutionError::RetryLimitExceeded => write!(f, "Retry limit exceeded"),
-            ExecutionError::InvalidSize => write!(f, "Invalid order size"),
+            ClobError::Transport(msg) => write!(f, "No response from CLOB: {}", msg),
+            ClobError::Rejected { status, message } => match status {
+                Some(status) => write!(f, "Order rejected: {} - {}", status, message),
+                None => write!(f, "Order rejected: {}", message),
+            },
         }
     }
 }
 
-impl std::error::Error for ExecutionError {}
+impl std::error::Error for ClobError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_responses() {
+        let unavailable = |status: u16, message: &str| ClobError::Unavailable {
+            status,
+            message: message.into(),
+        };
+        let gateway = |status: u16, message: &str| ClobError::Gateway {
+            status,
+            message: message.into(),
+        };
+
+        // (status, body, expected)
+        let cases = [
+            (
+                400,
+                r#"{"error":"not enough balance / allowance"}"#,
+                ClobError::InsufficientBalance("not enough balance / allowance".into()),
+            ),
+            (
+                400,
+                r#"{"error":"order 0x1 is invalid. Price breaks minimum tick size rules"}"#,
+                ClobError::InvalidTick(
+                    "order 0x1 is invalid. Price breaks minimum tick size rules".into(),
+                ),
+            ),
+            (
+                400,
+                r#"{"error":"Size (2) lower than the minimum: 5"}"#,
+                ClobError::InvalidSize("Size (2) lower than the minimum: 5".into()),
+            ),
+            (
+                400,
+                r#"{"errorMsg":"market is closed"}"#,
+                ClobError::MarketClosed("market is closed".into()),
+            ),
+            (
+                400,
+                r#"{"error":"order couldn't be fully filled. FOK orders are fully filled or killed."}"#,
+                ClobError::NoMatch(
+                    "order couldn't be fully filled. FOK orders are fully filled or killed.".into(),
+                ),
+            ),
+            (
+                401,
+                r#"{"error":"Unauthorized/Invalid api key"}"#,
+                ClobError::Unauthorized("Unauthorized/Invalid api key".into()),
+            ),
+            (
+                401,
+                r#"{"error":"invalid signature"}"#,
+                ClobError::InvalidSignature("invalid signature".into()),
+            ),
+            (425, "Too Early", unavailable(425, "Too Early")),
+            (
+                429,
+                r#"{"message":"slow down"}"#,
+                unavailable(429, "slow down"),
+            ),
+            (503, "trading paused", unavailable(503, "trading paused")),
+            (
+                502,
+                "<html>Bad Gateway</html>",
+                gateway(502, "<html>Bad Gateway</html>"),
+            ),
+            (504, " timeout ", gateway(504, "timeout")),
+            (
+                500,
+                r#"{"error":"something else"}"#,
+                ClobError::Rejected {
+                    status: Some(500),
+                    message: "something else".into(),
+                },
+            ),
+        ];
+
+        for (status, body, expected) in cases {
+            assert_eq!(
+                ClobError::from_response(status, body),
+                expected,
+                "{status} {body}"
+            );
+        }
+    }
+
+    #[test]
+    fn classifies_batch_messages() {
+        assert_eq!(
+            ClobError::from_message("no orders found to match with FAK order"),
+            ClobError::NoMatch("no orders found to match with FAK order".into())
+        );
+        assert_eq!(
+            ClobError::from_message("invalid amount for a marketable BUY order"),
+            ClobError::InvalidSize("invalid amount for a marketable BUY order".into())
+        );
+        assert_eq!(
+            ClobError::from_message("whatever"),
+            ClobError::Rejected {
+                status: None,
+                message: "whatever".into()
+            }
+        );
+    }
+
+    #[test]
+    fn only_unsent_or_too_early_orders_are_retried() {
+        let throttled = ClobError::from_send(
+            Throttled {
+                route: "POST /order".into(),
+                wait: Duration::from_millis(50),
+            }
+            .into(),
+        );
+        let transport = ClobError::from_send(anyhow::anyhow!("connection reset"));
+
+        // (name, error, retryable, outcome unknown)
+        let cases = [
+            ("own limiter", throttled, true, false),
+            ("too early", ClobError::from_response(425, ""), true, false),
+            (
+                "exchange 429",
+                ClobError::from_response(429, ""),
+                false,
+                false,
+            ),
+            ("503", ClobError::from_response(503, ""), false, false),
+            ("502", ClobError::from_response(502, ""), false, true),
+            ("504", ClobError::from_response(504, ""), false, true),
+            ("transport", transport, false, true),
+            (
+                "no match",
+                ClobError::from_message("no match"),
+                false,
+                false,
+            ),
+        ];
+
+        for (name, e, retryable, unknown) in cases {
+            assert_eq!(e.is_retryable(), retryable, "{name}");
+            assert_eq!(e.outcome_unknown(), unknown, "{name}");
+        }
+    }
+}
@@ -0,0 +1,58 @@
+use std::fmt;
+use std::time::Duration;
+
+/// Milliseconds, fractional
+pub fn ms(d: Duration) -> f64 {
+    d.as_secs_f64() * 1000.0
+}
+
+/// Round trip of one submitted order
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SubmitLatency {
+    /// Submission until the exchange's response arrived
+    pub send: Duration,
+    /// Reading and parsing that response
+    pub ack: Duration,
+}
+
+/// Wall time of each stage of one trade
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StageTimings {
+    /// Snapshot taken → opportunity reaches the trader
+    pub detect: Duration,
+    /// Balance refresh, sizing, reservation and readiness gate
+    pub size: Duration,
+    /// Signing both legs
+    pub sign: Duration,
+    /// Both legs in flight: the slower leg's send
+    pub send: Duration,
+    /// The slower leg's ack
+    pub ack: Duration,
+}
+
+impl StageTimings {
+    /// Both legs are sent together, so the trade waits on the slower one
+    pub fn record_legs(&mut self, legs: &[SubmitLatency]) {
+        self.send = legs.iter().map(|l| l.send).max().unwrap_or_default();
+        self.ack = legs.iter().map(|l| l.ack).max().unwrap_or_default();
+    }
+
+    pub fn total(&self) -> Duration {
+        self.detect + self.size + self.sign + self.send + self.ack
+    }
+}
+
+impl fmt::Display for StageTimings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "detect {:.1}ms | size {:.1}ms | sign {:.1}ms | send {:.1}ms | ack {:.1}ms | total {:.1}ms",
+            ms(self.detect),
+            ms(self.size),
+            ms(self.sign),
+            ms(self.send),
+            ms(self.ack),
+            ms(self.total())
+        )
+    }
+}
@@ -0,0 +1,307 @@
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+// ==================================================
+// SNAPSHOT
+// ==================================================
+
+/// Where the bot's USDC is at one moment
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CapitalSnapshot {
+    /// Wallet USDC: last balance read, less fills booked since
+    pub wallet: Decimal,
+    /// Held for orders that are out but not yet filled
+    pub reserved: Decimal,
+    /// Cost of open positions in live markets
+    pub in_positions: Decimal,
+    /// Cost of positions in closed markets, not yet redeemed
+    pub pending_redemption: Decimal,
+}
+
+impl CapitalSnapshot {
+    /// What a new trade may spend
+    pub fn available(&self) -> Decimal {
+        (self.wallet - self.reserved).max(Decimal::ZERO)
+    }
+}
+
+impl fmt::Display for CapitalSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "available ${} | reserved ${} | in positions ${} | pending redemption ${}",
+            self.available().round_dp(2),
+            self.reserved.round_dp(2),
+            self.in_positions.round_dp(2),
+            self.pending_redemption.round_dp(2)
+        )
+    }
+}
+
+// ==================================================
+// LEDGER
+// ==================================================
+
+struct Holding {
+    condition_id: String,
+    size: Decimal,
+    cost: Decimal,
+}
+
+#[derive(Default)]
+struct LedgerState {
+    wallet: Decimal,
+    /// Last balance read from the chain
+    last_read: Option<Decimal>,
+    /// Cost of fills booked that no balance read has shown yet
+    unsettled: Decimal,
+    next_id: u64,
+    /// Reservation id → USDC still held
+    reservations: HashMap<u64, Decimal>,
+    /// Token id → open position
+    holdings: HashMap<String, Holding>,
+    /// Condition id → cost awaiting redemption
+    pending: HashMap<String, Decimal>,
+}
+
+/// Capital accounting shared by every trade in flight.
+///
+/// The wallet balance alone oversubscribes: two opportunities sized off
+/// the same read both think they can spend it. Orders reserve first, fills
+/// move the reservation into positions, and closed markets wait in pending
+/// redemption until paid out. Clones share the same books.
+#[derive(Clone, Default)]
+pub struct CapitalLedger {
+    state: Arc<Mutex<LedgerState>>,
+}
+
+impl CapitalLedger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, LedgerState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Take a fresh wallet balance. Reservations and positions are kept.
+    ///
+    /// Fills settle on chain after they're booked, so the read may not show
+    /// them yet: their cost stays deducted until the balance drops by it.
+    pub fn sync_wallet(&self, usdc: Decimal) {
+        let mut state = self.lock();
+        if let Some(last) = state.last_read {
+            let spent = (last - usdc).max(Decimal::ZERO);
+            state.unsettled = (state.unsettled - spent).max(Decimal::ZERO);
+        }
+        state.last_read = Some(usdc);
+        state.wallet = usdc - state.unsettled;
+    }
+
+    pub fn snapshot(&self) -> CapitalSnapshot {
+        let state = self.lock();
+        CapitalSnapshot {
+            wallet: state.wallet,
+            reserved: state.reservations.values().copied().sum(),
+            in_positions: state.holdings.values().map(|h| h.cost).sum(),
+            pending_redemption: state.pending.values().copied().sum(),
+        }
+    }
+
+    pub fn available(&self) -> Decimal {
+        self.snapshot().available()
+    }
+
+    /// Hold `amount` for orders about to go out. None when it isn't
+    /// available, e.g. another trade reserved it first.
+    pub fn reserve(&self, amount: Decimal) -> Option<Reservation> {
+        let mut state = self.lock();
+        let reserved: Decimal = state.reservations.values().copied().sum();
+        if amount > state.wallet - reserved {
+            return None;
+        }
+
+        state.next_id += 1;
+        let id = state.next_id;
+        state.reservations.insert(id, amount);
+
+        Some(Reservation {
+            ledger: self.clone(),
+            id,
+        })
+    }
+
+    fn release(&self, id: u64) {
+        self.lock().reservations.remove(&id);
+    }
+
+    fn record_buy(
+        &self,
+        id: u64,
+        token_id: &str,
+        condition_id: &str,
+        size: Decimal,
+        cost: Decimal,
+    ) {
+        let mut state = self.lock();
+        if let Some(held) = state.reservations.get_mut(&id) {
+            *held = (*held - cost).max(Decimal::ZERO);
+        }
+        state.wallet -= cost;
+        state.unsettled += cost;
+
+        let holding = state
+            .holdings
+            .entry(token_id.to_string())
+            .or_insert_with(|| Holding {
+                condition_id: condition_id.to_string(),
+                size: Decimal::ZERO,
+                cost: Decimal::ZERO,
+            });
+        holding.size += size;
+        holding.cost += cost;
+    }
+
+    /// A sell filled: its share of the position's cost leaves, proceeds
+    /// come back to the wallet.
+    pub fn record_sell(&self, token_id: &str, size: Decimal, proceeds: Decimal) {
+        let mut state = self.lock();
+        state.wallet += proceeds;
+
+        let Some(holding) = state.holdings.get_mut(token_id) else {
+            return;
+        };
+        let sold = size.min(holding.size);
+        if holding.size > Decimal::ZERO {
+            holding.cost -= holding.cost * sold / holding.size;
+        }
+        holding.size -= sold;
+
+        if holding.size <= Decimal::ZERO {
+            state.holdings.remove(token_id);
+        }
+    }
+
+    /// The market closed: its positions now wait for redemption.
+    pub fn mark_pending(&self, condition_id: &str) {
+        let mut state = self.lock();
+        let tokens: Vec<String> = state
+            .holdings
+            .iter()
+            .filter(|(_, h)| h.condition_id == condition_id)
+            .map(|(t, _)| t.clone())
+            .collect();
+
+        let mut cost = Decimal::ZERO;
+        for token_id in tokens {
+            if let Some(h) = state.holdings.remove(&token_id) {
+                cost += h.cost;
+            }
+        }
+        if cost > Decimal::ZERO {
+            *state.pending.entry(condition_id.to_string()).or_default() += cost;
+        }
+    }
+
+    /// `condition_id` paid out `payout` USDC.
+    pub fn record_redemption(&self, condition_id: &str, payout: Decimal) {
+        let mut state = self.lock();
+        state.pending.remove(condition_id);
+        state.wallet += payout;
+    }
+}
+
+/// USDC held for one trade's orders. Fills draw it down; whatever is left
+/// is released when it drops.
+pub struct Reservation {
+    ledger: CapitalLedger,
+    id: u64,
+}
+
+impl Reservation {
+    /// A buy filled: `cost` USDC moves from the reservation into a position.
+    pub fn record_fill(&self, token_id: &str, condition_id: &str, size: Decimal, cost: Decimal) {
+        self.ledger
+            .record_buy(self.id, token_id, condition_id, size, cost);
+    }
+}
+
+impl Drop for Reservation {
+    fn drop(&mut self) {
+        self.ledger.release(self.id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn reservations_hold_until_filled_or_dropped() {
+        let ledger = CapitalLedger::new();
+        ledger.sync_wallet(dec!(100));
+
+        let trade = ledger.reserve(dec!(60)).unwrap();
+        assert!(ledger.reserve(dec!(50)).is_none());
+        assert_eq!(ledger.available(), dec!(40));
+
+        // A partial fill moves its cost into a position
+        trade.record_fill("eth-up", "eth", dec!(50), dec!(20));
+        let snap = ledger.snapshot();
+        assert_eq!(snap.wallet, dec!(80));
+        assert_eq!(snap.reserved, dec!(40));
+        assert_eq!(snap.in_positions, dec!(20));
+
+        // The unfilled rest goes back on drop
+        drop(trade);
+        assert_eq!(ledger.available(), dec!(80));
+    }
+
+    #[test]
+    fn sync_keeps_fills_the_balance_has_not_caught_up_with() {
+        let ledger = CapitalLedger::new();
+        ledger.sync_wallet(dec!(100));
+
+        let trade = ledger.reserve(dec!(30)).unwrap();
+        trade.record_fill("eth-up", "eth", dec!(75), dec!(30));
+        drop(trade);
+
+        // The read still shows the full balance: the fill isn't spent twice
+        ledger.sync_wallet(dec!(100));
+        assert_eq!(ledger.available(), dec!(70));
+
+        // Part settled, then all of it
+        ledger.sync_wallet(dec!(90));
+        assert_eq!(ledger.available(), dec!(70));
+        ledger.sync_wallet(dec!(70));
+        assert_eq!(ledger.available(), dec!(70));
+
+        // Later moves come straight from the chain
+        ledger.sync_wallet(dec!(65));
+        assert_eq!(ledger.available(), dec!(65));
+    }
+
+    #[test]
+    fn sells_and_redemptions_return_to_the_wallet() {
+        let ledger = CapitalLedger::new();
+        ledger.sync_wallet(dec!(100));
+        let trade = ledger.reserve(dec!(40)).unwrap();
+        trade.record_fill("eth-up", "eth", dec!(100), dec!(40));
+        drop(trade);
+
+        ledger.record_sell("eth-up", dec!(50), dec!(25));
+        let snap = ledger.snapshot();
+        assert_eq!(snap.wallet, dec!(85));
+        assert_eq!(snap.in_positions, dec!(20));
+
+        ledger.mark_pending("eth");
+        assert_eq!(ledger.snapshot().pending_redemption, dec!(20));
+        ledger.record_redemption("eth", dec!(50));
+        let snap = ledger.snapshot();
+        assert_eq!(snap.wallet, dec!(135));
+        assert_eq!(snap.pending_redemption, Decimal::ZERO);
+    }
+}
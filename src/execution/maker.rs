@@ -0,0 +1,959 @@
+use crate::cache::{Levels, PriceCache};
+use crate::domain::order::Side;
+use crate::domain::time::{seconds_remaining, TradingPhase};
+use crate::domain::{Market, OutcomeToken};
+use crate::execution::errors::ClobError;
+use crate::execution::ledger::Reservation;
+use crate::execution::Trader;
+use crate::journal::JournalEvent;
+use crate::rewards::{q_min, RewardParams};
+use crate::risk::{OrderIntent, RiskHold};
+use crate::scheduler::PreparedPeriod;
+use crate::strategy::fees::fee_per_share;
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use log::{info, warn};
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::collections::HashMap;
+use std::env;
+use tokio::time::{sleep, Duration, Instant};
+use tokio_util::sync::CancellationToken;
+
+// ==================================================
+// CONFIG
+// ==================================================
+
+/// Which leg pairs get quoted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MakerPairs {
+    /// UP + DOWN of one market: every filled pair pays exactly 1
+    Same,
+    /// ETH UP + BTC DOWN and ETH DOWN + BTC UP: a pair pays 0, 1 or 2
+    Cross,
+    All,
+}
+
+#[derive(Debug, Clone)]
+pub struct MakerParams {
+    pub enabled: bool,
+    pub pairs: MakerPairs,
+    /// Most the two resting bids may sum to
+    pub target_sum: Decimal,
+    /// Most a one-sided fill may be completed at by taking the other ask
+    pub complete_max_sum: Decimal,
+    /// Shares per quote
+    pub quote_size: Decimal,
+    /// Most shares bought per leg in a period
+    pub max_inventory: Decimal,
+    /// Replace a quote once the wanted price is this many ticks away
+    pub requote_ticks: u32,
+    pub poll_interval: Duration,
+    /// Lift bids into the reward spread and size to the reward minimum
+    /// when the pair still fits the target
+    pub reward_bias: bool,
+}
+
+impl MakerParams {
+    pub fn from_env() -> Self {
+        fn dec_env(key: &str, default: f64) -> Decimal {
+            let v = env::var(key)
+                .ok()
+                .and_then(|v| v.parse::<f64>().ok())
+                .unwrap_or(default);
+            Decimal::from_f64(v).unwrap_or_default()
+        }
+
+        // Read MAKER_MODE from env (default: false)
+        let enabled = env::var("MAKER_MODE")
+            .ok()
+            .and_then(|v| v.parse::<bool>().ok())
+            .unwrap_or(false);
+
+        // Read MAKER_PAIRS from env: same | cross | all (default: same)
+        let pairs = match env::var("MAKER_PAIRS")
+            .unwrap_or_default()
+            .to_ascii_lowercase()
+            .as_str()
+        {
+            "cross" => MakerPairs::Cross,
+            "all" => MakerPairs::All,
+            _ => MakerPairs::Same,
+        };
+
+        // Read MAKER_REQUOTE_TICKS from env (default: 1)
+        let requote_ticks = env::var("MAKER_REQUOTE_TICKS")
+            .ok()
+            .and_then(|v| v.parse::<u32>().ok())
+            .unwrap_or(1)
+            .max(1);
+
+        // Read MAKER_POLL_MS from env (default: 1000)
+        let poll_ms = env::var("MAKER_POLL_MS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(1000);
+
+        // Read MAKER_REWARD_BIAS from env (default: false)
+        let reward_bias = env::var("MAKER_REWARD_BIAS")
+            .ok()
+            .and_then(|v| v.parse::<bool>().ok())
+            .unwrap_or(false);
+
+        Self {
+            enabled,
+            pairs,
+            target_sum: dec_env("MAKER_TARGET_SUM", 0.97),
+            complete_max_sum: dec_env("MAKER_COMPLETE_MAX_SUM", 0.99),
+            quote_size: dec_env("MAKER_SIZE", 10.0),
+            max_inventory: dec_env("MAKER_MAX_INVENTORY", 100.0),
+            requote_ticks,
+            poll_interval: Duration::from_millis(poll_ms),
+            reward_bias,
+        }
+    }
+}
+
+// ==================================================
+// PAIRS
+// ==================================================
+
+#[derive(Debug, Clone)]
+pub struct MakerLeg {
+    /// e.g. "ETH UP"
+    pub label: String,
+    pub condition_id: String,
+    pub token_id: String,
+    /// The other outcome of the same market
+    pub complement_token_id: String,
+    pub tick: Decimal,
+    /// The market's liquidity reward program, if any
+    pub rewards: Option<RewardParams>,
+}
+
+/// Two legs bid together so that filling both locks in the edge
+#[derive(Debug, Clone)]
+pub struct MakerPair {
+    pub a: MakerLeg,
+    pub b: MakerLeg,
+    pub ends_at: DateTime<Utc>,
+}
+
+impl MakerPair {
+    pub fn label(&self) -> String {
+        format!("{} + {}", self.a.label, self.b.label)
+    }
+
+    /// Shares per quote: with the reward bias, at least both markets' minimum
+    pub fn quote_size(&self, params: &MakerParams) -> Decimal {
+        if !params.reward_bias {
+            return params.quote_size;
+        }
+        [&self.a, &self.b]
+            .iter()
+            .filter_map(|leg| leg.rewards.map(|r| r.min_size))
+            .fold(params.quote_size, Decimal::max)
+    }
+
+    /// The pairs `which` selects in `period`
+    pub fn for_period(period: &PreparedPeriod, which: MakerPairs) -> Vec<Self> {
+        let leg =
+            |label: &str, market: &Market, token: &OutcomeToken, other: &OutcomeToken| MakerLeg {
+                label: label.to_string(),
+                condition_id: market.condition_id.clone(),
+                token_id: token.token_id.clone(),
+                complement_token_id: other.token_id.clone(),
+                rewards: period.rewards.get(&market.condition_id).copied(),
+                tick: period
+                    .token_params
+                    .get(&token.token_id)
+                    .map(|p| p.tick_size)
+                    .unwrap_or(dec!(0.01)),
+            };
+
+        let (eth, btc) = (&period.eth_tokens, &period.btc_tokens);
+        let eth_up = leg("ETH UP", &period.eth_market, &eth.up, &eth.down);
+        let eth_down = leg("ETH DOWN", &period.eth_market, &eth.down, &eth.up);
+        let btc_up = leg("BTC UP", &period.btc_market, &btc.up, &btc.down);
+        let btc_down = leg("BTC DOWN", &period.btc_market, &btc.down, &btc.up);
+
+        let pair = |a: &MakerLeg, b: &MakerLeg| MakerPair {
+            a: a.clone(),
+            b: b.clone(),
+            ends_at: period.ends_at,
+        };
+
+        let same = [pair(&eth_up, &eth_down), pair(&btc_up, &btc_down)];
+        let cross = [pair(&eth_up, &btc_down), pair(&eth_down, &btc_up)];
+
+        match which {
+            MakerPairs::Same => same.into(),
+            MakerPairs::Cross => cross.into(),
+            MakerPairs::All => same.into_iter().chain(cross).collect(),
+        }
+    }
+}
+
+// ==================================================
+// PRICING
+// ==================================================
+
+/// Top of one leg's book, our own bid left out
+#[derive(Debug, Clone, Copy)]
+pub struct BookTop {
+    pub best_bid: Option<Decimal>,
+    pub best_ask: Option<Decimal>,
+    /// Midpoint of the whole book, ours included
+    pub mid: Option<Decimal>,
+    pub tick: Decimal,
+}
+
+fn floor_tick(price: Decimal, tick: Decimal) -> Decimal {
+    (price / tick).floor() * tick
+}
+
+/// Highest bid that still rests: one tick under the ask
+fn max_resting(top: BookTop) -> Decimal {
+    top.best_ask.unwrap_or(Decimal::ONE) - top.tick
+}
+
+/// One tick better than the best bid, never at or through the ask
+fn join_price(top: BookTop) -> Option<Decimal> {
+    let price = top
+        .best_bid
+        .map(|b| b + top.tick)
+        .unwrap_or(top.tick)
+        .min(max_resting(top));
+    (price >= top.tick).then_some(price)
+}
+
+/// Bids for both legs summing to at most `target`. Each starts one tick
+/// better than its best bid; any excess is shaded off in proportion to
+/// price and rounded down to the leg's tick.
+pub fn pair_bids(a: BookTop, b: BookTop, target: Decimal) -> Option<(Decimal, Decimal)> {
+    let (pa, pb) = (join_price(a)?, join_price(b)?);
+    let sum = pa + pb;
+    if sum <= target {
+        return Some((pa, pb));
+    }
+
+    let scale = target / sum;
+    let (pa, pb) = (
+        floor_tick(pa * scale, a.tick),
+        floor_tick(pb * scale, b.tick),
+    );
+    (pa >= a.tick && pb >= b.tick).then_some((pa, pb))
+}
+
+/// Bid for the missing leg once the other filled at `paired_price`: as
+/// much as still keeps the pair at `target`, without crossing.
+pub fn completion_bid(top: BookTop, paired_price: Decimal, target: Decimal) -> Option<Decimal> {
+    let price = floor_tick((target - paired_price).min(max_resting(top)), top.tick);
+    (price >= top.tick).then_some(price)
+}
+
+/// Lift each bid to the lowest price that scores for liquidity rewards,
+/// unless that pushes the pair over `target`.
+pub fn reward_bids(
+    a: (&MakerLeg, BookTop),
+    b: (&MakerLeg, BookTop),
+    bids: (Decimal, Decimal),
+    target: Decimal,
+) -> (Decimal, Decimal) {
+    let lift = |(leg, top): (&MakerLeg, BookTop), price: Decimal| match (leg.rewards, top.mid) {
+        (Some(rewards), Some(mid)) => price
+            .max(rewards.min_bid(mid, top.tick))
+            .min(max_resting(top)),
+        _ => price,
+    };
+
+    let lifted = (lift(a, bids.0), lift(b, bids.1));
+    if lifted.0 + lifted.1 <= target {
+        lifted
+    } else {
+        bids
+    }
+}
+
+/// Cached top of book for `leg`, with `own` (price, size) taken off the bids
+async fn book_top(
+    cache: &PriceCache,
+    leg: &MakerLeg,
+    own: Option<(Decimal, Decimal)>,
+) -> Option<BookTop> {
+    let book = cache.get(&leg.token_id).await?;
+
+    let best_bid = book
+        .bids
+        .iter()
+        .find(|&&(price, size)| match own {
+            Some((own_price, own_size)) if price == own_price => size > own_size,
+            _ => size > Decimal::ZERO,
+        })
+        .map(|&(price, _)| price);
+
+    let best_ask = book.asks.first().map(|&(price, _)| price);
+    let mid = match (book.bids.first(), best_ask) {
+        (Some(&(bid, _)), Some(ask)) => Some((bid + ask) / dec!(2)),
+        _ => None,
+    };
+
+    Some(BookTop {
+        best_bid,
+        best_ask,
+        mid,
+        tick: leg.tick,
+    })
+}
+
+/// `bids` less our own `(price, size)` orders
+fn others_bids(bids: &[(Decimal, Decimal)], own: &[(Decimal, Decimal)]) -> Levels {
+    bids.iter()
+        .map(|&(price, size)| {
+            let mine: Decimal = own.iter().filter(|o| o.0 == price).map(|o| o.1).sum();
+            (price, (size - mine).max(Decimal::ZERO))
+        })
+        .collect()
+}
+
+// ==================================================
+// STATE
+// ==================================================
+
+/// One of our bids on the book
+struct Quote {
+    intent: OrderIntent,
+    /// None when nothing was placed (read-only)
+    order_id: Option<String>,
+    matched: Decimal,
+    reservation: Reservation,
+    hold: RiskHold,
+}
+
+impl Quote {
+    fn remaining(&self) -> Decimal {
+        self.intent.size - self.matched
+    }
+}
+
+#[derive(Default)]
+struct LegBook {
+    quote: Option<Quote>,
+    /// Shares bought this period, quotes and completions
+    held: Decimal,
+    cost: Decimal,
+}
+
+impl LegBook {
+    fn avg_price(&self) -> Decimal {
+        if self.held > Decimal::ZERO {
+            self.cost / self.held
+        } else {
+            Decimal::ZERO
+        }
+    }
+
+    fn own_bid(&self) -> Option<(Decimal, Decimal)> {
+        self.quote.as_ref().map(|q| (q.intent.price, q.remaining()))
+    }
+}
+
+#[derive(Default)]
+struct PairBook {
+    a: LegBook,
+    b: LegBook,
+}
+
+// ==================================================
+// MAKER LOOP
+// ==================================================
+
+impl Trader {
+    /// Rest bids on every pair until `cancel` fires, then pull them all.
+    ///
+    /// While both legs hold the same number of shares, each leg is bid a
+    /// tick better than the book with the two summing to at most the target.
+    /// Once one leg is ahead the other is completed at the ask if the pair
+    /// still costs no more than `complete_max_sum`, otherwise re-bid at
+    /// what keeps the pair at the target.
+    pub async fn run_maker(
+        &self,
+        pairs: Vec<MakerPair>,
+        cache: PriceCache,
+        params: MakerParams,
+        cancel: CancellationToken,
+    ) {
+        info!(
+            "🏦 Maker: quoting {} pair(s), bids summing ≤ {} ({} shares, max {} per leg)",
+            pairs.len(),
+            params.target_sum,
+            params.quote_size,
+            params.max_inventory
+        );
+
+        let mut books: Vec<PairBook> = pairs.iter().map(|_| PairBook::default()).collect();
+        let mut sampled_at = Instant::now();
+
+        loop {
+            for (pair, book) in pairs.iter().zip(books.iter_mut()) {
+                if let Err(e) = self.maker_step(pair, book, &cache, &params).await {
+                    warn!("⚠️  Maker {}: {}", pair.label(), e);
+                }
+            }
+
+            // Credit the time since the last pass to what rests now
+            let secs = sampled_at.elapsed().as_secs_f64();
+            sampled_at = Instant::now();
+            self.sample_rewards(&pairs, &books, &cache, secs).await;
+
+            tokio::select! {
+                _ = cancel.cancelled() => break,
+                _ = sleep(params.poll_interval) => {}
+            }
+        }
+
+        for (pair, book) in pairs.iter().zip(books.iter_mut()) {
+            self.pull_quote(&pair.a, &mut book.a, "period end").await;
+            self.pull_quote(&pair.b, &mut book.b, "period end").await;
+
+            let unpaired = (book.a.held - book.b.held).abs();
+            if unpaired > Decimal::ZERO {
+                warn!(
+                    "⚠️  Maker {} ends with {} unpaired share(s)",
+                    pair.label(),
+                    unpaired
+                );
+            }
+            info!(
+                "🏦 Maker {}: {} pair(s) filled for ${}",
+                pair.label(),
+                book.a.held.min(book.b.held),
+                (book.a.cost + book.b.cost).round_dp(2)
+            );
+        }
+
+        let epoch = self.clock.now().date_naive();
+        for (condition_id, estimate) in self.rewards.epoch(epoch) {
+            if pairs
+                .iter()
+                .any(|p| p.a.condition_id == condition_id || p.b.condition_id == condition_id)
+            {
+                info!(
+                    "🎁 Est. liquidity rewards {} today: {}",
+                    condition_id, estimate
+                );
+            }
+        }
+        info!("💼 Capital: {}", self.ledger.snapshot());
+    }
+
+    /// Score our resting bids against the rest of the book in every market
+    /// with a reward program, crediting them `secs` of resting.
+    async fn sample_rewards(
+        &self,
+        pairs: &[MakerPair],
+        books: &[PairBook],
+        cache: &PriceCache,
+        secs: f64,
+    ) {
+        let mut own: HashMap<&str, Levels> = HashMap::new();
+        let mut markets: HashMap<&str, (&MakerLeg, RewardParams)> = HashMap::new();
+
+        for (pair, book) in pairs.iter().zip(books) {
+            for (leg, lb) in [(&pair.a, &book.a), (&pair.b, &book.b)] {
+                if let Some(q) = lb.quote.as_ref().filter(|q| q.order_id.is_some()) {
+                    own.entry(leg.token_id.as_str())
+                        .or_default()
+                        .push((q.intent.price, q.remaining()));
+                }
+                if let Some(rewards) = leg.rewards {
+                    markets
+                        .entry(leg.condition_id.as_str())
+                        .or_insert((leg, rewards));
+                }
+            }
+        }
+
+        let epoch = self.clock.now().date_naive();
+        for (condition_id, (leg, rewards)) in markets {
+            // Bids on the complement count as asks on the market
+            let mut sides = Vec::with_capacity(2);
+            for token_id in [&leg.token_id, &leg.complement_token_id] {
+                let Some(book) = cache.get(token_id).await else {
+                    break;
+                };
+                let (Some(&(bid, _)), Some(&(ask, _))) = (book.bids.first(), book.asks.first())
+                else {
+                    break;
+                };
+
+                let mid = (bid + ask) / dec!(2);
+                let mine = own.get(token_id.as_str()).cloned().unwrap_or_default();
+                let others = others_bids(&book.bids, &mine);
+                sides.push((
+                    mid,
+                    rewards.bids_score(mid, &mine),
+                    rewards.bids_score(mid, &others),
+                ));
+            }
+
+            let [(mid, ours_one, others_one), (_, ours_two, others_two)] = sides[..] else {
+                continue;
+            };
+            self.rewards.sample(
+                epoch,
+                condition_id,
+                &rewards,
+                q_min(ours_one, ours_two, mid),
+                q_min(others_one, others_two, mid),
+                secs,
+            );
+        }
+    }
+
+    async fn maker_step(
+        &self,
+        pair: &MakerPair,
+        book: &mut PairBook,
+        cache: &PriceCache,
+        params: &MakerParams,
+    ) -> Result<()> {
+        // Fills since the last look
+        self.poll_quote(&pair.a, &mut book.a).await;
+        self.poll_quote(&pair.b, &mut book.b).await;
+
+        let (Some(top_a), Some(top_b)) = (
+            book_top(cache, &pair.a, book.a.own_bid()).await,
+            book_top(cache, &pair.b, book.b.own_bid()).await,
+        ) else {
+            return Ok(());
+        };
+
+        let phase = self
+            .expiry
+            .phase(Some(seconds_remaining(pair.ends_at, self.clock.now())));
+
+        // One leg ahead: stop adding to it and complete the other
+        if book.a.held > book.b.held {
+            self.pull_quote(&pair.a, &mut book.a, "other leg unpaired")
+                .await;
+            return self
+                .complete_leg(&pair.b, &mut book.b, &book.a, top_b, params, phase)
+                .await;
+        }
+        if book.b.held > book.a.held {
+            self.pull_quote(&pair.b, &mut book.b, "other leg unpaired")
+                .await;
+            return self
+                .complete_leg(&pair.a, &mut book.a, &book.b, top_a, params, phase)
+                .await;
+        }
+
+        let size = pair.quote_size(params);
+        let bids = pair_bids(top_a, top_b, params.target_sum).map(|bids| {
+            if params.reward_bias {
+                reward_bids((&pair.a, top_a), (&pair.b, top_b), bids, params.target_sum)
+            } else {
+                bids
+            }
+        });
+
+        let stop = if self.shutdown.is_cancelled() {
+            Some("shutdown".to_string())
+        } else if !phase.allows_entry() {
+            Some(format!("{} phase", phase))
+        } else if book.a.held + size > params.max_inventory {
+            Some("inventory limit".to_string())
+        } else if bids.is_none() {
+            Some(format!("no bids sum to {}", params.target_sum))
+        } else {
+            None
+        };
+
+        match (stop, bids) {
+            (None, Some((price_a, price_b))) => {
+                self.requote(&pair.a, &mut book.a, price_a, size, params)
+                    .await?;
+                self.requote(&pair.b, &mut book.b, price_b, size, params)
+                    .await
+            }
+            (reason, _) => {
+                let reason = reason.unwrap_or_default();
+                self.pull_quote(&pair.a, &mut book.a, &reason).await;
+                self.pull_quote(&pair.b, &mut book.b, &reason).await;
+                Ok(())
+            }
+        }
+    }
+
+    /// Bring `leg` level with `ahead`: take the ask when the pair still
+    /// pays, otherwise bid what keeps the pair at the target.
+    async fn complete_leg(
+        &self,
+        leg: &MakerLeg,
+        lb: &mut LegBook,
+        ahead: &LegBook,
+        top: BookTop,
+        params: &MakerParams,
+        phase: TradingPhase,
+    ) -> Result<()> {
+        let (target_held, paired_price) = (ahead.held, ahead.avg_price());
+        let need = target_held - lb.held;
+
+        if let Some(ask) = top.best_ask {
+            let fee = fee_per_share(ask, self.fees.taker_bps(&leg.token_id));
+            if paired_price + ask + fee <= params.complete_max_sum {
+                self.pull_quote(leg, lb, "completing at the ask").await;
+
+                // The pull may have caught a last fill
+                let need = target_held - lb.held;
+                if need <= Decimal::ZERO {
+                    return Ok(());
+                }
+
+                let intent = OrderIntent {
+                    condition_id: leg.condition_id.clone(),
+                    token_id: leg.token_id.clone(),
+                    side: Side::Buy,
+                    price: ask,
+                    size: need,
+                };
+                let reservation = self
+                    .ledger
+                    .reserve(ask * need)
+                    .ok_or_else(|| anyhow!("${} not available to complete", ask * need))?;
+
+                let fill = self.place_leg(&intent, phase, &reservation).await?;
+                lb.held += fill.filled_size;
+                lb.cost += fill.filled_size * fill.avg_price;
+
+                info!(
+                    "🏦 Completed {} with {} @ {} (pair {})",
+                    leg.label,
+                    fill.filled_size,
+                    fill.avg_price,
+                    (paired_price + fill.avg_price).round_dp(4)
+                );
+                return Ok(());
+            }
+        }
+
+        match completion_bid(top, paired_price, params.target_sum) {
+            Some(price) => self.requote(leg, lb, price, need, params).await,
+            None => {
+                self.pull_quote(leg, lb, "no completion bid").await;
+                Ok(())
+            }
+        }
+    }
+
+    /// Keep a bid of `size` at `price` resting, replacing the current one
+    /// once it is `requote_ticks` away or the wrong size.
+    async fn requote(
+        &self,
+        leg: &MakerLeg,
+        lb: &mut LegBook,
+        price: Decimal,
+        size: Decimal,
+        params: &MakerParams,
+    ) -> Result<()> {
+        if let Some(quote) = &lb.quote {
+            let drift = (quote.intent.price - price).abs();
+            if quote.remaining() == size && drift < leg.tick * Decimal::from(params.requote_ticks) {
+                return Ok(());
+            }
+
+            let held = lb.held;
+            self.pull_quote(leg, lb, "book moved").await;
+
+            // Filled while being pulled: re-balance on the next pass
+            if lb.held != held || lb.quote.is_some() {
+                return Ok(());
+            }
+        }
+
+        self.place_quote(leg, lb, price, size).await
+    }
+
+    async fn place_quote(
+        &self,
+        leg: &MakerLeg,
+        lb: &mut LegBook,
+        price: Decimal,
+        size: Decimal,
+    ) -> Result<()> {
+        let intent = OrderIntent {
+            condition_id: leg.condition_id.clone(),
+            token_id: leg.token_id.clone(),
+            side: Side::Buy,
+            price,
+            size,
+        };
+
+        let hold = self.risk.check(std::slice::from_ref(&intent))?;
+
+        let reservation = self
+            .ledger
+            .reserve(price * size)
+            .ok_or_else(|| anyhow!("${} not available to quote", price * size))?;
+
+        // Resting orders are signed with the maker rate and never expire
+        let (order, sig) = self
+            .sign_leg(&intent, self.fees.maker_bps(&leg.token_id), 0)
+            .await?;
+        let order_id = match self.backend.rest(&intent, order, sig).await {
+            Ok(order_id) => order_id,
+            Err(e) => {
+                // A closed market stops being quoted at the next risk check
+                if let Some(clob) = ClobError::find(&e) {
+                    self.risk.record_rejection(&intent, clob);
+                }
+                return Err(e);
+            }
+        };
+
+        info!(
+            "🏦 Quote {} {} @ {} ({})",
+            leg.label,
+            size,
+            price,
+            order_id.as_deref().unwrap_or("not placed")
+        );
+        self.journal.record(JournalEvent::QuotePlaced {
+            token_id: leg.token_id.clone(),
+            price: price.to_f64().unwrap_or(0.0),
+            size: size.to_f64().unwrap_or(0.0),
+            order_id: order_id.clone(),
+        });
+
+        lb.quote = Some(Quote {
+            intent,
+            order_id,
+            matched: Decimal::ZERO,
+            reservation,
+            hold,
+        });
+        Ok(())
+    }
+
+    /// Book fills on the leg's quote; forget it once it no longer rests
+    async fn poll_quote(&self, leg: &MakerLeg, lb: &mut LegBook) {
+        let Some(quote) = &mut lb.quote else {
+            return;
+        };
+        let Some(order_id) = quote.order_id.clone() else {
+            return;
+        };
+
+        let status = match self.backend.order_status(&order_id).await {
+            Ok(status) => status,
+            Err(e) => {
+                warn!("⚠️  Quote {} status unavailable: {}", order_id, e);
+                return;
+            }
+        };
+
+        let filled = status.matched - quote.matched;
+        if filled > Decimal::ZERO {
+            let cost = filled * quote.intent.price;
+            quote
+                .reservation
+                .record_fill(&leg.token_id, &leg.condition_id, filled, cost);
+            quote.hold.record_fill(&OrderIntent {
+                size: filled,
+                ..quote.intent.clone()
+            });
+            quote.matched = status.matched;
+            lb.held += filled;
+            lb.cost += cost;
+
+            info!(
+                "🏦 Quote filled {} {} @ {} ({}/{})",
+                leg.label, filled, quote.intent.price, quote.matched, quote.intent.size
+            );
+            self.journal.record(JournalEvent::QuoteFilled {
+                token_id: leg.token_id.clone(),
+                price: quote.intent.price.to_f64().unwrap_or(0.0),
+                filled: filled.to_f64().unwrap_or(0.0),
+                order_id,
+            });
+        }
+
+        if !status.live {
+            lb.quote = None;
+        }
+    }
+
+    /// Cancel the leg's quote, booking any fill that beat the cancel
+    async fn pull_quote(&self, leg: &MakerLeg, lb: &mut LegBook, reason: &str) {
+        let Some(order_id) = lb.quote.as_ref().map(|q| q.order_id.clone()) else {
+            return;
+        };
+
+        if let Some(id) = &order_id {
+            if let Err(e) = self.backend.cancel(id).await {
+                // Still resting as far as we know: retried on the next pass
+                warn!("⚠️  Cancel of quote {} failed: {}", id, e);
+                return;
+            }
+            self.poll_quote(leg, lb).await;
+        }
+
+        lb.quote = None;
+        info!("🏦 Pulled {} quote: {}", leg.label, reason);
+        self.journal.record(JournalEvent::QuoteCancelled {
+            token_id: leg.token_id.clone(),
+            order_id,
+            reason: reason.to_string(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn top(bid: Decimal, ask: Decimal, tick: Decimal) -> BookTop {
+        BookTop {
+            best_bid: Some(bid),
+            best_ask: Some(ask),
+            mid: Some((bid + ask) / dec!(2)),
+            tick,
+        }
+    }
+
+    fn leg(rewards: Option<RewardParams>) -> MakerLeg {
+        MakerLeg {
+            label: "ETH UP".into(),
+            condition_id: "eth".into(),
+            token_id: "eth-up".into(),
+            complement_token_id: "eth-down".into(),
+            tick: dec!(0.01),
+            rewards,
+        }
+    }
+
+    fn on_tick(price: Decimal, tick: Decimal) -> bool {
+        (price / tick).fract().is_zero()
+    }
+
+    #[test]
+    fn pair_bids_stay_on_tick_under_the_ask_and_within_target() {
+        let cases = [
+            // (a, b, target, bids)
+            (
+                top(dec!(0.40), dec!(0.45), dec!(0.01)),
+                top(dec!(0.50), dec!(0.55), dec!(0.01)),
+                dec!(0.97),
+                Some((dec!(0.41), dec!(0.51))),
+            ),
+            // One tick over the bid would touch the ask
+            (
+                top(dec!(0.44), dec!(0.45), dec!(0.01)),
+                top(dec!(0.50), dec!(0.52), dec!(0.01)),
+                dec!(1),
+                Some((dec!(0.44), dec!(0.51))),
+            ),
+            // Over target: shaded in proportion, floored to each tick
+            (
+                top(dec!(0.48), dec!(0.55), dec!(0.01)),
+                top(dec!(0.50), dec!(0.60), dec!(0.001)),
+                dec!(0.97),
+                Some((dec!(0.47), dec!(0.490))),
+            ),
+            // Shaded below a tick: nothing to bid
+            (
+                top(dec!(0.48), dec!(0.55), dec!(0.01)),
+                top(dec!(0.50), dec!(0.60), dec!(0.01)),
+                dec!(0.01),
+                None,
+            ),
+        ];
+
+        for (a, b, target, expected) in cases {
+            let bids = pair_bids(a, b, target);
+            assert_eq!(bids, expected, "target {}", target);
+
+            let Some((pa, pb)) = bids else { continue };
+            assert!(pa + pb <= target);
+            assert!(on_tick(pa, a.tick) && on_tick(pb, b.tick));
+            assert!(pa < a.best_ask.unwrap() && pb < b.best_ask.unwrap());
+        }
+    }
+
+    #[test]
+    fn completion_bid_cases() {
+        let cases = [
+            // (top, paired price, target, bid)
+            (
+                top(dec!(0.40), dec!(0.60), dec!(0.01)),
+                dec!(0.45),
+                dec!(0.97),
+                Some(dec!(0.52)),
+            ),
+            (
+                top(dec!(0.40), dec!(0.60), dec!(0.01)),
+                dec!(0.455),
+                dec!(0.97),
+                Some(dec!(0.51)),
+            ),
+            // Never at or through the ask
+            (
+                top(dec!(0.40), dec!(0.50), dec!(0.01)),
+                dec!(0.45),
+                dec!(0.97),
+                Some(dec!(0.49)),
+            ),
+            (
+                top(dec!(0.40), dec!(0.60), dec!(0.01)),
+                dec!(0.97),
+                dec!(0.97),
+                None,
+            ),
+        ];
+
+        for (top, paired, target, expected) in cases {
+            let bid = completion_bid(top, paired, target);
+            assert_eq!(bid, expected, "paired {}", paired);
+            if let Some(bid) = bid {
+                assert!(paired + bid <= target);
+            }
+        }
+    }
+
+    #[test]
+    fn reward_bids_lift_to_scoring_only_within_target() {
+        let rewards = Some(RewardParams {
+            max_spread: dec!(0.03),
+            min_size: dec!(50),
+            daily_rate: dec!(100),
+        });
+        let (a, b) = (leg(rewards), leg(rewards));
+        let book = top(dec!(0.45), dec!(0.55), dec!(0.01));
+        let bids = (dec!(0.45), dec!(0.45));
+
+        // Mid 0.50 scores from 0.48 up
+        assert_eq!(
+            reward_bids((&a, book), (&b, book), bids, dec!(0.97)),
+            (dec!(0.48), dec!(0.48))
+        );
+        // Lifting both would break the target: keep the pair bids
+        assert_eq!(reward_bids((&a, book), (&b, book), bids, dec!(0.95)), bids);
+        // No program, no lift
+        assert_eq!(
+            reward_bids((&leg(None), book), (&b, book), bids, dec!(0.97)),
+            (dec!(0.45), dec!(0.48))
+        );
+
+        // A scoring bid that would cross stops a tick under the ask
+        let tight = BookTop {
+            best_ask: Some(dec!(0.47)),
+            ..book
+        };
+        assert_eq!(
+            reward_bids((&a, tight), (&b, book), bids, dec!(0.97)),
+            (dec!(0.46), dec!(0.48))
+        );
+    }
+}
@@ -1,24 +1,41 @@
+pub mod backend;
 pub mod clob_client;
-use crate::client::PolymarketClient;
-use crate::config::{PositionSizing, TradeMode, TradingConfig, WalletConfig};
+pub mod paper;
+use crate::clock::{self, Clock};
+use crate::config::{TradingConfig, WalletConfig};
 use crate::domain::*;
+use crate::domain::time::{seconds_remaining, TradingPhase};
+use crate::domain::order::Side;
+use crate::journal::{JournalEvent, TradeJournal};
+use crate::rewards::RewardTracker;
+use crate::risk::{OrderIntent, RiskHold, RiskManager};
+use crate::strategy::expiry::ExpiryRules;
+use crate::strategy::fees::FeeRates;
+use latency::{ms, StageTimings, SubmitLatency};
+use errors::ClobError;
+use ledger::{CapitalLedger, Reservation};
+use sizing::{LegShares, Sizer, SizingInput};
 use crate::wallet::signer::{ClobOrder, WalletSigner};
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use std::str::FromStr;
-use ::rand::Rng;
 use ethers::types::Address;
+pub use backend::{ExecutionBackend, FillReport, LiveBackend};
 pub use clob_client::ClobClient;
 use ethers::types::{H256, U256};
 use ethers::utils::keccak256;
 use log::{info, warn};
 use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
 use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
 use std::sync::Arc;
-use std::time::{SystemTime, UNIX_EPOCH};
-use tokio::sync::Mutex;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use tokio_util::sync::CancellationToken;
 pub mod errors;
+pub mod latency;
+pub mod ledger;
+pub mod maker;
 pub mod orderbook;
-pub mod trader;
+pub mod sizing;
 
 // ==================================================
 // Helpers
@@ -33,6 +50,16 @@ fn to_u256_scaled(v: Decimal) -> U256 {
     U256::from((f * 1_000_000.0) as u128)
 }
 
+/// The exchange refused the order outright; sending it again is safe
+fn is_retryable(e: &anyhow::Error) -> bool {
+    ClobError::find(e).is_some_and(ClobError::is_retryable)
+}
+
+/// The order may have reached the book despite the error
+fn outcome_unknown(e: &anyhow::Error) -> bool {
+    ClobError::find(e).is_some_and(ClobError::outcome_unknown)
+}
+
 fn now_ts() -> u64 {
     SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -53,43 +80,101 @@ fn make_nonce() -> U256 {
 // ==================================================
 
 pub struct Trader {
-    api: Arc<PolymarketClient>,
-    clob: Arc<ClobClient>,
+    backend: Arc<dyn ExecutionBackend>,
+    journal: Arc<TradeJournal>,
+    risk: Arc<RiskManager>,
+    shutdown: CancellationToken,
+    #[allow(dead_code)]
     config: TradingConfig,
     wallet: WalletConfig,
     signer: WalletSigner,
-    sizing: PositionSizing,
+    sizer: Sizer,
+    fees: FeeRates,
+    expiry: ExpiryRules,
+    clock: Arc<dyn Clock>,
 
-    live_usdc_balance: Arc<Mutex<Decimal>>,
+    ledger: CapitalLedger,
+    rewards: RewardTracker,
 }
 
 impl Trader {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
-        api: Arc<PolymarketClient>,
-        clob: Arc<ClobClient>,
+        backend: Arc<dyn ExecutionBackend>,
         config: TradingConfig,
         wallet: WalletConfig,
         signer: WalletSigner,
+        journal: Arc<TradeJournal>,
+        risk: Arc<RiskManager>,
+        shutdown: CancellationToken,
     ) -> Self {
+        info!("⚙️  Execution backend: {}", backend.name());
+
         Self {
-            api,
-            clob,
+            backend,
+            journal,
+            risk,
+            shutdown,
             config,
             wallet,
             signer,
-            sizing: PositionSizing::from_env(),
-            live_usdc_balance: Arc::new(Mutex::new(Decimal::ZERO)),
+            sizer: Sizer::from_env(),
+            fees: FeeRates::new(),
+            expiry: ExpiryRules::from_env(),
+            clock: clock::system(),
+            ledger: CapitalLedger::new(),
+            rewards: RewardTracker::new(),
         }
     }
 
+    /// Sign each order with its token's taker fee rate
+    pub fn with_fees(mut self, fees: FeeRates) -> Self {
+        self.fees = fees;
+        self
+    }
+
+    /// Measure time to expiry on `clock` instead of the wall clock
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Share capital accounting with other traders or settlement tasks
+    pub fn with_ledger(mut self, ledger: CapitalLedger) -> Self {
+        self.ledger = ledger;
+        self
+    }
+
+    pub fn ledger(&self) -> CapitalLedger {
+        self.ledger.clone()
+    }
+
+    /// Liquidity rewards earned by the maker's quotes
+    pub fn rewards(&self) -> RewardTracker {
+        self.rewards.clone()
+    }
+
+    /// Estimated liquidity rewards for the current epoch on the trader's clock
+    pub fn rewards_today(&self) -> f64 {
+        self.rewards.epoch_total(self.clock.now().date_naive())
+    }
+
+    /// Phase of the opportunity's window right now
+    fn current_phase(&self, opportunity: &ArbitrageOpportunity) -> TradingPhase {
+        let remaining = opportunity
+            .ends_at
+            .map(|t| seconds_remaining(t, self.clock.now()));
+        self.expiry.phase(remaining)
+    }
+
     // ==================================================
     // BALANCE
     // ==================================================
 
     async fn refresh_balance(&self) -> Result<()> {
-        let bal = self.api.get_usdc_balance().await?;
-        *self.live_usdc_balance.lock().await = bal;
-        info!("💰 USDC balance: {}", bal);
+        let bal = self.backend.usdc_balance().await?;
+        self.ledger.sync_wallet(bal);
+        info!("💰 USDC balance: {} | {}", bal, self.ledger.snapshot());
         Ok(())
     }
 
@@ -98,67 +183,635 @@ impl Trader {
     // ==================================================
 
     pub async fn execute_arbitrage(&self, opportunity: &ArbitrageOpportunity) -> Result<()> {
+        // New entries stop at shutdown; legs already started run to the end
+        if self.shutdown.is_cancelled() {
+            info!("🛑 Shutdown requested — skipping new opportunity");
+            return Ok(());
+        }
+
+        let started = Instant::now();
+        let mut timings = StageTimings {
+            detect: opportunity
+                .observed_at
+                .map(|t| started.saturating_duration_since(t))
+                .unwrap_or_default(),
+            ..Default::default()
+        };
+
         // 1️⃣ Refresh balance
         self.refresh_balance().await?;
 
         // 2️⃣ Calculate size
-        let units = self.calculate_position_size(opportunity).await?;
-        if units <= 0.0 {
+        let shares = self.calculate_position_size(opportunity).await?;
+        if shares.is_empty() {
+            warn!("❌ Trade skipped (below minimum size)");
             return Ok(());
         }
 
-        let cost = opportunity.total_cost.to_f64().unwrap_or(0.0);
-        let spend = units * cost;
+        let spend = shares.spend(opportunity);
 
-        if spend < 1.0 {
-            warn!("❌ Trade skipped (below $1 minimum)");
+        // Hold the USDC so concurrent trades can't size off it too
+        let Some(reservation) = self
+            .ledger
+            .reserve(Decimal::from_f64(spend).unwrap_or_default())
+        else {
+            warn!("❌ Trade skipped (${:.2} no longer available)", spend);
             return Ok(());
-        }
+        };
 
         // 3️⃣ HARD GATE — balance + allowance + ERC1155
-        self.clob
+        self.backend
             .ensure_trading_ready((spend * 1_000_000.0) as u128)
             .await?;
 
+        // Last check before committing the legs
+        if self.shutdown.is_cancelled() {
+            info!("🛑 Shutdown requested — not sending legs");
+            return Ok(());
+        }
+
+        // The window may have moved on since detection
+        let phase = self.current_phase(opportunity);
+        if !phase.allows_entry() {
+            info!("⏳ Entered {} phase since detection — not sending legs", phase);
+            return Ok(());
+        }
+
         info!(
-            "🚀 EXEC | units={} spend=${:.2} expected_profit={}",
-            units, spend, opportunity.expected_profit
+            "🚀 EXEC | shares={}/{} spend=${:.2} expected_profit={} phase={}",
+            shares.eth, shares.btc, spend, opportunity.expected_profit, phase
         );
 
-        let size_dec = Decimal::from_f64(units).unwrap();
-
-        // ================= ETH LEG =================
-        // ================= ETH LEG =================
-        self.place_leg(
-            &opportunity.eth_up_token_id,
-            0,
-            opportunity.eth_up_price,
-            size_dec,
-        )
-        .await?;
-
-        // ================= BTC LEG =================
-        self.place_leg(
-            &opportunity.btc_down_token_id,
-            0,
-            opportunity.btc_down_price,
-            size_dec,
-        )
-        .await?;
+        let eth_leg = OrderIntent {
+            condition_id: opportunity.eth_condition_id.clone(),
+            token_id: opportunity.eth_up_token_id.clone(),
+            side: Side::Buy,
+            price: opportunity.eth_up_price,
+            size: Decimal::from_f64(shares.eth).unwrap_or_default(),
+        };
+        let btc_leg = OrderIntent {
+            condition_id: opportunity.btc_condition_id.clone(),
+            token_id: opportunity.btc_down_token_id.clone(),
+            side: Side::Buy,
+            price: opportunity.btc_down_price,
+            size: Decimal::from_f64(shares.btc).unwrap_or_default(),
+        };
+
+        // 4️⃣ RISK GATE — both legs together, before anything is sent
+        let hold = match self.risk.check(&[eth_leg.clone(), btc_leg.clone()]) {
+            Ok(hold) => hold,
+            Err(reason) => {
+                warn!("🚫 Risk rejected trade: {}", reason);
+                self.journal.record(JournalEvent::RiskRejected {
+                    reason: reason.to_string(),
+                });
+                return Err(reason.into());
+            }
+        };
+
+        timings.size = started.elapsed();
+
+        // ================= SIGN BOTH LEGS =================
+        // Signing is CPU-bound: each leg gets its own task to run in parallel
+        let signing = Instant::now();
+        let sign = |leg: &OrderIntent| -> Result<_> {
+            let fee_rate_bps = self.fees.taker_bps(&leg.token_id);
+            let order = self.build_order(leg, fee_rate_bps, now_ts() + 300)?;
+            let signer = self.signer.clone();
+            Ok(tokio::spawn(async move {
+                let sig = signer.sign_order(&order).await?;
+                anyhow::Ok((order, sig))
+            }))
+        };
+        let (eth_signed, btc_signed) = tokio::try_join!(sign(&eth_leg)?, sign(&btc_leg)?)?;
+        let (eth_signed, btc_signed) = (eth_signed?, btc_signed?);
+        timings.sign = signing.elapsed();
+
+        // ================= SEND BOTH LEGS =================
+        // One request carries both; whatever doesn't pair up is unwound below
+        let mut fills = self
+            .send_legs(
+                vec![(&eth_leg, eth_signed), (&btc_leg, btc_signed)],
+                phase,
+                &reservation,
+                &hold,
+            )
+            .await
+            .into_iter();
+        // Fills are booked; completions and unwinds check on their own
+        drop(hold);
+        let (eth_fill, btc_fill) = match (fills.next(), fills.next()) {
+            (Some(eth), Some(btc)) => (eth, btc),
+            _ => unreachable!("send_legs returns one result per leg"),
+        };
+
+        let legs: Vec<SubmitLatency> = [&eth_fill, &btc_fill]
+            .into_iter()
+            .filter_map(|f| f.as_ref().ok().map(|f| f.latency))
+            .collect();
+        timings.record_legs(&legs);
+        self.record_timings(opportunity, &timings);
+
+        // A leg the exchange refused outright was never placed: send it
+        // once more before giving up on the pair
+        let (eth_fill, btc_fill) = match (eth_fill, btc_fill) {
+            (Ok(eth), Err(e)) if is_retryable(&e) => {
+                warn!("🔁 BTC leg refused ({}) — resending once", e);
+                let btc = self.place_leg(&btc_leg, phase, &reservation).await;
+                (Ok(eth), btc)
+            }
+            (Err(e), Ok(btc)) if is_retryable(&e) => {
+                warn!("🔁 ETH leg refused ({}) — resending once", e);
+                let eth = self.place_leg(&eth_leg, phase, &reservation).await;
+                (eth, Ok(btc))
+            }
+            fills => fills,
+        };
+
+        // The ledger's idea of the wallet was wrong: resync before the next trade
+        let short_of_funds = |f: &Result<FillReport>| {
+            f.as_ref().is_err_and(|e| {
+                matches!(ClobError::find(e), Some(ClobError::InsufficientBalance(_)))
+            })
+        };
+        if short_of_funds(&eth_fill) || short_of_funds(&btc_fill) {
+            if let Err(e) = self.refresh_balance().await {
+                warn!("⚠️  Balance refresh after rejection failed: {}", e);
+            }
+        }
+
+        // Before selling anything back: cancel what may still rest and size
+        // the unwind on what really matched
+        let paired = matches!(
+            (&eth_fill, &btc_fill),
+            (Ok(eth), Ok(btc)) if eth.filled_size == btc.filled_size
+        );
+        let (eth_fill, btc_fill) = if paired {
+            (eth_fill, btc_fill)
+        } else {
+            (self.confirm_fill(eth_fill).await, self.confirm_fill(btc_fill).await)
+        };
+
+        match (eth_fill, btc_fill) {
+            (Ok(eth), Ok(btc)) if eth.filled_size > btc.filled_size => {
+                warn!(
+                    "⚠️  BTC leg filled {} of ETH's {} — unwinding ETH excess",
+                    btc.filled_size, eth.filled_size
+                );
+                self.unwind_leg(
+                    &OrderIntent {
+                        size: eth.filled_size - btc.filled_size,
+                        ..eth_leg
+                    },
+                    phase,
+                    &reservation,
+                )
+                .await?;
+            }
+            (Ok(eth), Ok(btc)) if btc.filled_size > eth.filled_size => {
+                warn!(
+                    "⚠️  ETH leg filled {} of BTC's {} — unwinding BTC excess",
+                    eth.filled_size, btc.filled_size
+                );
+                self.unwind_leg(
+                    &OrderIntent {
+                        size: btc.filled_size - eth.filled_size,
+                        ..btc_leg
+                    },
+                    phase,
+                    &reservation,
+                )
+                .await?;
+            }
+            (Ok(_), Ok(_)) => {}
+            // Read-back already failed: selling could leave us short if the
+            // other leg did fill, so both stay for a human to reconcile
+            (Ok(_), Err(e)) if outcome_unknown(&e) => {
+                warn!("⚠️  BTC leg outcome unknown, ETH leg filled — keeping it: {}", e);
+                return Err(e);
+            }
+            (Err(e), Ok(_)) if outcome_unknown(&e) => {
+                warn!("⚠️  ETH leg outcome unknown, BTC leg filled — keeping it: {}", e);
+                return Err(e);
+            }
+            (Ok(eth), Err(e)) => {
+                warn!("⚠️  BTC leg failed, ETH leg filled — unwinding: {}", e);
+                self.unwind_leg(
+                    &OrderIntent {
+                        size: eth.filled_size,
+                        ..eth_leg
+                    },
+                    phase,
+                    &reservation,
+                )
+                .await?;
+                return Err(e);
+            }
+            (Err(e), Ok(btc)) => {
+                warn!("⚠️  ETH leg failed, BTC leg filled — unwinding: {}", e);
+                self.unwind_leg(
+                    &OrderIntent {
+                        size: btc.filled_size,
+                        ..btc_leg
+                    },
+                    phase,
+                    &reservation,
+                )
+                .await?;
+                return Err(e);
+            }
+            (Err(e), Err(other)) => {
+                if outcome_unknown(&e) || outcome_unknown(&other) {
+                    warn!(
+                        "⚠️  Neither leg confirmed, outcome unknown — check positions: {} / {}",
+                        e, other
+                    );
+                }
+                return Err(e);
+            }
+        }
+
+        drop(reservation);
+        info!("💼 Capital: {}", self.ledger.snapshot());
 
         Ok(())
     }
 
-    async fn place_leg(
+    /// Sell back a leg whose partner failed, priced through the book by
+    /// UNWIND_SLIPPAGE (default: 0.02) so it actually crosses.
+    async fn unwind_leg(
         &self,
-        token_id: &str,
-        side: u8, // 0 BUY, 1 SELL
-        price: Decimal,
-        size: Decimal,
+        leg: &OrderIntent,
+        phase: TradingPhase,
+        reservation: &Reservation,
     ) -> Result<()> {
-        let price_u256 = to_u256_scaled(price);
-        let size_u256 = to_u256_scaled(size);
-        
+        let slippage = std::env::var("UNWIND_SLIPPAGE")
+            .ok()
+            .and_then(|v| v.parse::<Decimal>().ok())
+            .unwrap_or(dec!(0.02));
+
+        // Read UNWIND_RETRIES from env (default: 3)
+        let retries = std::env::var("UNWIND_RETRIES")
+            .ok()
+            .and_then(|v| v.parse::<u32>().ok())
+            .unwrap_or(3);
+
+        let mut unwind = OrderIntent {
+            side: Side::Sell,
+            price: (leg.price - slippage).max(dec!(0.01)),
+            ..leg.clone()
+        };
+
+        // Refusals are sent again, no-matches and partial fills go deeper
+        // into the book; anything else leaves the rest to the resolution
+        let (mut sold, mut proceeds) = (Decimal::ZERO, Decimal::ZERO);
+        let mut attempt = 0;
+        let result = loop {
+            let e = match self.place_leg(&unwind, phase, reservation).await {
+                Ok(fill) => {
+                    sold += fill.filled_size;
+                    proceeds += fill.filled_size * fill.avg_price;
+                    unwind.size -= fill.filled_size;
+                    if unwind.size <= Decimal::ZERO {
+                        break Ok(());
+                    }
+                    let left = format!("{} of {} left unsold", unwind.size, leg.size);
+                    ClobError::NoMatch(left).into()
+                }
+                Err(e) => e,
+            };
+            if attempt >= retries {
+                break Err(e);
+            }
+            attempt += 1;
+
+            match ClobError::find(&e) {
+                Some(ClobError::RateLimited {
+                    retry_after: Some(wait),
+                }) => tokio::time::sleep(*wait).await,
+                Some(err) if err.is_retryable() => {
+                    tokio::time::sleep(std::time::Duration::from_millis(250 << attempt)).await
+                }
+                Some(ClobError::NoMatch(_)) if unwind.price > dec!(0.01) => {
+                    unwind.price = (unwind.price - slippage).max(dec!(0.01));
+                    warn!("↘️  Unwind found no match — retrying at {}", unwind.price);
+                }
+                Some(ClobError::MarketClosed(_)) => {
+                    warn!("🔒 Market closed — {} rides to resolution", unwind.token_id);
+                    break Err(e);
+                }
+                _ => break Err(e),
+            }
+        };
+
+        if sold > Decimal::ZERO {
+            self.journal.record(JournalEvent::LegUnwound {
+                token_id: unwind.token_id.clone(),
+                size: sold.to_f64().unwrap_or(0.0),
+                price: (proceeds / sold).to_f64().unwrap_or(0.0),
+            });
+        }
+
+        result
+    }
+
+    /// Make sure nothing of `fill`'s order still rests and report what it
+    /// really matched, so an unwind only sells shares we hold
+    async fn confirm_fill(&self, fill: Result<FillReport>) -> Result<FillReport> {
+        let fill = fill?;
+        let Some(order_id) = fill.order_id.as_deref() else {
+            return Ok(fill);
+        };
+
+        let mut status = match self.backend.order_status(order_id).await {
+            Ok(status) => status,
+            Err(e) => {
+                info!("🔎 Could not read back {} ({}) — using the reported fill", order_id, e);
+                return Ok(fill);
+            }
+        };
+        if status.live {
+            warn!("🧹 {} still rests — cancelling the remainder", order_id);
+            if let Err(e) = self.backend.cancel(order_id).await {
+                warn!("⚠️  Cancel of {} failed: {}", order_id, e);
+            }
+            // Matches may have landed before the cancel did
+            if let Ok(after) = self.backend.order_status(order_id).await {
+                status = after;
+            }
+        }
+
+        if status.matched != fill.filled_size {
+            warn!(
+                "⚠️  {} matched {}, not the {} reported",
+                order_id, status.matched, fill.filled_size
+            );
+        }
+        Ok(FillReport {
+            filled_size: status.matched,
+            ..fill
+        })
+    }
+
+    /// Cancel resting orders and flush the journal. Called once at exit.
+    pub async fn shutdown(&self) -> Result<()> {
+        match self.backend.cancel_all().await {
+            Ok(()) => self.journal.record(JournalEvent::OrdersCancelled),
+            Err(e) => warn!("⚠️  Cancel-all failed during shutdown: {}", e),
+        }
+
+        self.journal.record(JournalEvent::Shutdown);
+        self.journal.flush()
+    }
+
+    async fn place_leg(
+        &self,
+        leg: &OrderIntent,
+        phase: TradingPhase,
+        reservation: &Reservation,
+    ) -> Result<FillReport> {
+        // Every order passes the risk gate right before submission
+        let hold = match self.risk.check(std::slice::from_ref(leg)) {
+            Ok(hold) => hold,
+            Err(reason) => {
+                warn!("🚫 Risk rejected order {}: {}", leg.token_id, reason);
+                self.journal.record(JournalEvent::RiskRejected {
+                    reason: reason.to_string(),
+                });
+                return Err(reason.into());
+            }
+        };
+
+        let signed = self
+            .sign_leg(leg, self.fees.taker_bps(&leg.token_id), now_ts() + 300)
+            .await?;
+
+        self.send_leg(leg, signed, phase, reservation, &hold).await
+    }
+
+    /// Submit a signed leg and book whatever fills
+    async fn send_leg(
+        &self,
+        leg: &OrderIntent,
+        (order, sig): (ClobOrder, ethers::types::Signature),
+        phase: TradingPhase,
+        reservation: &Reservation,
+        hold: &RiskHold,
+    ) -> Result<FillReport> {
+        let order_id = order.order_id();
+        let result = self.backend.submit(leg, order, sig).await;
+        let result = self.read_back(leg, order_id.as_deref(), result).await;
+        self.book_leg(leg, result, phase, reservation, hold)
+    }
+
+    /// Submit signed legs in one batch and book each. One result per leg,
+    /// in order; a batch that never landed fails every leg.
+    async fn send_legs(
+        &self,
+        legs: Vec<(&OrderIntent, (ClobOrder, ethers::types::Signature))>,
+        phase: TradingPhase,
+        reservation: &Reservation,
+        hold: &RiskHold,
+    ) -> Vec<Result<FillReport>> {
+        let intents: Vec<&OrderIntent> = legs.iter().map(|(leg, _)| *leg).collect();
+        let order_ids: Vec<Option<String>> = legs
+            .iter()
+            .map(|(_, (order, _))| order.order_id())
+            .collect();
+        let orders = legs
+            .into_iter()
+            .map(|(leg, (order, sig))| (leg, order, sig))
+            .collect();
+
+        let mut results = match self.backend.submit_batch(orders).await {
+            Ok(results) => results.into_iter(),
+            Err(e) => {
+                intents
+                    .iter()
+                    .map(|_| Err(ClobError::copy(&e)))
+                    .collect::<Vec<_>>()
+                    .into_iter()
+            }
+        };
+
+        let mut fills = Vec::with_capacity(intents.len());
+        for (leg, order_id) in intents.into_iter().zip(order_ids) {
+            let result = results
+                .next()
+                .unwrap_or_else(|| Err(anyhow!("No batch result for {}", leg.token_id)));
+            let result = self.read_back(leg, order_id.as_deref(), result).await;
+            fills.push(self.book_leg(leg, result, phase, reservation, hold));
+        }
+        fills
+    }
+
+    /// Settle an order whose outcome is unknown by looking it up under its
+    /// hash: cancel anything still resting and report what it matched.
+    /// Other results, and orders that can't be found, pass through.
+    async fn read_back(
+        &self,
+        leg: &OrderIntent,
+        order_id: Option<&str>,
+        result: Result<FillReport>,
+    ) -> Result<FillReport> {
+        let e = match result {
+            Err(e) if outcome_unknown(&e) => e,
+            other => return other,
+        };
+        let Some(order_id) = order_id else {
+            return Err(e);
+        };
+
+        // The matching engine may still be working through it
+        let mut status = None;
+        for attempt in 0..3u32 {
+            tokio::time::sleep(std::time::Duration::from_millis(250 << attempt)).await;
+            match self.backend.order_status(order_id).await {
+                Ok(s) => {
+                    status = Some(s);
+                    break;
+                }
+                Err(lookup) => info!("🔎 {} not readable yet: {}", order_id, lookup),
+            }
+        }
+        let Some(mut status) = status else {
+            warn!("⚠️  {} outcome still unknown after read-back: {}", order_id, e);
+            return Err(e);
+        };
+
+        if status.live {
+            warn!("🧹 {} rests after an unknown outcome — cancelling", order_id);
+            if let Err(cancel) = self.backend.cancel(order_id).await {
+                warn!("⚠️  Cancel of {} failed: {}", order_id, cancel);
+            }
+            if let Ok(after) = self.backend.order_status(order_id).await {
+                status = after;
+            }
+        }
+
+        info!("🔎 {} read back after '{}': matched {}", order_id, e, status.matched);
+        if status.matched.is_zero() {
+            return Err(ClobError::NoMatch(format!("order {} matched nothing", order_id)).into());
+        }
+        Ok(FillReport {
+            order_id: Some(order_id.to_string()),
+            filled_size: status.matched,
+            avg_price: leg.price,
+            latency: SubmitLatency::default(),
+        })
+    }
+
+    /// Book a submitted leg's fill against the ledger, risk and journal
+    fn book_leg(
+        &self,
+        leg: &OrderIntent,
+        result: Result<FillReport>,
+        phase: TradingPhase,
+        reservation: &Reservation,
+        hold: &RiskHold,
+    ) -> Result<FillReport> {
+        let token_id = leg.token_id.as_str();
+        let side_str = leg.side.as_str().to_uppercase();
+        let price_f = leg.price.to_f64().unwrap_or(0.0);
+        let size_f = leg.size.to_f64().unwrap_or(0.0);
+
+        match result {
+            Ok(fill) => {
+                info!("✅ Order submitted {} (matched {})", token_id, fill.filled_size);
+                let notional = fill.filled_size * fill.avg_price;
+                if fill.filled_size > Decimal::ZERO {
+                    match leg.side {
+                        Side::Buy => reservation.record_fill(
+                            token_id,
+                            &leg.condition_id,
+                            fill.filled_size,
+                            notional,
+                        ),
+                        Side::Sell => self
+                            .ledger
+                            .record_sell(token_id, fill.filled_size, notional),
+                    }
+                    hold.record_fill(&OrderIntent {
+                        size: fill.filled_size,
+                        price: fill.avg_price,
+                        ..leg.clone()
+                    });
+                }
+                self.journal.record(JournalEvent::OrderSubmitted {
+                    token_id: token_id.to_string(),
+                    side: side_str,
+                    price: price_f,
+                    size: size_f,
+                    filled: fill.filled_size.to_f64().unwrap_or(0.0),
+                    order_id: fill.order_id.clone(),
+                    phase,
+                });
+                Ok(fill)
+            }
+            Err(e) => {
+                let clob = ClobError::find(&e);
+                warn!("❌ Order rejected {} → {}", token_id, e);
+                if let Some(clob) = clob {
+                    self.risk.record_rejection(leg, clob);
+                }
+                self.journal.record(JournalEvent::OrderFailed {
+                    token_id: token_id.to_string(),
+                    side: side_str,
+                    price: price_f,
+                    size: size_f,
+                    error: e.to_string(),
+                    kind: clob.map(|c| c.kind().to_string()),
+                    phase,
+                });
+                Err(e)
+            }
+        }
+    }
+
+    /// Log and journal how long each stage of a trade took
+    fn record_timings(&self, opportunity: &ArbitrageOpportunity, timings: &StageTimings) {
+        info!("⏱️  {}", timings);
+        self.journal.record(JournalEvent::TradeTimings {
+            eth_token_id: opportunity.eth_up_token_id.clone(),
+            btc_token_id: opportunity.btc_down_token_id.clone(),
+            detect_ms: ms(timings.detect),
+            size_ms: ms(timings.size),
+            sign_ms: ms(timings.sign),
+            send_ms: ms(timings.send),
+            ack_ms: ms(timings.ack),
+            total_ms: ms(timings.total()),
+        });
+    }
+
+    /// Build and sign the CLOB order for `leg`
+    async fn sign_leg(
+        &self,
+        leg: &OrderIntent,
+        fee_rate_bps: u32,
+        expiration: u64,
+    ) -> Result<(ClobOrder, ethers::types::Signature)> {
+        let order = self.build_order(leg, fee_rate_bps, expiration)?;
+        let sig = self.signer.sign_order(&order).await?;
+        Ok((order, sig))
+    }
+
+    /// The CLOB order for `leg`. `fee_rate_bps` must match the market's
+    /// rate for the order's role or the CLOB rejects it; `expiration` 0
+    /// never expires.
+    fn build_order(
+        &self,
+        leg: &OrderIntent,
+        fee_rate_bps: u32,
+        expiration: u64,
+    ) -> Result<ClobOrder> {
+        let side: u8 = match leg.side {
+            Side::Buy => 0,
+            Side::Sell => 1,
+        };
+
+        let price_u256 = to_u256_scaled(leg.price);
+        let size_u256 = to_u256_scaled(leg.size);
+
         // Calculate maker/taker amounts
         let (maker_amount, taker_amount) = if side == 0 {
             // BUY: makerAmount = price × size, takerAmount = size
@@ -167,54 +820,40 @@ impl Trader {
             // SELL: makerAmount = size, takerAmount = price × size
             (size_u256, price_u256 * size_u256 / U256::from(1_000_000))
         };
-        
+
         let order = ClobOrder {
             salt: U256::from(::rand::random::<u64>()),
             maker: Address::from_str(&self.wallet.proxy_wallet)?,
             signer: self.signer.address(),
             taker: Address::zero(),
-            token_id: str_to_h256(token_id),
+            token_id: str_to_h256(&leg.token_id),
             maker_amount,
             taker_amount,
             side,
-            fee_rate_bps: U256::zero(),
+            fee_rate_bps: U256::from(fee_rate_bps),
             nonce: make_nonce(),
-            expiration: U256::from(now_ts() + 300),
+            expiration: U256::from(expiration),
         };
 
-        let sig = self.signer.sign_order(&order).await?;
-
-        match self
-            .clob
-            .submit_order(order, sig, &self.wallet.proxy_wallet)
-            .await
-        {
-            Ok(_) => info!("✅ Order submitted {}", token_id),
-            Err(e) => warn!("❌ Order rejected {} → {}", token_id, e),
-        }
-
-        Ok(())
+        Ok(order)
     }
 
     // ==================================================
     // POSITION SIZING
     // ==================================================
 
-    async fn calculate_position_size(&self, opportunity: &ArbitrageOpportunity) -> Result<f64> {
-        let bal = self.live_usdc_balance.lock().await;
-        let balance = bal.to_f64().unwrap_or(0.0);
-        let cost = opportunity.total_cost.to_f64().unwrap_or(1.0);
-
-        let spend = match self.sizing.mode {
-            TradeMode::Fixed => self.sizing.fixed_usdc.unwrap_or(0.0),
-            TradeMode::Percentage => balance * (self.sizing.percentage.unwrap_or(10.0) / 100.0),
-            TradeMode::Dynamic => {
-                let edge = opportunity.expected_profit.to_f64().unwrap_or(0.0);
-                (balance * 0.01 * (1.0 + edge)).min(balance * 0.25)
-            }
-            TradeMode::Free => balance,
-        };
+    async fn calculate_position_size(
+        &self,
+        opportunity: &ArbitrageOpportunity,
+    ) -> Result<LegShares> {
+        let capital = self.ledger.snapshot();
 
-        Ok((spend / cost).floor())
+        Ok(self.sizer.size(&SizingInput {
+            opportunity,
+            balance: capital.wallet.to_f64().unwrap_or(0.0),
+            reserved: capital.reserved.to_f64().unwrap_or(0.0),
+            eth_depth: opportunity.eth_depth.and_then(|d| d.to_f64()),
+            btc_depth: opportunity.btc_depth.and_then(|d| d.to_f64()),
+        }))
     }
 }
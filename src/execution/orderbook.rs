@@ -1,5 +1,5 @@
 use anyhow::{anyhow, Result};
-use reqwest::Client;
+use rust_decimal::Decimal;
 use serde::Deserialize;
 
 use crate::client::PolymarketClient;
@@ -34,12 +34,12 @@ FETCH ORDERBOOK - Using /price endpoint (CORRECT DATA)
 =============================== */
 
 pub async fn fetch_orderbook(api: &PolymarketClient, token_id: &str) -> Result<OrderBook> {
-    let client = Client::new();
+    let client = api.http();
 
     // Fetch BID price (what we can SELL for)
     let bid_url = format!("{}/price?token_id={}&side=BUY", api.clob_url, token_id);
 
-    let bid_response = client.get(&bid_url).send().await?;
+    let bid_response = client.send(client.get(&bid_url)).await?;
 
     if !bid_response.status().is_success() {
         return Err(anyhow!(
@@ -57,7 +57,7 @@ pub async fn fetch_orderbook(api: &PolymarketClient, token_id: &str) -> Result<O
     // Fetch ASK price (what we must PAY to buy)
     let ask_url = format!("{}/price?token_id={}&side=SELL", api.clob_url, token_id);
 
-    let ask_response = client.get(&ask_url).send().await?;
+    let ask_response = client.send(client.get(&ask_url)).await?;
 
     if !ask_response.status().is_success() {
         return Err(anyhow!(
@@ -73,8 +73,56 @@ pub async fn fetch_orderbook(api: &PolymarketClient, token_id: &str) -> Result<O
         .map_err(|e| anyhow!("Failed to parse ask price: {}", e))?;
 
     // Create orderbook with single best bid/ask
+    // /price carries no depth: the sizes are placeholders, never size on them
     Ok(OrderBook {
-        bids: vec![(bid_price, 1.0)], // Size doesn't matter for best price
+        bids: vec![(bid_price, 1.0)],
         asks: vec![(ask_price, 1.0)],
     })
 }
+
+/* ===============================
+FETCH FULL LADDER - /book endpoint
+=============================== */
+
+#[derive(Debug, Deserialize)]
+struct BookLevel {
+    price: String,
+    size: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct BookResponse {
+    bids: Vec<BookLevel>,
+    asks: Vec<BookLevel>,
+}
+
+/// Full depth as (price, size), bids best-first (descending) and asks
+/// best-first (ascending).
+pub type Ladder = (Vec<(Decimal, Decimal)>, Vec<(Decimal, Decimal)>);
+
+pub async fn fetch_book_ladder(api: &PolymarketClient, token_id: &str) -> Result<Ladder> {
+    let url = format!("{}/book?token_id={}", api.clob_url, token_id);
+
+    let client = api.http();
+    let response = client.send(client.get(&url)).await?;
+
+    if !response.status().is_success() {
+        return Err(anyhow!("Failed to fetch book: {}", response.status()));
+    }
+
+    let book: BookResponse = response.json().await?;
+
+    let parse = |levels: Vec<BookLevel>| -> Vec<(Decimal, Decimal)> {
+        levels
+            .into_iter()
+            .filter_map(|l| Some((l.price.parse().ok()?, l.size.parse().ok()?)))
+            .collect()
+    };
+
+    let mut bids = parse(book.bids);
+    let mut asks = parse(book.asks);
+    bids.sort_by_key(|l| std::cmp::Reverse(l.0));
+    asks.sort_by_key(|l| l.0);
+
+    Ok((bids, asks))
+}
@@ -0,0 +1,609 @@
+use crate::cache::{now_ms, CachedOrderbook, PriceCache};
+use crate::client::PolymarketClient;
+use crate::domain::order::Side;
+use crate::execution::backend::{ExecutionBackend, FillReport, OrderStatus};
+use crate::execution::errors::ClobError;
+use crate::execution::latency::SubmitLatency;
+use crate::execution::orderbook::fetch_book_ladder;
+use crate::journal::{JournalEvent, TradeJournal};
+use crate::risk::OrderIntent;
+use crate::strategy::fees::fee_per_share;
+use crate::wallet::signer::ClobOrder;
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use ethers::types::Signature;
+use log::{info, warn};
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::env;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::time::{sleep, Duration, Instant};
+
+// ==================================================
+// CONFIG
+// ==================================================
+
+#[derive(Debug, Clone)]
+pub struct PaperConfig {
+    pub starting_usdc: Decimal,
+    /// Delay between "send" and matching against the book
+    pub latency: Duration,
+    /// Share of each displayed level we assume is reachable ahead of the
+    /// queue (1.0 = we get everything shown)
+    pub queue_fraction: Decimal,
+    /// Cached ladders older than this are refreshed over REST
+    pub max_book_age_ms: u128,
+}
+
+impl PaperConfig {
+    pub fn from_env() -> Self {
+        fn env_f64(key: &str, default: f64) -> f64 {
+            env::var(key)
+                .ok()
+                .and_then(|v| v.parse::<f64>().ok())
+                .unwrap_or(default)
+        }
+
+        Self {
+            starting_usdc: Decimal::from_f64(env_f64("PAPER_STARTING_USDC", 1000.0))
+                .unwrap_or_default(),
+            latency: Duration::from_millis(env_f64("PAPER_LATENCY_MS", 250.0) as u64),
+            queue_fraction: Decimal::from_f64(env_f64("PAPER_QUEUE_FRACTION", 0.5).clamp(0.0, 1.0))
+                .unwrap_or(Decimal::ONE),
+            max_book_age_ms: env_f64("PAPER_MAX_BOOK_AGE_MS", 2000.0) as u128,
+        }
+    }
+}
+
+// ==================================================
+// ACCOUNT
+// ==================================================
+
+#[derive(Debug, Clone, Default)]
+pub struct PaperPosition {
+    pub condition_id: String,
+    pub size: Decimal,
+    pub cost: Decimal,
+}
+
+#[derive(Debug, Default)]
+struct PaperAccount {
+    usdc: Decimal,
+    realized_pnl: Decimal,
+    positions: HashMap<String, PaperPosition>,
+}
+
+/// A paper GTC order
+#[derive(Debug, Clone)]
+struct RestingOrder {
+    intent: OrderIntent,
+    fee_bps: u32,
+    matched: Decimal,
+    cancelled: bool,
+}
+
+// ==================================================
+// MATCHING
+// ==================================================
+
+/// Walk a ladder (best level first) up to the limit price, taking
+/// `queue_fraction` of each level. Returns (filled, notional).
+pub fn match_ladder(
+    ladder: &[(Decimal, Decimal)],
+    side: &Side,
+    limit: Decimal,
+    size: Decimal,
+    queue_fraction: Decimal,
+) -> (Decimal, Decimal) {
+    let mut remaining = size;
+    let mut notional = Decimal::ZERO;
+
+    for &(price, level_size) in ladder {
+        let crosses = match side {
+            Side::Buy => price <= limit,
+            Side::Sell => price >= limit,
+        };
+        if !crosses || remaining <= Decimal::ZERO {
+            break;
+        }
+
+        let fill = remaining.min(level_size * queue_fraction);
+        notional += fill * price;
+        remaining -= fill;
+    }
+
+    (size - remaining, notional)
+}
+
+/// Whether paper orders may fill against a cached book. It must be a full
+/// ladder, since a price change only caches size-1 placeholders at the
+/// touch; live it must also be fresh, while offline it is the recorded book.
+fn cached_book_usable(book: &CachedOrderbook, online: bool, max_age_ms: u128, now: u128) -> bool {
+    book.full_depth && (!online || now.saturating_sub(book.last_update_ms) <= max_age_ms)
+}
+
+// ==================================================
+// PAPER BACKEND
+// ==================================================
+
+/// Simulated execution against the live book. Submitted orders are IOC:
+/// whatever the reachable ladder cannot fill at the limit price is dropped.
+/// Resting orders fill at their own price once the other side of the book
+/// trades through them — joining a level is never assumed to fill.
+pub struct PaperBackend {
+    /// None when offline (replays): no REST book fallback or resolution polling
+    api: Option<Arc<PolymarketClient>>,
+    cache: PriceCache,
+    config: PaperConfig,
+    journal: Arc<TradeJournal>,
+    account: Mutex<PaperAccount>,
+    resting: Mutex<HashMap<String, RestingOrder>>,
+    next_id: AtomicU64,
+}
+
+impl PaperBackend {
+    pub fn new(
+        api: Arc<PolymarketClient>,
+        cache: PriceCache,
+        config: PaperConfig,
+        journal: Arc<TradeJournal>,
+    ) -> Self {
+        Self::build(Some(api), cache, config, journal)
+    }
+
+    /// Fill only against what is pushed into `cache`; never calls the API.
+    pub fn offline(cache: PriceCache, config: PaperConfig, journal: Arc<TradeJournal>) -> Self {
+        Self::build(None, cache, config, journal)
+    }
+
+    fn build(
+        api: Option<Arc<PolymarketClient>>,
+        cache: PriceCache,
+        config: PaperConfig,
+        journal: Arc<TradeJournal>,
+    ) -> Self {
+        info!("🧪 Paper trading enabled:");
+        info!("   Starting USDC: ${}", config.starting_usdc);
+        info!("   Latency: {:?}", config.latency);
+        info!("   Queue fraction: {}", config.queue_fraction);
+
+        let account = PaperAccount {
+            usdc: config.starting_usdc,
+            ..Default::default()
+        };
+
+        Self {
+            api,
+            cache,
+            config,
+            journal,
+            account: Mutex::new(account),
+            resting: Mutex::new(HashMap::new()),
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    pub fn positions(&self) -> HashMap<String, PaperPosition> {
+        self.lock().positions.clone()
+    }
+
+    pub fn realized_pnl(&self) -> Decimal {
+        self.lock().realized_pnl
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, PaperAccount> {
+        self.account.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn lock_resting(&self) -> std::sync::MutexGuard<'_, HashMap<String, RestingOrder>> {
+        self.resting.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn next_order_id(&self) -> String {
+        format!("paper-{}", self.next_id.fetch_add(1, Ordering::Relaxed))
+    }
+
+    /// Move cash and shares for a fill. Returns the USDC left.
+    fn book_fill(
+        &self,
+        intent: &OrderIntent,
+        filled: Decimal,
+        notional: Decimal,
+        fee: Decimal,
+    ) -> Result<Decimal> {
+        let mut account = self.lock();
+
+        match intent.side {
+            Side::Buy => {
+                let notional = notional + fee;
+                if account.usdc < notional {
+                    return Err(ClobError::InsufficientBalance(format!(
+                        "paper order needs ${} USDC, have ${}",
+                        notional, account.usdc
+                    ))
+                    .into());
+                }
+                account.usdc -= notional;
+
+                let pos = account
+                    .positions
+                    .entry(intent.token_id.clone())
+                    .or_default();
+                pos.condition_id = intent.condition_id.clone();
+                pos.size += filled;
+                pos.cost += notional;
+            }
+            Side::Sell => {
+                let pos = account
+                    .positions
+                    .get_mut(&intent.token_id)
+                    .filter(|p| p.size >= filled)
+                    .ok_or_else(|| {
+                        ClobError::InsufficientBalance("not enough paper shares to sell".into())
+                    })?;
+
+                let avg_cost = pos.cost / pos.size;
+                pos.size -= filled;
+                pos.cost -= filled * avg_cost;
+                if pos.size <= Decimal::ZERO {
+                    account.positions.remove(&intent.token_id);
+                }
+
+                account.usdc += notional - fee;
+                account.realized_pnl += notional - fee - filled * avg_cost;
+            }
+        }
+
+        Ok(account.usdc)
+    }
+
+    /// Ladder for one side of the book, preferring the WS cache
+    async fn ladder(&self, token_id: &str, side: &Side) -> Result<Vec<(Decimal, Decimal)>> {
+        let online = self.api.is_some();
+        let cached = self
+            .cache
+            .get(token_id)
+            .await
+            .filter(|b| cached_book_usable(b, online, self.config.max_book_age_ms, now_ms()));
+
+        let (bids, asks) = match (cached, &self.api) {
+            (Some(book), _) => (book.bids, book.asks),
+            (None, Some(api)) => {
+                let (bids, asks) = fetch_book_ladder(api, token_id).await?;
+                self.cache
+                    .update(token_id, bids.clone(), asks.clone())
+                    .await;
+                (bids, asks)
+            }
+            (None, None) => return Err(anyhow!("No recorded book for {}", token_id)),
+        };
+
+        Ok(match side {
+            Side::Buy => asks,
+            Side::Sell => bids,
+        })
+    }
+
+    /// Pay out paper positions in `condition_id` by the real outcome.
+    ///
+    /// Returns the payout, or None while the market has not resolved yet.
+    pub async fn settle_market(&self, condition_id: &str) -> Result<Option<Decimal>> {
+        let api = self
+            .api
+            .as_ref()
+            .ok_or_else(|| anyhow!("Offline paper backend cannot query resolutions"))?;
+        let details = api.get_market_details(condition_id).await?;
+
+        let Some(winner) = details.tokens.iter().find(|t| t.winner) else {
+            return Ok(None);
+        };
+
+        Ok(Some(self.settle_with_winner(
+            condition_id,
+            &winner.token_id,
+            &winner.outcome,
+        )))
+    }
+
+    /// Pay out paper positions in `condition_id` given its winning token.
+    /// Returns the total paid out.
+    pub fn settle_with_winner(
+        &self,
+        condition_id: &str,
+        winner_token_id: &str,
+        outcome: &str,
+    ) -> Decimal {
+        let mut account = self.lock();
+        let mut total = Decimal::ZERO;
+        let tokens: Vec<String> = account
+            .positions
+            .iter()
+            .filter(|(_, p)| p.condition_id == condition_id)
+            .map(|(t, _)| t.clone())
+            .collect();
+
+        for token_id in tokens {
+            let Some(pos) = account.positions.remove(&token_id) else {
+                continue;
+            };
+
+            let payout = if token_id == winner_token_id {
+                pos.size
+            } else {
+                Decimal::ZERO
+            };
+            let pnl = payout - pos.cost;
+
+            account.usdc += payout;
+            account.realized_pnl += pnl;
+            total += payout;
+
+            info!(
+                "🧪 Paper settled {} ({}) → payout ${} pnl ${}",
+                token_id, outcome, payout, pnl
+            );
+            self.journal.record(JournalEvent::PaperSettled {
+                condition_id: condition_id.to_string(),
+                token_id,
+                payout: payout.to_f64().unwrap_or(0.0),
+                pnl: pnl.to_f64().unwrap_or(0.0),
+            });
+        }
+
+        total
+    }
+
+    /// Poll until `condition_id` resolves (or `timeout` passes), then settle.
+    /// Returns the payout, None if it never resolved.
+    pub async fn settle_when_resolved(
+        &self,
+        condition_id: &str,
+        timeout: Duration,
+    ) -> Option<Decimal> {
+        let deadline = tokio::time::Instant::now() + timeout;
+
+        while tokio::time::Instant::now() < deadline {
+            match self.settle_market(condition_id).await {
+                Ok(Some(payout)) => return Some(payout),
+                Ok(None) => {}
+                Err(e) => warn!("⚠️  Paper settlement check failed: {}", e),
+            }
+            sleep(Duration::from_secs(30)).await;
+        }
+
+        warn!(
+            "⚠️  {} did not resolve in time — paper positions left open",
+            condition_id
+        );
+        None
+    }
+}
+
+#[async_trait]
+impl ExecutionBackend for PaperBackend {
+    fn name(&self) -> &'static str {
+        "paper"
+    }
+
+    async fn usdc_balance(&self) -> Result<Decimal> {
+        Ok(self.lock().usdc)
+    }
+
+    async fn ensure_trading_ready(&self, required_usdc: u128) -> Result<()> {
+        let required = Decimal::from(required_usdc) / Decimal::from(1_000_000);
+        let usdc = self.lock().usdc;
+
+        if usdc < required {
+            return Err(anyhow!(
+                "❌ Insufficient paper USDC. Need: {}, Have: {}",
+                required,
+                usdc
+            ));
+        }
+        Ok(())
+    }
+
+    async fn submit(
+        &self,
+        intent: &OrderIntent,
+        order: ClobOrder,
+        _sig: Signature,
+    ) -> Result<FillReport> {
+        let started = Instant::now();
+        sleep(self.config.latency).await;
+        let sent = started.elapsed();
+
+        let ladder = self.ladder(&intent.token_id, &intent.side).await?;
+        let (filled, notional) = match_ladder(
+            &ladder,
+            &intent.side,
+            intent.price,
+            intent.size,
+            self.config.queue_fraction,
+        );
+
+        if filled <= Decimal::ZERO {
+            return Err(ClobError::NoMatch(format!(
+                "no paper liquidity at {} for {}",
+                intent.price, intent.token_id
+            ))
+            .into());
+        }
+
+        // Charge the fee rate the order was signed with
+        let fee_bps = order.fee_rate_bps.low_u32();
+        let fee = fee_per_share(notional / filled, fee_bps) * filled;
+
+        let usdc = self.book_fill(intent, filled, notional, fee)?;
+
+        let order_id = self.next_order_id();
+        let avg_price = notional / filled;
+
+        info!(
+            "🧪 Paper fill {} {} {}/{} @ {} fee ${} (USDC ${})",
+            order_id,
+            intent.side.as_str(),
+            filled,
+            intent.size,
+            avg_price.round_dp(4),
+            fee.round_dp(4),
+            usdc.round_dp(2)
+        );
+
+        Ok(FillReport {
+            order_id: Some(order_id),
+            filled_size: filled,
+            avg_price,
+            latency: SubmitLatency {
+                send: sent,
+                ack: started.elapsed() - sent,
+            },
+        })
+    }
+
+    async fn rest(
+        &self,
+        intent: &OrderIntent,
+        order: ClobOrder,
+        _sig: Signature,
+    ) -> Result<Option<String>> {
+        sleep(self.config.latency).await;
+
+        let order_id = self.next_order_id();
+        self.lock_resting().insert(
+            order_id.clone(),
+            RestingOrder {
+                intent: intent.clone(),
+                fee_bps: order.fee_rate_bps.low_u32(),
+                matched: Decimal::ZERO,
+                cancelled: false,
+            },
+        );
+
+        info!(
+            "🧪 Paper resting {} {} {} @ {}",
+            order_id,
+            intent.side.as_str(),
+            intent.size,
+            intent.price
+        );
+        Ok(Some(order_id))
+    }
+
+    async fn order_status(&self, order_id: &str) -> Result<OrderStatus> {
+        let resting = self
+            .lock_resting()
+            .get(order_id)
+            .cloned()
+            .ok_or_else(|| anyhow!("Unknown paper order {}", order_id))?;
+
+        let intent = &resting.intent;
+        let remaining = intent.size - resting.matched;
+        let mut matched = resting.matched;
+
+        if remaining > Decimal::ZERO && !resting.cancelled {
+            let ladder = self.ladder(&intent.token_id, &intent.side).await?;
+            let (filled, _) = match_ladder(
+                &ladder,
+                &intent.side,
+                intent.price,
+                remaining,
+                self.config.queue_fraction,
+            );
+
+            if filled > Decimal::ZERO {
+                // A resting order fills at its own price
+                let notional = filled * intent.price;
+                let fee = fee_per_share(intent.price, resting.fee_bps) * filled;
+                let usdc = self.book_fill(intent, filled, notional, fee)?;
+                matched += filled;
+
+                if let Some(order) = self.lock_resting().get_mut(order_id) {
+                    order.matched = matched;
+                }
+
+                info!(
+                    "🧪 Paper maker fill {} {} {}/{} @ {} fee ${} (USDC ${})",
+                    order_id,
+                    intent.side.as_str(),
+                    matched,
+                    intent.size,
+                    intent.price,
+                    fee.round_dp(4),
+                    usdc.round_dp(2)
+                );
+            }
+        }
+
+        Ok(OrderStatus {
+            order_id: order_id.to_string(),
+            matched,
+            live: matched < intent.size && !resting.cancelled,
+        })
+    }
+
+    async fn cancel(&self, order_id: &str) -> Result<()> {
+        // Kept so a status check after the cancel still sees its fills
+        if let Some(order) = self.lock_resting().get_mut(order_id) {
+            order.cancelled = true;
+        }
+        Ok(())
+    }
+
+    async fn cancel_all(&self) -> Result<()> {
+        for order in self.lock_resting().values_mut() {
+            order.cancelled = true;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn price_change_cache_does_not_cap_live_fills() {
+        let cache = PriceCache::new();
+        let price_change = json!({ "bid": "0.44", "ask": "0.45" });
+        let book = json!({
+            "bids": [{ "price": "0.44", "size": "200" }],
+            "asks": [{ "price": "0.45", "size": "200" }],
+        });
+
+        cache.update_from_price_ws("tok", &price_change).await;
+        let placeholder = cache.get("tok").await.unwrap();
+
+        // Matching the placeholder would cap a 100-share buy at half a share
+        let (filled, _) = match_ladder(
+            &placeholder.asks,
+            &Side::Buy,
+            dec!(0.45),
+            dec!(100),
+            dec!(0.5),
+        );
+        assert_eq!(filled, dec!(0.5));
+
+        // So live paper goes to REST for the ladder instead
+        let now = placeholder.last_update_ms;
+        assert!(!cached_book_usable(&placeholder, true, 2000, now));
+
+        cache.update_from_price_ws("tok", &book).await;
+        let full = cache.get("tok").await.unwrap();
+        assert!(cached_book_usable(&full, true, 2000, full.last_update_ms));
+        assert!(!cached_book_usable(
+            &full,
+            true,
+            2000,
+            full.last_update_ms + 2001
+        ));
+
+        // Offline a recorded full book never goes stale, placeholders never fill
+        assert!(cached_book_usable(&full, false, 2000, now + 10_000));
+        assert!(!cached_book_usable(&placeholder, false, 2000, now + 10_000));
+    }
+}
@@ -0,0 +1,389 @@
+use crate::config::{Config, PositionSizing, TradeMode};
+use crate::domain::{ArbitrageOpportunity, PayoutOdds};
+use rust_decimal::prelude::ToPrimitive;
+
+// ==================================================
+// INPUT / OUTPUT
+// ==================================================
+
+/// Everything a policy may look at when sizing one pair entry
+#[derive(Debug, Clone, Copy)]
+pub struct SizingInput<'a> {
+    pub opportunity: &'a ArbitrageOpportunity,
+    /// USDC in the wallet
+    pub balance: f64,
+    /// USDC already committed elsewhere (open orders, unsettled legs)
+    pub reserved: f64,
+    /// Shares available at the quoted ask on each leg, when known
+    pub eth_depth: Option<f64>,
+    pub btc_depth: Option<f64>,
+}
+
+impl SizingInput<'_> {
+    /// USDC this trade may draw on
+    pub fn available(&self) -> f64 {
+        (self.balance - self.reserved).max(0.0)
+    }
+
+    /// USDC per pair, both asks
+    pub fn unit_cost(&self) -> f64 {
+        self.opportunity.total_cost.to_f64().unwrap_or(0.0)
+    }
+}
+
+/// Whole shares to buy of each leg
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct LegShares {
+    pub eth: f64,
+    pub btc: f64,
+}
+
+impl LegShares {
+    pub fn is_empty(&self) -> bool {
+        self.eth <= 0.0 || self.btc <= 0.0
+    }
+
+    /// USDC the pair costs at the opportunity's asks
+    pub fn spend(&self, opportunity: &ArbitrageOpportunity) -> f64 {
+        let eth = opportunity.eth_up_price.to_f64().unwrap_or(0.0);
+        let btc = opportunity.btc_down_price.to_f64().unwrap_or(0.0);
+        self.eth * eth + self.btc * btc
+    }
+}
+
+/// Hard bounds every policy's stake is held to
+#[derive(Debug, Clone)]
+pub struct SizingLimits {
+    /// Stakes below this are skipped (the CLOB's $1 minimum)
+    pub min_notional: f64,
+    /// Stakes above this are cut down to it
+    pub max_notional: f64,
+}
+
+impl SizingLimits {
+    /// MIN_TRADE_SIZE / MAX_TRADE_SIZE
+    pub fn from_env() -> Self {
+        Self {
+            min_notional: Config::min_trade_size(),
+            max_notional: Config::max_trade_size(),
+        }
+    }
+}
+
+// ==================================================
+// POLICIES
+// ==================================================
+
+/// How much USDC to put on an opportunity, before limits and depth
+pub trait SizingPolicy: Send + Sync {
+    fn stake(&self, input: &SizingInput) -> f64;
+}
+
+/// FIXED: the same USDC every trade
+pub struct FixedPolicy {
+    pub usdc: f64,
+}
+
+impl SizingPolicy for FixedPolicy {
+    fn stake(&self, _input: &SizingInput) -> f64 {
+        self.usdc
+    }
+}
+
+/// PERCENTAGE: a share of available balance
+pub struct PercentagePolicy {
+    pub percent: f64,
+}
+
+impl SizingPolicy for PercentagePolicy {
+    fn stake(&self, input: &SizingInput) -> f64 {
+        input.available() * self.percent / 100.0
+    }
+}
+
+/// DYNAMIC: `max_risk_percent` of available balance, scaled up by the
+/// edge and never more than a quarter of it
+pub struct DynamicPolicy {
+    pub max_risk_percent: f64,
+}
+
+impl SizingPolicy for DynamicPolicy {
+    fn stake(&self, input: &SizingInput) -> f64 {
+        let available = input.available();
+        let edge = input.opportunity.expected_profit.to_f64().unwrap_or(0.0);
+        (available * self.max_risk_percent / 100.0 * (1.0 + edge)).min(available * 0.25)
+    }
+}
+
+/// FREE: everything available (still held to the notional limits)
+pub struct FreePolicy;
+
+impl SizingPolicy for FreePolicy {
+    fn stake(&self, input: &SizingInput) -> f64 {
+        input.available()
+    }
+}
+
+/// KELLY: `fraction` of the growth-optimal stake, capped at `max_fraction`
+/// of available balance and bounded by its own notional limits
+pub struct KellyPolicy {
+    pub fraction: f64,
+    pub max_fraction: f64,
+    pub min_notional: f64,
+    pub max_notional: f64,
+}
+
+impl SizingPolicy for KellyPolicy {
+    fn stake(&self, input: &SizingInput) -> f64 {
+        let o = input.opportunity;
+        let unit_cost = (o.total_cost + o.fees).to_f64().unwrap_or(0.0);
+
+        // Without payout odds there is nothing to size the risk from
+        let Some(odds) = &o.payout_odds else {
+            return 0.0;
+        };
+        let full = kelly_fraction(odds, unit_cost);
+
+        let stake = (input.available() * (full * self.fraction).min(self.max_fraction))
+            .min(self.max_notional);
+        if stake < self.min_notional {
+            return 0.0;
+        }
+        stake
+    }
+}
+
+/// Growth-optimal fraction of bankroll on a pair costing `cost` per unit
+/// (fees included) that pays 2, 1 or 0 with `odds`: the f maximizing
+/// E[ln(1 + f·r)], found by bisection on its derivative. Never above 1
+/// (no leverage); 0 when the expected return isn't positive.
+pub fn kelly_fraction(odds: &PayoutOdds, cost: f64) -> f64 {
+    if cost <= 0.0 {
+        return 0.0;
+    }
+
+    // (probability, return per dollar staked)
+    let outcomes: Vec<(f64, f64)> = [
+        (odds.both, 2.0 / cost - 1.0),
+        (odds.one, 1.0 / cost - 1.0),
+        (odds.neither, -1.0),
+    ]
+    .into_iter()
+    .filter(|&(p, _)| p > 0.0)
+    .collect();
+
+    let edge: f64 = outcomes.iter().map(|(p, r)| p * r).sum();
+    if edge <= 0.0 {
+        return 0.0;
+    }
+
+    let worst = outcomes
+        .iter()
+        .map(|&(_, r)| r)
+        .fold(f64::INFINITY, f64::min);
+    if worst >= 0.0 {
+        return 1.0;
+    }
+
+    // Growth slope falls from `edge` at f = 0 to −∞ at the ruin point
+    let slope = |f: f64| -> f64 { outcomes.iter().map(|(p, r)| p * r / (1.0 + f * r)).sum() };
+    let (mut lo, mut hi) = (0.0, (-1.0 / worst).min(1.0));
+    if slope(hi) >= 0.0 {
+        return hi;
+    }
+    for _ in 0..60 {
+        let mid = 0.5 * (lo + hi);
+        if slope(mid) > 0.0 {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+    lo
+}
+
+// ==================================================
+// SIZER
+// ==================================================
+
+/// The one place entries are sized — trader and backtester both go
+/// through it, so they can't drift apart.
+pub struct Sizer {
+    policy: Box<dyn SizingPolicy>,
+    limits: SizingLimits,
+}
+
+impl Sizer {
+    pub fn new(policy: Box<dyn SizingPolicy>, limits: SizingLimits) -> Self {
+        Self { policy, limits }
+    }
+
+    /// Policy for the configured TRADE_MODE
+    pub fn from_config(sizing: &PositionSizing, limits: SizingLimits) -> Self {
+        let policy: Box<dyn SizingPolicy> = match sizing.mode {
+            TradeMode::Fixed => Box::new(FixedPolicy {
+                usdc: sizing.fixed_usdc.unwrap_or(0.0),
+            }),
+            TradeMode::Percentage => Box::new(PercentagePolicy {
+                percent: sizing.percentage.unwrap_or(10.0),
+            }),
+            TradeMode::Dynamic => Box::new(DynamicPolicy {
+                max_risk_percent: sizing.max_risk_percent.unwrap_or(1.0),
+            }),
+            TradeMode::Free => Box::new(FreePolicy),
+            TradeMode::Kelly => Box::new(KellyPolicy {
+                fraction: sizing.kelly_fraction.unwrap_or(0.25),
+                max_fraction: sizing.kelly_max_fraction.unwrap_or(0.10),
+                min_notional: sizing.min_notional.unwrap_or(1.0),
+                max_notional: sizing.max_notional.unwrap_or(f64::INFINITY),
+            }),
+        };
+        Self::new(policy, limits)
+    }
+
+    pub fn from_env() -> Self {
+        Self::from_config(&PositionSizing::from_env(), SizingLimits::from_env())
+    }
+
+    /// Shares per leg: the policy's stake, cut to what's available and the
+    /// max notional, floored to whole pairs, cut to book depth. Empty when
+    /// what's left is under the min notional.
+    pub fn size(&self, input: &SizingInput) -> LegShares {
+        let cost = input.unit_cost();
+        if cost <= 0.0 {
+            return LegShares::default();
+        }
+
+        let stake = self
+            .policy
+            .stake(input)
+            .min(input.available())
+            .min(self.limits.max_notional)
+            .max(0.0);
+
+        let mut units = (stake / cost).floor();
+        for depth in [input.eth_depth, input.btc_depth].into_iter().flatten() {
+            units = units.min(depth.floor());
+        }
+
+        if units * cost < self.limits.min_notional {
+            return LegShares::default();
+        }
+
+        LegShares {
+            eth: units,
+            btc: units,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal::Decimal;
+    use rust_decimal_macros::dec;
+
+    fn opportunity(eth: Decimal, btc: Decimal, odds: Option<PayoutOdds>) -> ArbitrageOpportunity {
+        ArbitrageOpportunity {
+            payout_odds: odds,
+            ..ArbitrageOpportunity::at(eth, btc)
+        }
+    }
+
+    fn sizing(mode: TradeMode) -> PositionSizing {
+        PositionSizing {
+            mode,
+            fixed_usdc: Some(20.0),
+            percentage: Some(10.0),
+            max_risk_percent: Some(2.0),
+            kelly_fraction: Some(0.25),
+            kelly_max_fraction: Some(0.2),
+            min_notional: Some(1.0),
+            max_notional: Some(150.0),
+        }
+    }
+
+    fn limits() -> SizingLimits {
+        SizingLimits {
+            min_notional: 1.0,
+            max_notional: 100.0,
+        }
+    }
+
+    // (name, mode, balance, reserved, eth ask, btc ask, odds, depth, expected shares)
+    type Case = (
+        &'static str,
+        TradeMode,
+        f64,
+        f64,
+        Decimal,
+        Decimal,
+        Option<PayoutOdds>,
+        Option<f64>,
+        f64,
+    );
+
+    const ODDS: PayoutOdds = PayoutOdds {
+        both: 0.10,
+        one: 0.85,
+        neither: 0.05,
+    };
+
+    #[test]
+    fn sizes_every_mode() {
+        #[rustfmt::skip]
+        let cases: Vec<Case> = vec![
+            ("fixed", TradeMode::Fixed, 1000.0, 0.0, dec!(0.45), dec!(0.50), None, None, 21.0),
+            ("fixed over available", TradeMode::Fixed, 10.0, 0.0, dec!(0.45), dec!(0.50), None, None, 10.0),
+            ("fixed under min", TradeMode::Fixed, 0.9, 0.0, dec!(0.45), dec!(0.50), None, None, 0.0),
+            ("percentage", TradeMode::Percentage, 1000.0, 0.0, dec!(0.45), dec!(0.50), None, None, 105.0),
+            ("percentage less reserved", TradeMode::Percentage, 1000.0, 600.0, dec!(0.45), dec!(0.50), None, None, 42.0),
+            ("percentage capped", TradeMode::Percentage, 5000.0, 0.0, dec!(0.45), dec!(0.50), None, None, 105.0),
+            ("percentage depth", TradeMode::Percentage, 1000.0, 0.0, dec!(0.45), dec!(0.50), None, Some(30.5), 30.0),
+            ("dynamic", TradeMode::Dynamic, 1000.0, 0.0, dec!(0.45), dec!(0.50), None, None, 22.0),
+            ("dynamic reserved all", TradeMode::Dynamic, 1000.0, 1000.0, dec!(0.45), dec!(0.50), None, None, 0.0),
+            ("free", TradeMode::Free, 50.0, 0.0, dec!(0.45), dec!(0.50), None, None, 52.0),
+            ("free capped", TradeMode::Free, 1000.0, 0.0, dec!(0.45), dec!(0.50), None, None, 105.0),
+            ("kelly no odds", TradeMode::Kelly, 200.0, 0.0, dec!(0.45), dec!(0.50), None, None, 0.0),
+            ("kelly odds", TradeMode::Kelly, 200.0, 0.0, dec!(0.45), dec!(0.50), Some(ODDS), None, 29.0),
+            ("kelly no edge", TradeMode::Kelly, 200.0, 0.0, dec!(0.60), dec!(0.50), Some(ODDS), None, 0.0),
+        ];
+
+        for (name, mode, balance, reserved, eth, btc, odds, depth, expected) in cases {
+            let o = opportunity(eth, btc, odds);
+            let input = SizingInput {
+                opportunity: &o,
+                balance,
+                reserved,
+                eth_depth: depth,
+                btc_depth: None,
+            };
+            let shares = Sizer::from_config(&sizing(mode), limits()).size(&input);
+            assert_eq!(shares.eth, expected, "{}", name);
+            assert_eq!(shares.btc, expected, "{}", name);
+        }
+    }
+
+    #[test]
+    fn kelly_fraction_cases() {
+        // (name, odds, cost, expected, tolerance)
+        #[rustfmt::skip]
+        let cases = [
+            ("binary even odds", PayoutOdds { both: 0.0, one: 0.6, neither: 0.4 }, 0.5, 0.2, 1e-9),
+            ("no downside", PayoutOdds { both: 0.2, one: 0.8, neither: 0.0 }, 0.95, 1.0, 0.0),
+            ("no edge", PayoutOdds { both: 0.0, one: 0.5, neither: 0.5 }, 0.5, 0.0, 0.0),
+            ("pair odds", ODDS, 0.95, 0.5537, 1e-3),
+        ];
+
+        for (name, odds, cost, expected, tolerance) in cases {
+            let f = kelly_fraction(&odds, cost);
+            assert!(
+                (f - expected).abs() <= tolerance,
+                "{}: {} vs {}",
+                name,
+                f,
+                expected
+            );
+        }
+    }
+}
@@ -0,0 +1,639 @@
+use anyhow::{Context, Result};
+use log::warn;
+use reqwest::{Client, Method, Request, RequestBuilder, Response, StatusCode};
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::{sleep, Instant};
+
+/// Longest Retry-After we honour; anything beyond is treated as this
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(60);
+
+/// First backoff step; doubles per attempt up to BACKOFF_CAP
+const BACKOFF_BASE: Duration = Duration::from_millis(250);
+const BACKOFF_CAP: Duration = Duration::from_secs(5);
+
+// ==================================================
+// ROUTE LIMITS
+// ==================================================
+
+/// Requests allowed per window on one route. A request draws from every
+/// route it matches, so an endpoint limit stacks on its API's general one.
+#[derive(Debug, Clone, Copy)]
+struct RouteLimit {
+    host: &'static str,
+    /// None matches any method
+    method: Option<&'static str>,
+    /// Exact path or a parent of it; a trailing '/' matches anything under
+    path: &'static str,
+    requests: u32,
+    window_secs: u64,
+}
+
+const fn limit(
+    host: &'static str,
+    method: Option<&'static str>,
+    path: &'static str,
+    requests: u32,
+) -> RouteLimit {
+    RouteLimit {
+        host,
+        method,
+        path,
+        requests,
+        window_secs: 10,
+    }
+}
+
+const CLOB: &str = "clob.polymarket.com";
+const GAMMA: &str = "gamma-api.polymarket.com";
+
+/// Polymarket's published limits, per 10 seconds
+const ROUTE_LIMITS: &[RouteLimit] = &[
+    // CLOB
+    limit(CLOB, None, "/", 9000),
+    limit(CLOB, Some("GET"), "/book", 1500),
+    limit(CLOB, Some("GET"), "/books", 500),
+    limit(CLOB, Some("GET"), "/price", 1500),
+    limit(CLOB, Some("GET"), "/prices", 500),
+    limit(CLOB, Some("GET"), "/tick-size", 200),
+    limit(CLOB, Some("GET"), "/fee-rate", 200),
+    limit(CLOB, Some("GET"), "/markets", 250),
+    limit(CLOB, Some("GET"), "/data/", 500),
+    limit(CLOB, Some("POST"), "/order", 3500),
+    limit(CLOB, Some("POST"), "/orders", 1000),
+    limit(CLOB, Some("DELETE"), "/order", 3000),
+    limit(CLOB, Some("DELETE"), "/cancel-all", 250),
+    // Gamma
+    limit(GAMMA, None, "/", 4000),
+    limit(GAMMA, Some("GET"), "/events", 500),
+    limit(GAMMA, Some("GET"), "/markets", 300),
+];
+
+impl RouteLimit {
+    fn matches(&self, method: &Method, host: &str, path: &str) -> bool {
+        if self.host != host || self.method.is_some_and(|m| m != method.as_str()) {
+            return false;
+        }
+
+        if self.path.ends_with('/') {
+            path.starts_with(self.path)
+        } else {
+            path == self.path
+                || path
+                    .strip_prefix(self.path)
+                    .is_some_and(|rest| rest.starts_with('/'))
+        }
+    }
+
+    fn label(&self) -> String {
+        format!("{} {}{}", self.method.unwrap_or("*"), self.host, self.path)
+    }
+}
+
+// ==================================================
+// TOKEN BUCKET
+// ==================================================
+
+struct Bucket {
+    label: String,
+    capacity: f64,
+    tokens: f64,
+    per_sec: f64,
+    refilled: Instant,
+    /// Set by a 429's Retry-After: nothing goes out before this
+    paused_until: Option<Instant>,
+}
+
+/// What a caller must do before sending
+enum Admit {
+    Go,
+    Wait(Duration),
+    Paused(Duration),
+}
+
+impl Bucket {
+    fn new(limit: &RouteLimit, scale: f64) -> Self {
+        let capacity = (limit.requests as f64 * scale).max(1.0);
+        Self {
+            label: limit.label(),
+            capacity,
+            tokens: capacity,
+            per_sec: capacity / limit.window_secs as f64,
+            refilled: Instant::now(),
+            paused_until: None,
+        }
+    }
+
+    /// Take a token if one is available, otherwise say how long to wait
+    fn admit(&mut self, now: Instant) -> Admit {
+        if let Some(until) = self.paused_until {
+            if until > now {
+                return Admit::Paused(until - now);
+            }
+            self.paused_until = None;
+        }
+
+        let elapsed = now.saturating_duration_since(self.refilled).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.per_sec).min(self.capacity);
+        self.refilled = now;
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Admit::Go
+        } else {
+            Admit::Wait(Duration::from_secs_f64((1.0 - self.tokens) / self.per_sec))
+        }
+    }
+
+    fn pause(&mut self, until: Instant) {
+        self.paused_until = Some(self.paused_until.map_or(until, |u| u.max(until)));
+    }
+}
+
+/// A request not sent because its route is paused by a Retry-After
+#[derive(Debug, Clone)]
+pub struct Throttled {
+    pub route: String,
+    pub wait: Duration,
+}
+
+impl fmt::Display for Throttled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} rate limited for another {}ms",
+            self.route,
+            self.wait.as_millis()
+        )
+    }
+}
+
+impl std::error::Error for Throttled {}
+
+// ==================================================
+// METRICS
+// ==================================================
+
+#[derive(Default)]
+struct Counters {
+    requests: AtomicU64,
+    throttled: AtomicU64,
+    throttled_ms: AtomicU64,
+    rate_limited: AtomicU64,
+    server_errors: AtomicU64,
+    retries: AtomicU64,
+}
+
+/// Throttling seen so far, across every clone of the client
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HttpStats {
+    /// Requests sent, retries included
+    pub requests: u64,
+    /// Requests held back by our own limits
+    pub throttled: u64,
+    /// Total time spent held back
+    pub throttled_ms: u64,
+    /// 429 responses
+    pub rate_limited: u64,
+    /// 5xx responses
+    pub server_errors: u64,
+    /// GETs sent again after a 429, 5xx or transport error
+    pub retries: u64,
+}
+
+impl fmt::Display for HttpStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} requests | {} throttled ({}ms) | {} × 429 | {} × 5xx | {} retries",
+            self.requests,
+            self.throttled,
+            self.throttled_ms,
+            self.rate_limited,
+            self.server_errors,
+            self.retries
+        )
+    }
+}
+
+// ==================================================
+// CLIENT
+// ==================================================
+
+/// The one HTTP client behind every Polymarket REST call. Clones share the
+/// connection pool, the rate limits and the metrics.
+///
+/// Every request waits for a token from each route it matches. GETs are
+/// idempotent and retried on 429, 5xx and transport errors with jittered
+/// exponential backoff. Everything else goes out exactly once: a POSTed
+/// order that timed out may still have landed, so the caller decides.
+#[derive(Clone)]
+pub struct HttpClient {
+    client: Client,
+    limits: Arc<Vec<(RouteLimit, Mutex<Bucket>)>>,
+    counters: Arc<Counters>,
+    max_retries: u32,
+}
+
+impl HttpClient {
+    pub fn new() -> Result<Self> {
+        // Read HTTP_RATE_SCALE from env (default: 1.0)
+        let scale = std::env::var("HTTP_RATE_SCALE")
+            .ok()
+            .and_then(|v| v.parse::<f64>().ok())
+            .filter(|s| *s > 0.0)
+            .unwrap_or(1.0);
+
+        // Read HTTP_MAX_RETRIES from env (default: 3)
+        let max_retries = std::env::var("HTTP_MAX_RETRIES")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(3);
+
+        Self::with_limits(ROUTE_LIMITS, scale, max_retries)
+    }
+
+    fn with_limits(routes: &[RouteLimit], scale: f64, max_retries: u32) -> Result<Self> {
+        // Orders reuse a pooled, kept-alive connection instead of paying
+        // for TCP + TLS on the hot path
+        let client = Client::builder()
+            .timeout(Duration::from_secs(10))
+            .pool_max_idle_per_host(8)
+            .pool_idle_timeout(Duration::from_secs(90))
+            .tcp_keepalive(Duration::from_secs(30))
+            .tcp_nodelay(true)
+            .build()?;
+
+        let limits = routes
+            .iter()
+            .map(|limit| (*limit, Mutex::new(Bucket::new(limit, scale))))
+            .collect();
+
+        Ok(Self {
+            client,
+            limits: Arc::new(limits),
+            counters: Arc::new(Counters::default()),
+            max_retries,
+        })
+    }
+
+    pub fn get(&self, url: &str) -> RequestBuilder {
+        self.client.get(url)
+    }
+
+    pub fn post(&self, url: &str) -> RequestBuilder {
+        self.client.post(url)
+    }
+
+    pub fn delete(&self, url: &str) -> RequestBuilder {
+        self.client.delete(url)
+    }
+
+    /// Send under the rate limits. Non-success statuses come back as a
+    /// response, like reqwest; only transport errors are Err.
+    pub async fn send(&self, request: RequestBuilder) -> Result<Response> {
+        let request = request.build()?;
+        let buckets = self.buckets(&request);
+
+        if request.method() != Method::GET {
+            // Holding an order through a Retry-After pause only makes it stale
+            let wait_out_pause = request.method() != Method::POST;
+            self.admit(&buckets, wait_out_pause).await?;
+            let result = self.client.execute(request).await;
+            self.observe(&buckets, &result);
+            return Ok(result?);
+        }
+
+        let mut attempt = 0;
+        loop {
+            self.admit(&buckets, true).await?;
+
+            let this = request.try_clone().context("GET request not clonable")?;
+            let url = this.url().clone();
+            let result = self.client.execute(this).await;
+            let retry_after = self.observe(&buckets, &result);
+
+            let retryable = match &result {
+                Ok(resp) => {
+                    resp.status() == StatusCode::TOO_MANY_REQUESTS
+                        || resp.status().is_server_error()
+                }
+                Err(e) => e.is_timeout() || e.is_connect() || e.is_request(),
+            };
+            if !retryable || attempt >= self.max_retries {
+                return Ok(result?);
+            }
+
+            // A Retry-After already paused the route; admit() waits it out
+            let delay = match retry_after {
+                Some(_) => Duration::ZERO,
+                None => backoff(attempt),
+            };
+            attempt += 1;
+            self.counters.retries.fetch_add(1, Ordering::Relaxed);
+
+            let cause = match &result {
+                Ok(resp) => resp.status().to_string(),
+                Err(e) => e.to_string(),
+            };
+            warn!(
+                "🔁 Retrying GET {} ({}/{}) in {}ms: {}",
+                url.path(),
+                attempt,
+                self.max_retries,
+                delay.as_millis(),
+                cause
+            );
+            sleep(delay).await;
+        }
+    }
+
+    pub fn stats(&self) -> HttpStats {
+        let c = &self.counters;
+        HttpStats {
+            requests: c.requests.load(Ordering::Relaxed),
+            throttled: c.throttled.load(Ordering::Relaxed),
+            throttled_ms: c.throttled_ms.load(Ordering::Relaxed),
+            rate_limited: c.rate_limited.load(Ordering::Relaxed),
+            server_errors: c.server_errors.load(Ordering::Relaxed),
+            retries: c.retries.load(Ordering::Relaxed),
+        }
+    }
+
+    fn buckets(&self, request: &Request) -> Vec<&Mutex<Bucket>> {
+        let url = request.url();
+        let host = url.host_str().unwrap_or_default();
+        self.limits
+            .iter()
+            .filter(|(limit, _)| limit.matches(request.method(), host, url.path()))
+            .map(|(_, bucket)| bucket)
+            .collect()
+    }
+
+    /// Wait for a token from every bucket. A paused bucket fails the request
+    /// unless `wait_out_pause`.
+    async fn admit(&self, buckets: &[&Mutex<Bucket>], wait_out_pause: bool) -> Result<()> {
+        let started = Instant::now();
+
+        for bucket in buckets {
+            loop {
+                let (admit, label) = {
+                    let mut b = bucket.lock().unwrap_or_else(|e| e.into_inner());
+                    (b.admit(Instant::now()), b.label.clone())
+                };
+
+                match admit {
+                    Admit::Go => break,
+                    Admit::Wait(wait) => sleep(wait).await,
+                    Admit::Paused(wait) if wait_out_pause => sleep(wait).await,
+                    Admit::Paused(wait) => return Err(Throttled { route: label, wait }.into()),
+                }
+            }
+        }
+
+        let waited = started.elapsed();
+        if !waited.is_zero() {
+            self.counters.throttled.fetch_add(1, Ordering::Relaxed);
+            self.counters
+                .throttled_ms
+                .fetch_add(waited.as_millis() as u64, Ordering::Relaxed);
+        }
+        Ok(())
+    }
+
+    /// Count the outcome; on a 429, pause the routes for its Retry-After
+    fn observe(
+        &self,
+        buckets: &[&Mutex<Bucket>],
+        result: &reqwest::Result<Response>,
+    ) -> Option<Duration> {
+        self.counters.requests.fetch_add(1, Ordering::Relaxed);
+
+        let resp = result.as_ref().ok()?;
+        if resp.status().is_server_error() {
+            self.counters.server_errors.fetch_add(1, Ordering::Relaxed);
+        }
+        if resp.status() != StatusCode::TOO_MANY_REQUESTS {
+            return None;
+        }
+
+        self.counters.rate_limited.fetch_add(1, Ordering::Relaxed);
+
+        let retry_after = resp
+            .headers()
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.trim().parse::<f64>().ok())
+            .map(|secs| Duration::from_secs_f64(secs.max(0.0)).min(MAX_RETRY_AFTER));
+
+        warn!(
+            "🚦 429 from {} — retry after {}",
+            resp.url().path(),
+            retry_after.map_or("n/a".to_string(), |d| format!("{}ms", d.as_millis()))
+        );
+
+        if let Some(wait) = retry_after {
+            let until = Instant::now() + wait;
+            for bucket in buckets {
+                bucket
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .pause(until);
+            }
+        }
+        retry_after
+    }
+}
+
+/// Exponential backoff with full jitter: uniform in [0, base × 2^attempt]
+fn backoff(attempt: u32) -> Duration {
+    let ceiling = BACKOFF_BASE
+        .saturating_mul(2u32.saturating_pow(attempt))
+        .min(BACKOFF_CAP);
+    ceiling.mul_f64(::rand::random::<f64>())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Local server answering each request with the next canned response,
+    /// repeating the last. Returns its base URL and a request counter.
+    async fn serve(responses: Vec<&'static str>) -> (String, Arc<AtomicU64>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let served = Arc::new(AtomicU64::new(0));
+
+        let count = served.clone();
+        tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut request = Vec::new();
+                let mut buf = [0u8; 1024];
+                while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                    let n = socket.read(&mut buf).await.unwrap();
+                    if n == 0 {
+                        break;
+                    }
+                    request.extend_from_slice(&buf[..n]);
+                }
+
+                let i = count.fetch_add(1, Ordering::SeqCst) as usize;
+                let status = responses[i.min(responses.len() - 1)];
+                let response = format!(
+                    "HTTP/1.1 {}\r\ncontent-length: 0\r\nconnection: close\r\n\r\n",
+                    status
+                );
+                socket.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+
+        (url, served)
+    }
+
+    fn local(max_retries: u32) -> HttpClient {
+        let routes = [limit("127.0.0.1", None, "/", 100)];
+        HttpClient::with_limits(&routes, 1.0, max_retries).unwrap()
+    }
+
+    #[test]
+    fn routes_match_by_host_method_and_path() {
+        let order = limit(CLOB, Some("POST"), "/order", 1);
+        assert!(order.matches(&Method::POST, CLOB, "/order"));
+        assert!(order.matches(&Method::POST, CLOB, "/order/0xabc"));
+        assert!(!order.matches(&Method::POST, CLOB, "/orders"));
+        assert!(!order.matches(&Method::DELETE, CLOB, "/order"));
+        assert!(!order.matches(&Method::POST, GAMMA, "/order"));
+
+        let data = limit(CLOB, Some("GET"), "/data/", 1);
+        assert!(data.matches(&Method::GET, CLOB, "/data/orders"));
+        assert!(!data.matches(&Method::GET, CLOB, "/data"));
+
+        // An order draws from the CLOB-wide bucket and its endpoint's
+        let http = HttpClient::new().unwrap();
+        let post = http
+            .post("https://clob.polymarket.com/order")
+            .build()
+            .unwrap();
+        assert_eq!(http.buckets(&post).len(), 2);
+        let books = http
+            .get("https://clob.polymarket.com/books")
+            .build()
+            .unwrap();
+        assert_eq!(http.buckets(&books).len(), 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn each_route_refills_its_own_bucket() {
+        let routes = [
+            limit(CLOB, Some("GET"), "/book", 2),
+            limit(CLOB, Some("GET"), "/price", 2),
+        ];
+        let http = HttpClient::with_limits(&routes, 1.0, 0).unwrap();
+        let book = http
+            .get("https://clob.polymarket.com/book")
+            .build()
+            .unwrap();
+        let price = http
+            .get("https://clob.polymarket.com/price")
+            .build()
+            .unwrap();
+
+        let started = Instant::now();
+        for _ in 0..2 {
+            http.admit(&http.buckets(&book), true).await.unwrap();
+        }
+        assert_eq!(started.elapsed(), Duration::ZERO);
+
+        // 2 per 10s: the third waits 5s for a token
+        http.admit(&http.buckets(&book), true).await.unwrap();
+        assert_eq!(started.elapsed(), Duration::from_secs(5));
+
+        // The other route still has both of its tokens
+        let started = Instant::now();
+        for _ in 0..2 {
+            http.admit(&http.buckets(&price), true).await.unwrap();
+        }
+        assert_eq!(started.elapsed(), Duration::ZERO);
+        assert_eq!(http.stats().throttled, 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn paused_routes_hold_gets_and_turn_away_orders() {
+        let routes = [limit(CLOB, None, "/", 100)];
+        let http = HttpClient::with_limits(&routes, 1.0, 0).unwrap();
+        let request = http
+            .post("https://clob.polymarket.com/order")
+            .build()
+            .unwrap();
+        let buckets = http.buckets(&request);
+
+        let started = Instant::now();
+        buckets[0]
+            .lock()
+            .unwrap()
+            .pause(started + Duration::from_secs(3));
+
+        let err = http.admit(&buckets, false).await.unwrap_err();
+        let throttled = err.downcast_ref::<Throttled>().unwrap();
+        assert_eq!(throttled.wait, Duration::from_secs(3));
+
+        http.admit(&buckets, true).await.unwrap();
+        assert_eq!(started.elapsed(), Duration::from_secs(3));
+    }
+
+    // Real sockets below: a paused clock would jump to reqwest's timeout
+    // whenever the runtime idles on I/O
+
+    #[tokio::test]
+    async fn retry_after_pauses_the_route() {
+        let (url, served) = serve(vec![
+            "429 Too Many Requests\r\nretry-after: 0.2",
+            "200 OK",
+            "429 Too Many Requests\r\nretry-after: 30",
+        ])
+        .await;
+        let http = local(3);
+
+        // The GET waits out the pause and succeeds
+        let started = Instant::now();
+        let resp = http.send(http.get(&url)).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert!(started.elapsed() >= Duration::from_millis(200));
+        assert_eq!(served.load(Ordering::SeqCst), 2);
+
+        // A 429 on a POST comes straight back, and pauses the route
+        let resp = http.send(http.post(&url)).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(served.load(Ordering::SeqCst), 3);
+
+        // A POST during the pause is never sent
+        let err = http.send(http.post(&url)).await.unwrap_err();
+        assert!(err.downcast_ref::<Throttled>().is_some());
+        assert_eq!(served.load(Ordering::SeqCst), 3);
+
+        let stats = http.stats();
+        assert_eq!((stats.rate_limited, stats.retries), (2, 1));
+    }
+
+    #[tokio::test]
+    async fn only_gets_are_retried() {
+        let (url, served) = serve(vec!["503 Service Unavailable"]).await;
+        let http = local(2);
+
+        let resp = http.send(http.get(&url)).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(served.load(Ordering::SeqCst), 3);
+
+        let resp = http.send(http.post(&url)).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(served.load(Ordering::SeqCst), 4);
+
+        let stats = http.stats();
+        assert_eq!((stats.server_errors, stats.retries), (4, 2));
+    }
+}
@@ -0,0 +1,185 @@
+use crate::clock::{self, Clock};
+use crate::domain::time::TradingPhase;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use log::warn;
+use serde::Serialize;
+use std::env;
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+// ==================================================
+// EVENTS
+// ==================================================
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum JournalEvent {
+    OpportunityAdmitted {
+        pair: String,
+        fingerprint: String,
+        entry: u32,
+        phase: TradingPhase,
+    },
+    OpportunitySuppressed {
+        pair: String,
+        reason: String,
+    },
+    OrderSubmitted {
+        token_id: String,
+        side: String,
+        price: f64,
+        size: f64,
+        filled: f64,
+        order_id: Option<String>,
+        phase: TradingPhase,
+    },
+    OrderFailed {
+        token_id: String,
+        side: String,
+        price: f64,
+        size: f64,
+        error: String,
+        /// ClobError kind when the exchange said why
+        kind: Option<String>,
+        phase: TradingPhase,
+    },
+    TradeTimings {
+        eth_token_id: String,
+        btc_token_id: String,
+        detect_ms: f64,
+        size_ms: f64,
+        sign_ms: f64,
+        send_ms: f64,
+        ack_ms: f64,
+        total_ms: f64,
+    },
+    LegUnwound {
+        token_id: String,
+        size: f64,
+        price: f64,
+    },
+    QuotePlaced {
+        token_id: String,
+        price: f64,
+        size: f64,
+        order_id: Option<String>,
+    },
+    QuoteFilled {
+        token_id: String,
+        price: f64,
+        filled: f64,
+        order_id: String,
+    },
+    QuoteCancelled {
+        token_id: String,
+        order_id: Option<String>,
+        reason: String,
+    },
+    RiskRejected {
+        reason: String,
+    },
+    PaperSettled {
+        condition_id: String,
+        token_id: String,
+        payout: f64,
+        pnl: f64,
+    },
+    OrdersCancelled,
+    Shutdown,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct JournalEntry {
+    pub ts: DateTime<Utc>,
+    #[serde(flatten)]
+    pub event: JournalEvent,
+}
+
+// ==================================================
+// JOURNAL (APPEND-ONLY JSONL)
+// ==================================================
+
+pub struct TradeJournal {
+    path: PathBuf,
+    /// None for a discarding journal
+    writer: Option<Mutex<BufWriter<File>>>,
+    clock: Arc<dyn Clock>,
+}
+
+impl TradeJournal {
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+
+        Ok(Self {
+            path,
+            writer: Some(Mutex::new(BufWriter::new(file))),
+            clock: clock::system(),
+        })
+    }
+
+    /// A journal that drops every event (backtests, offline tools)
+    pub fn discard() -> Self {
+        Self {
+            path: PathBuf::new(),
+            writer: None,
+            clock: clock::system(),
+        }
+    }
+
+    /// Stamp entries from `clock` instead of the wall clock
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Open the journal at JOURNAL_PATH (default: trades.jsonl)
+    pub fn from_env() -> Result<Self> {
+        let path = env::var("JOURNAL_PATH").unwrap_or_else(|_| "trades.jsonl".to_string());
+        Self::open(path)
+    }
+
+    pub fn path(&self) -> &PathBuf {
+        &self.path
+    }
+
+    /// Append an event. Journal failures are logged, never propagated —
+    /// a full disk must not abort a half-executed trade.
+    pub fn record(&self, event: JournalEvent) {
+        let Some(writer) = &self.writer else {
+            return;
+        };
+
+        let entry = JournalEntry {
+            ts: self.clock.now(),
+            event,
+        };
+
+        let line = match serde_json::to_string(&entry) {
+            Ok(line) => line,
+            Err(e) => {
+                warn!("⚠️  Journal serialize failed: {}", e);
+                return;
+            }
+        };
+
+        let mut writer = writer.lock().unwrap_or_else(|e| e.into_inner());
+        if let Err(e) = writeln!(writer, "{}", line) {
+            warn!("⚠️  Journal write failed: {}", e);
+        }
+    }
+
+    pub fn flush(&self) -> Result<()> {
+        let Some(writer) = &self.writer else {
+            return Ok(());
+        };
+
+        let mut writer = writer.lock().unwrap_or_else(|e| e.into_inner());
+        writer.flush()?;
+        writer.get_ref().sync_data()?;
+        Ok(())
+    }
+}
@@ -1,10 +1,20 @@
+pub mod backtest;
 pub mod cache;
 pub mod client;
+pub mod clock;
 pub mod config;
 pub mod domain;
 pub mod execution;
+pub mod http;
+pub mod journal;
 pub mod logging;
 pub mod monitor;
+pub mod pricing;
+pub mod recorder;
+pub mod replay;
+pub mod rewards;
+pub mod risk;
+pub mod scheduler;
 pub mod strategy;
 pub mod wallet;
 pub mod ws;
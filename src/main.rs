@@ -5,29 +5,34 @@ use clap::Parser;
 use config::{Args, Config};
 use log::{info, warn}; // ← CHANGED: Added 'warn' import
 use std::sync::Arc;
+use std::time::Duration;
 
 use crate::config::WalletConfig;
 use cache::PriceCache;
 use client::PolymarketClient;
+use clock::ReplayClock;
+use domain::Outcome;
 use ethers::providers::{Http, Provider};
-use execution::{clob_client::ClobClient, Trader};
-use monitor::MarketMonitor;
+use execution::maker::{MakerPair, MakerParams};
+use execution::paper::{PaperBackend, PaperConfig};
+use execution::{clob_client::ClobClient, ExecutionBackend, LiveBackend, Trader};
+use journal::TradeJournal;
+use monitor::{MarketMonitor, MarketSnapshot};
+use pricing::feed::run_feed;
+use pricing::joint::JointModel;
+use pricing::PricingModel;
+use recorder::Recorder;
+use replay::{ReplayConfig, ReplaySession, ReplaySource};
+use risk::{RiskLimits, RiskManager};
+use scheduler::{sleep_until, PeriodScheduler};
+use strategy::dedup::OpportunityFilter;
+use strategy::fees::FeeRates;
 use strategy::ArbitrageDetector;
+use tokio_util::sync::CancellationToken;
 use wallet::allowance::verify_allowances;
+use wallet::balance::BalanceTracker;
 use wallet::signer::WalletSigner;
 
-// ===============================
-// TIME HELPERS
-// ===============================
-fn current_15m_period() -> u64 {
-    use std::time::{SystemTime, UNIX_EPOCH};
-    let now = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_secs();
-    (now / 900) * 900
-}
-
 #[tokio::main]
 async fn main() -> Result<()> {
     dotenv::dotenv().ok();
@@ -42,13 +47,6 @@ async fn main() -> Result<()> {
     let args = Args::parse();
     let config = Config::load(&args.config)?;
 
-    // ===============================
-    // PROVIDER
-    // ===============================
-    let rpc_url = std::env::var("RPC_URL").expect("RPC_URL missing in .env");
-
-    let provider = Arc::new(Provider::<Http>::try_from(&rpc_url)?);
-
     // ===============================
     // WALLET SIGNER (EOA) - READ FROM .ENV
     // ===============================
@@ -64,6 +62,26 @@ async fn main() -> Result<()> {
     info!("🔑 Signer loaded");
     info!("🧾 Proxy wallet: {}", proxy_wallet);
 
+    let wallet_config = WalletConfig {
+        private_key: Some(private_key.clone()),
+        chain_id: 137,
+        proxy_wallet: proxy_wallet.clone(),
+    };
+
+    // ===============================
+    // REPLAY MODE (NO NETWORK)
+    // ===============================
+    if let Some(replay) = ReplayConfig::from_env() {
+        return run_replay(replay, config, wallet_config, signer).await;
+    }
+
+    // ===============================
+    // PROVIDER
+    // ===============================
+    let rpc_url = std::env::var("RPC_URL").expect("RPC_URL missing in .env");
+
+    let provider = Arc::new(Provider::<Http>::try_from(&rpc_url)?);
+
     // ===============================
     // STAGE 2 — WALLET / ALLOWANCE PREFLIGHT
     // ===============================
@@ -116,150 +134,598 @@ async fn main() -> Result<()> {
     // ===============================
     // CORE OBJECTS
     // ===============================
-    let _price_cache = PriceCache::new();
+    let price_cache = PriceCache::new();
+
+    // Fee rates of the active markets, shared by detector and trader
+    let fees = FeeRates::new();
+
+    // Spot fair value, when a feed is configured
+    let spot_feed = pricing::feed::from_env(clock::system())?;
+    let pricing = spot_feed
+        .as_ref()
+        .map(|_| PricingModel::from_env().with_clock(clock::system()));
+
+    // ETH/BTC joint outcome history: recordings now, Gamma in the background
+    let joint = JointModel::from_env();
+    joint.seed_from_env();
+    {
+        let joint = joint.clone();
+        let api = api.clone();
+        // Read JOINT_HISTORY_PERIODS from env (default: 96)
+        let periods = std::env::var("JOINT_HISTORY_PERIODS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(96);
+        tokio::spawn(async move {
+            joint
+                .backfill(&api, periods, chrono::Utc::now().timestamp() as u64)
+                .await;
+        });
+    }
+
+    let detector = ArbitrageDetector::new(config.trading.min_profit_threshold)
+        .with_fees(fees.clone())
+        .with_joint(joint.clone());
+    let detector = Arc::new(match &pricing {
+        Some(model) => detector.with_pricing(model.clone()),
+        None => detector,
+    });
+
+    // ===============================
+    // SHUTDOWN + JOURNAL
+    // ===============================
+    let shutdown = CancellationToken::new();
+    tokio::spawn(wait_for_signal(shutdown.clone()));
+
+    if let (Some(feed), Some(model)) = (spot_feed, &pricing) {
+        tokio::spawn(run_feed(feed, model.history(), shutdown.clone()));
+    }
 
-    let detector = Arc::new(ArbitrageDetector::new(config.trading.min_profit_threshold));
+    let journal = Arc::new(TradeJournal::from_env()?);
+    info!("📓 Journal: {}", journal.path().display());
 
-    let wallet_config = WalletConfig {
-        private_key: Some(private_key.clone()),
-        chain_id: 137,
-        proxy_wallet: proxy_wallet.clone(),
+    let filter = Arc::new(OpportunityFilter::from_env(journal.clone()));
+
+    let recorder = Recorder::from_env()?;
+
+    let risk = Arc::new(RiskManager::new(RiskLimits::from_env()));
+    tokio::spawn(wait_for_kill_signal(risk.clone()));
+
+    // ===============================
+    // EXECUTION BACKEND (LIVE / PAPER)
+    // ===============================
+    let paper_trading = std::env::var("PAPER_TRADING")
+        .unwrap_or_else(|_| "false".to_string())
+        .parse::<bool>()
+        .unwrap_or(false);
+
+    let paper = paper_trading.then(|| {
+        Arc::new(PaperBackend::new(
+            api.clone(),
+            price_cache.clone(),
+            PaperConfig::from_env(),
+            journal.clone(),
+        ))
+    });
+
+    // Keep the order connection open between trades
+    if paper.is_none() {
+        let clob = clob.clone();
+        let cancel = shutdown.clone();
+        // Read CLOB_PING_SECS from env (default: 15)
+        let interval = std::env::var("CLOB_PING_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(15);
+        tokio::spawn(async move { clob.keep_warm(Duration::from_secs(interval), cancel).await });
+    }
+
+    // Live balances follow USDC / CTF transfer logs off the hot path
+    let balances = match &paper {
+        Some(_) => None,
+        None => match BalanceTracker::from_env() {
+            Ok(tracker) => {
+                tokio::spawn(tracker.clone().run(shutdown.clone()));
+                Some(tracker)
+            }
+            Err(e) => {
+                warn!(
+                    "⚠️  Balance tracker unavailable, reading balance per trade: {}",
+                    e
+                );
+                None
+            }
+        },
+    };
+
+    let backend: Arc<dyn ExecutionBackend> = match &paper {
+        Some(paper) => paper.clone(),
+        None => {
+            let live = LiveBackend::new(api.clone(), clob.clone(), proxy_wallet.clone());
+            Arc::new(match &balances {
+                Some(tracker) => live.with_balances(tracker.clone()),
+                None => live,
+            })
+        }
     };
 
-    let trader = Arc::new(Trader::new(
+    let trader = Arc::new(
+        Trader::new(
+            backend,
+            config.trading.clone(),
+            wallet_config,
+            signer,
+            journal,
+            risk.clone(),
+            shutdown.clone(),
+        )
+        .with_fees(fees.clone()),
+    );
+
+    // Passive quoting alongside the taker pipeline, when enabled
+    let maker = MakerParams::from_env();
+
+    let pipeline = Pipeline {
+        detector,
+        filter,
+        risk,
+        trader: trader.clone(),
+        recorder: recorder.clone(),
+    };
+
+    // ===============================
+    // WEBSOCKET + PERIOD SCHEDULER
+    // ===============================
+    let ws = ws::spawn_ws(
+        config.polymarket.ws_url.clone(),
+        price_cache.clone(),
+        Vec::new(),
         api.clone(),
-        clob.clone(),
-        config.trading.clone(),
-        wallet_config,
-        signer,
-    ));
+        recorder.clone(),
+    );
+
+    let scheduler = PeriodScheduler::new(api.clone(), Some(ws));
 
-    let mut current_period = current_15m_period();
+    info!("🔍 Discovering current 15m markets...");
+    let mut active = tokio::select! {
+        active = scheduler.prepare_current() => active?,
+        _ = shutdown.cancelled() => return Ok(()),
+    };
 
     // ===============================
     // MAIN LOOP
     // ===============================
     loop {
-        info!("🔍 Discovering current 15m markets...");
+        info!("✅ ETH Market: {}", active.eth_market.slug);
+        info!("✅ BTC Market: {}", active.btc_market.slug);
 
-        let (eth_market, btc_market) = discover_markets(&api).await?;
+        fees.load(&active.token_params);
+        if let Some(recorder) = &recorder {
+            recorder.record_period(&active);
+        }
 
-        info!("✅ ETH Market: {}", eth_market.slug);
-        info!("✅ BTC Market: {}", btc_market.slug);
+        // Seed this period's outcome tokens; their transfers follow from logs
+        if let Some(tracker) = &balances {
+            let tracker = tracker.clone();
+            let token_ids = active.token_ids();
+            tokio::spawn(async move {
+                for token_id in token_ids {
+                    if let Err(e) = tracker.track_token(&token_id).await {
+                        warn!("⚠️  Could not seed balance of {}: {}", token_id, e);
+                    }
+                }
+            });
+        }
 
         let monitor = MarketMonitor::new(
             api.clone(),
-            eth_market,
-            btc_market,
+            price_cache.clone(),
+            active.eth_market.clone(),
+            active.btc_market.clone(),
             config.trading.check_interval_ms,
-        );
+        )?;
+
+        let period_cancel = shutdown.child_token();
+        let monitor_handle = pipeline.spawn(monitor, period_cancel.clone());
 
-        // ╔═══════════════════════════════════════════════════════════╗
-        // ║  CHANGED SECTION - Lines 166-199                         ║
-        // ║  What: Fixed error handling and added debug logging      ║
-        // ║  Why: Silent failures prevented seeing trader errors     ║
-        // ╚═══════════════════════════════════════════════════════════╝
-        let monitor_handle = tokio::spawn({
-            let detector = detector.clone();
+        let maker_handle = maker.enabled.then(|| {
             let trader = trader.clone();
+            let pairs = MakerPair::for_period(&active, maker.pairs);
+            let (cache, params, cancel) =
+                (price_cache.clone(), maker.clone(), period_cancel.clone());
+            tokio::spawn(async move { trader.run_maker(pairs, cache, params, cancel).await })
+        });
 
-            async move {
-                monitor
-                    .start_monitoring(move |snapshot| {
-                        let detector = detector.clone();
-                        let trader = trader.clone();
-
-                        async move {
-                            // CHANGED: Store opportunities instead of inline iteration
-                            let opportunities = detector.detect_opportunities(&snapshot);
-
-                            // CHANGED: Log how many opportunities found
-                            if !opportunities.is_empty() {
-                                info!(
-                                    "🔔 Found {} arbitrage opportunity(ies)!",
-                                    opportunities.len()
-                                );
-                            }
-
-                            // CHANGED: Explicit enumeration with proper error handling
-                            for (i, o) in opportunities.iter().enumerate() {
-                                info!(
-                                    "📋 Processing opportunity {} of {}",
-                                    i + 1,
-                                    opportunities.len()
-                                );
-
-                                // CHANGED: Use match instead of let _ to catch errors
-                                match trader.execute_arbitrage(&o).await {
-                                    Ok(_) => {
-                                        info!("✅ Opportunity {} handled successfully", i + 1);
-                                    }
-                                    Err(e) => {
-                                        warn!("❌ Opportunity {} failed: {}", i + 1, e);
-                                    }
-                                }
-                            }
-                        }
-                    })
-                    .await;
+        // Discover + warm up the next pair while this one trades
+        let next = tokio::select! {
+            next = scheduler.preroll_next(&active) => next,
+            _ = shutdown.cancelled() => None,
+        };
+
+        tokio::select! {
+            _ = sleep_until(active.ends_at) => info!("⏰ 15m rollover — swapping to next pair"),
+            _ = shutdown.cancelled() => {}
+        }
+
+        // Stop new opportunities, let any in-flight legs finish
+        period_cancel.cancel();
+        if let Err(e) = monitor_handle.await {
+            warn!("⚠️  Monitor task ended abnormally: {}", e);
+        }
+        if let Some(handle) = maker_handle {
+            if let Err(e) = handle.await {
+                warn!("⚠️  Maker task ended abnormally: {}", e);
             }
-        });
-        // ╔═══════════════════════════════════════════════════════════╗
-        // ║  END OF CHANGED SECTION                                   ║
-        // ╚═══════════════════════════════════════════════════════════╝
-
-        loop {
-            tokio::time::sleep(std::time::Duration::from_secs(1)).await;
-            let new_period = current_15m_period();
-
-            if new_period != current_period {
-                info!("⏰ 15m rollover — restarting monitor");
-                current_period = new_period;
-                monitor_handle.abort();
-                break;
+            info!(
+                "📊 Daily PnL ${} | est. liquidity rewards ${:.2}",
+                pipeline.risk.daily_pnl().round_dp(2),
+                trader.rewards_today()
+            );
+        }
+
+        // Nothing trades on the expired tokens any more
+        scheduler.release(&active);
+
+        // This period's positions now wait on the resolution
+        let ledger = trader.ledger();
+        ledger.mark_pending(&active.eth_market.condition_id);
+        ledger.mark_pending(&active.btc_market.condition_id);
+        info!("🌐 HTTP: {}", clob.http.stats());
+
+        // Paper positions settle on the real outcome once it is published
+        if let Some(paper) = &paper {
+            for condition_id in [
+                active.eth_market.condition_id.clone(),
+                active.btc_market.condition_id.clone(),
+            ] {
+                let paper = paper.clone();
+                let ledger = ledger.clone();
+                tokio::spawn(async move {
+                    if let Some(payout) = paper
+                        .settle_when_resolved(&condition_id, Duration::from_secs(3600))
+                        .await
+                    {
+                        ledger.record_redemption(&condition_id, payout);
+                    }
+                });
+            }
+        }
+
+        // Held positions leave the risk book at their resolution payout
+        for condition_id in [
+            active.eth_market.condition_id.clone(),
+            active.btc_market.condition_id.clone(),
+        ] {
+            let risk = pipeline.risk.clone();
+            let api = api.clone();
+            tokio::spawn(async move {
+                risk.settle_when_resolved(&api, &condition_id, Duration::from_secs(3600))
+                    .await;
+            });
+        }
+
+        // Count this period's directions once both markets resolve
+        {
+            let joint = joint.clone();
+            let api = api.clone();
+            let (eth_id, btc_id) = (
+                active.eth_market.condition_id.clone(),
+                active.btc_market.condition_id.clone(),
+            );
+            tokio::spawn(async move {
+                joint
+                    .watch_period(&api, &eth_id, &btc_id, Duration::from_secs(3600))
+                    .await;
+            });
+        }
+
+        // Record the winners so replays can settle this period
+        if let Some(recorder) = &recorder {
+            for condition_id in [
+                active.eth_market.condition_id.clone(),
+                active.btc_market.condition_id.clone(),
+            ] {
+                let recorder = recorder.clone();
+                let api = api.clone();
+                tokio::spawn(async move {
+                    recorder
+                        .watch_resolution(api, condition_id, Duration::from_secs(3600))
+                        .await;
+                });
             }
         }
+
+        if shutdown.is_cancelled() {
+            break;
+        }
+
+        active = match next {
+            Some(next) => next,
+            None => scheduler.await_current().await,
+        };
+    }
+
+    info!("🛑 Shutting down — cancelling resting orders");
+    trader.shutdown().await?;
+    if let Some(recorder) = &recorder {
+        recorder.close().await;
     }
+    info!("👋 Shutdown complete");
+
+    Ok(())
 }
 
 // ===============================
-// MARKET DISCOVERY (OUTSIDE MAIN)
+// SNAPSHOT PIPELINE
 // ===============================
-async fn discover_markets(api: &PolymarketClient) -> Result<(domain::Market, domain::Market)> {
-    let now = std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)?
-        .as_secs();
 
-    let mut seen = std::collections::HashSet::new();
+/// Detector → filter → trader, shared by live and replay runs
+#[derive(Clone)]
+struct Pipeline {
+    detector: Arc<ArbitrageDetector>,
+    filter: Arc<OpportunityFilter>,
+    risk: Arc<RiskManager>,
+    trader: Arc<Trader>,
+    recorder: Option<Recorder>,
+}
+
+impl Pipeline {
+    fn spawn(
+        &self,
+        mut monitor: MarketMonitor,
+        cancel: CancellationToken,
+    ) -> tokio::task::JoinHandle<()> {
+        let pipeline = self.clone();
+
+        tokio::spawn(async move {
+            monitor
+                .start_monitoring(cancel, move |snapshot| {
+                    let pipeline = pipeline.clone();
+                    async move { pipeline.on_snapshot(snapshot).await }
+                })
+                .await;
+        })
+    }
+
+    // ╔═══════════════════════════════════════════════════════════╗
+    // ║  CHANGED SECTION - Lines 166-199                         ║
+    // ║  What: Fixed error handling and added debug logging      ║
+    // ║  Why: Silent failures prevented seeing trader errors     ║
+    // ╚═══════════════════════════════════════════════════════════╝
+    async fn on_snapshot(&self, snapshot: MarketSnapshot) {
+        if let Some(recorder) = &self.recorder {
+            recorder.record_snapshot(&snapshot);
+        }
+
+        self.risk.mark_snapshot(&snapshot);
 
-    let eth = discover_market(api, "ETH", "eth", now, &mut seen).await?;
-    seen.insert(eth.condition_id.clone());
+        // CHANGED: Store opportunities instead of inline iteration
+        let opportunities = self.detector.detect_opportunities(&snapshot);
+
+        // CHANGED: Log how many opportunities found
+        if !opportunities.is_empty() {
+            info!(
+                "🔔 Found {} arbitrage opportunity(ies)!",
+                opportunities.len()
+            );
+        }
 
-    let btc = discover_market(api, "BTC", "btc", now, &mut seen).await?;
+        // CHANGED: Explicit enumeration with proper error handling
+        for (i, o) in opportunities.iter().enumerate() {
+            info!(
+                "📋 Processing opportunity {} of {}",
+                i + 1,
+                opportunities.len()
+            );
+
+            // Drop repeats of a quote we already acted on
+            if let Err(reason) = self.filter.admit(o) {
+                info!("⏭️  Opportunity {} suppressed: {}", i + 1, reason);
+                continue;
+            }
 
-    Ok((eth, btc))
+            // CHANGED: Use match instead of let _ to catch errors
+            match self.trader.execute_arbitrage(o).await {
+                Ok(_) => {
+                    info!("✅ Opportunity {} handled successfully", i + 1);
+                }
+                Err(e) => {
+                    warn!("❌ Opportunity {} failed: {}", i + 1, e);
+                }
+            }
+        }
+    }
+    // ╔═══════════════════════════════════════════════════════════╗
+    // ║  END OF CHANGED SECTION                                   ║
+    // ╚═══════════════════════════════════════════════════════════╝
 }
 
-async fn discover_market(
-    api: &PolymarketClient,
-    name: &str,
-    prefix: &str,
-    now: u64,
-    seen: &mut std::collections::HashSet<String>,
-) -> Result<domain::Market> {
-    let base = (now / 900) * 900;
-
-    for i in 0..=3 {
-        let ts = base - i * 900;
-        let slug = format!("{}-updown-15m-{}", prefix, ts);
-
-        if let Ok(market) = api.get_market_by_slug(&slug).await {
-            if !seen.contains(&market.condition_id) && market.active {
-                info!("Found {} market: {}", name, market.slug);
-                return Ok(market);
+// ===============================
+// REPLAY
+// ===============================
+
+/// Run the live pipeline in paper mode over a recorded session, on a clock
+/// that follows the recording. Nothing touches the network.
+async fn run_replay(
+    replay: ReplayConfig,
+    config: Config,
+    wallet_config: WalletConfig,
+    signer: WalletSigner,
+) -> Result<()> {
+    info!(
+        "📼 REPLAY MODE — {} at {}x (paper trading, no network)",
+        replay.dir, replay.speed
+    );
+
+    let session = ReplaySession::load(&replay.dir)?;
+    let clock = Arc::new(ReplayClock::new(session.periods[0].records[0].ts_ms));
+
+    let shutdown = CancellationToken::new();
+    tokio::spawn(wait_for_signal(shutdown.clone()));
+
+    let price_cache = PriceCache::new();
+    // Fee rates of the active markets, shared by detector and trader
+    let fees = FeeRates::new();
+
+    // Spot fair value, when a feed is configured (e.g. a recorded tick file)
+    let spot_feed = pricing::feed::from_env(clock.clone())?;
+    let pricing = spot_feed
+        .as_ref()
+        .map(|_| PricingModel::from_env().with_clock(clock.clone()));
+    if let (Some(feed), Some(model)) = (spot_feed, &pricing) {
+        tokio::spawn(run_feed(feed, model.history(), shutdown.clone()));
+    }
+
+    // Learned period by period from the recorded resolutions, never ahead
+    let joint = JointModel::from_env();
+
+    let detector = ArbitrageDetector::new(config.trading.min_profit_threshold)
+        .with_fees(fees.clone())
+        .with_joint(joint.clone())
+        .with_clock(clock.clone());
+    let detector = Arc::new(match &pricing {
+        Some(model) => detector.with_pricing(model.clone()),
+        None => detector,
+    });
+
+    let journal = Arc::new(TradeJournal::from_env()?.with_clock(clock.clone()));
+    info!("📓 Journal: {}", journal.path().display());
+
+    let filter = Arc::new(OpportunityFilter::from_env(journal.clone()).with_clock(clock.clone()));
+    let risk = Arc::new(RiskManager::new(RiskLimits::from_env()).with_clock(clock.clone()));
+
+    // Order latency shrinks with the replay speed like everything else
+    let mut paper_config = PaperConfig::from_env();
+    paper_config.latency = replay.scale(paper_config.latency);
+
+    let paper = Arc::new(PaperBackend::offline(
+        price_cache.clone(),
+        paper_config,
+        journal.clone(),
+    ));
+
+    let trader = Arc::new(
+        Trader::new(
+            paper.clone(),
+            config.trading.clone(),
+            wallet_config,
+            signer,
+            journal,
+            risk.clone(),
+            shutdown.clone(),
+        )
+        .with_fees(fees.clone())
+        .with_clock(clock.clone()),
+    );
+
+    let pipeline = Pipeline {
+        detector,
+        filter,
+        risk,
+        trader: trader.clone(),
+        recorder: None,
+    };
+
+    for period in session.periods {
+        let active = &period.period;
+        fees.load(&active.token_params);
+        info!(
+            "📼 Period {} — {} / {}",
+            active.period_start, active.eth_market.slug, active.btc_market.slug
+        );
+
+        let source = ReplaySource::new(
+            period.records,
+            price_cache.clone(),
+            clock.clone(),
+            replay.clone(),
+        );
+        let monitor = MarketMonitor::with_source(Box::new(source));
+
+        if let Err(e) = pipeline.spawn(monitor, shutdown.child_token()).await {
+            warn!("⚠️  Monitor task ended abnormally: {}", e);
+        }
+
+        let ledger = trader.ledger();
+        for condition_id in [
+            &active.eth_market.condition_id,
+            &active.btc_market.condition_id,
+        ] {
+            ledger.mark_pending(condition_id);
+            match session.resolutions.get(condition_id) {
+                Some(w) => {
+                    let payout = paper.settle_with_winner(condition_id, &w.token_id, &w.outcome);
+                    ledger.record_redemption(condition_id, payout);
+                    pipeline.risk.settle(condition_id, &w.token_id);
+                }
+                None => warn!("⚠️  No recorded resolution for {}", condition_id),
             }
         }
+
+        let outcome = |id: &String| {
+            session
+                .resolutions
+                .get(id)
+                .and_then(|w| Outcome::from_label(&w.outcome))
+        };
+        if let (Some(eth), Some(btc)) = (
+            outcome(&active.eth_market.condition_id),
+            outcome(&active.btc_market.condition_id),
+        ) {
+            joint.observe(&active.eth_market.condition_id, eth, btc);
+        }
+
+        if shutdown.is_cancelled() {
+            break;
+        }
     }
 
-    anyhow::bail!("No active {} market found", name)
+    trader.shutdown().await?;
+    info!(
+        "📼 Replay complete — paper realized PnL ${}",
+        paper.realized_pnl().round_dp(2)
+    );
+
+    Ok(())
+}
+
+// ===============================
+// SIGNALS
+// ===============================
+async fn wait_for_signal(shutdown: CancellationToken) {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut sigterm = match signal(SignalKind::terminate()) {
+        Ok(s) => s,
+        Err(e) => {
+            warn!("⚠️  SIGTERM handler unavailable: {}", e);
+            let _ = tokio::signal::ctrl_c().await;
+            shutdown.cancel();
+            return;
+        }
+    };
+
+    tokio::select! {
+        _ = tokio::signal::ctrl_c() => info!("🛑 SIGINT received"),
+        _ = sigterm.recv() => info!("🛑 SIGTERM received"),
+    }
+
+    shutdown.cancel();
+}
+
+/// SIGUSR1 engages the risk kill switch without stopping the process
+async fn wait_for_kill_signal(risk: Arc<RiskManager>) {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut sigusr1 = match signal(SignalKind::user_defined1()) {
+        Ok(s) => s,
+        Err(e) => {
+            warn!("⚠️  SIGUSR1 handler unavailable: {}", e);
+            return;
+        }
+    };
+
+    while sigusr1.recv().await.is_some() {
+        risk.engage_kill_switch();
+    }
 }
@@ -1,17 +1,26 @@
+use crate::cache::{now_ms, CachedOrderbook, PriceCache};
 use crate::client::PolymarketClient;
 use crate::domain::*;
-use crate::execution::orderbook::fetch_orderbook;
+use crate::execution::orderbook::{fetch_orderbook, OrderBook};
 use anyhow::Result;
+use async_trait::async_trait;
 use log::{info, warn};
+use rust_decimal::prelude::FromPrimitive;
 use rust_decimal::Decimal;
 use std::sync::Arc;
 use tokio::time::{sleep, Duration};
+use tokio_util::sync::CancellationToken;
 
-pub struct MarketMonitor {
-    api: Arc<PolymarketClient>,
-    eth_market: Market,
-    btc_market: Market,
-    check_interval: Duration,
+// ==================================================
+// DATA SOURCES
+// ==================================================
+
+/// Where snapshots come from: the live CLOB, or a recorded session.
+/// A source paces itself — the monitor just asks for the next one.
+#[async_trait]
+pub trait MarketSource: Send {
+    /// Next snapshot; Ok(None) once the source is exhausted
+    async fn next_snapshot(&mut self) -> Result<Option<MarketSnapshot>>;
 }
 
 #[derive(Debug, Clone)]
@@ -21,22 +30,32 @@ pub struct MarketSnapshot {
     pub timestamp: std::time::Instant,
 }
 
+pub struct MarketMonitor {
+    source: Box<dyn MarketSource>,
+}
+
 impl MarketMonitor {
+    /// Poll the live order books every `check_interval_ms`. Exchange book
+    /// timestamps and hashes come from the WS `cache` when it has them.
     pub fn new(
         api: Arc<PolymarketClient>,
+        cache: PriceCache,
         eth_market: Market,
         btc_market: Market,
         check_interval_ms: u64,
-    ) -> Self {
-        Self {
-            api,
-            eth_market,
-            btc_market,
-            check_interval: Duration::from_millis(check_interval_ms),
-        }
+    ) -> Result<Self> {
+        let source = LiveSource::new(api, cache, eth_market, btc_market, check_interval_ms)?;
+        Ok(Self::with_source(Box::new(source)))
+    }
+
+    pub fn with_source(source: Box<dyn MarketSource>) -> Self {
+        Self { source }
     }
 
-    pub async fn start_monitoring<F, Fut>(&self, on_snapshot: F)
+    /// Run until `cancel` fires or the source runs dry. A snapshot callback
+    /// that has started is always awaited to completion, so in-flight
+    /// trades are never cut off.
+    pub async fn start_monitoring<F, Fut>(&mut self, cancel: CancellationToken, on_snapshot: F)
     where
         F: Fn(MarketSnapshot) -> Fut + Send + Sync + 'static,
         Fut: std::future::Future<Output = ()> + Send + 'static,
@@ -44,102 +63,235 @@ impl MarketMonitor {
         info!("🎬 Monitor starting...");
 
         loop {
-            match self.fetch_snapshot().await {
-                Ok(snapshot) => on_snapshot(snapshot).await,
+            let snapshot = tokio::select! {
+                biased;
+                _ = cancel.cancelled() => break,
+                snapshot = self.source.next_snapshot() => snapshot,
+            };
+
+            match snapshot {
+                Ok(Some(snapshot)) => on_snapshot(snapshot).await,
+                Ok(None) => {
+                    info!("📼 Market source exhausted");
+                    break;
+                }
                 Err(e) => warn!("📊 Snapshot error: {}", e),
             }
+        }
+
+        info!("🛑 Monitor stopped");
+    }
+}
+
+// ==================================================
+// LIVE (REST POLLING)
+// ==================================================
+
+pub struct LiveSource {
+    api: Arc<PolymarketClient>,
+    cache: PriceCache,
+    eth_market: Market,
+    btc_market: Market,
+    eth_tokens: UpDownTokens,
+    btc_tokens: UpDownTokens,
+    check_interval: Duration,
+    polled: bool,
+}
+
+impl LiveSource {
+    pub fn new(
+        api: Arc<PolymarketClient>,
+        cache: PriceCache,
+        eth_market: Market,
+        btc_market: Market,
+        check_interval_ms: u64,
+    ) -> Result<Self> {
+        let eth_tokens = eth_market.up_down_tokens()?;
+        let btc_tokens = btc_market.up_down_tokens()?;
+
+        Ok(Self {
+            api,
+            cache,
+            eth_market,
+            btc_market,
+            eth_tokens,
+            btc_tokens,
+            check_interval: Duration::from_millis(check_interval_ms),
+            polled: false,
+        })
+    }
+}
 
+#[async_trait]
+impl MarketSource for LiveSource {
+    async fn next_snapshot(&mut self) -> Result<Option<MarketSnapshot>> {
+        // First poll is immediate, the rest one interval apart
+        if self.polled {
             sleep(self.check_interval).await;
         }
+        self.polled = true;
+
+        self.fetch_snapshot().await.map(Some)
     }
+}
 
+impl LiveSource {
     async fn fetch_snapshot(&self) -> Result<MarketSnapshot> {
         Ok(MarketSnapshot {
-            eth_market: self.build_market("ETH", &self.eth_market).await?,
-            btc_market: self.build_market("BTC", &self.btc_market).await?,
+            eth_market: self
+                .build_market("ETH", &self.eth_market, &self.eth_tokens)
+                .await?,
+            btc_market: self
+                .build_market("BTC", &self.btc_market, &self.btc_tokens)
+                .await?,
             timestamp: std::time::Instant::now(),
         })
     }
 
-    async fn build_market(&self, name: &str, market: &Market) -> Result<MarketData> {
-        // ===============================
-        // CRITICAL FIX: Use clob_token_ids instead of tokens field
-        // The tokens field is often None for 15-minute markets
-        // ===============================
+    async fn build_market(
+        &self,
+        name: &str,
+        market: &Market,
+        tokens: &UpDownTokens,
+    ) -> Result<MarketData> {
+        // Token sides come from the market's outcome labels, resolved once
+        // at construction — never from array position.
+        let up_token = self.quote(name, "UP  ", &tokens.up.token_id).await;
+        let down_token = self.quote(name, "DOWN", &tokens.down.token_id).await;
 
-        let token_ids_str = market
-            .clob_token_ids
-            .as_ref()
-            .ok_or_else(|| anyhow::anyhow!("{} market missing clob_token_ids", name))?;
+        Ok(MarketData {
+            condition_id: market.condition_id.clone(),
+            market_name: name.to_string(),
+            up_token: Some(up_token),
+            down_token: Some(down_token),
+            end_time: market.end_time(),
+        })
+    }
 
-        let token_ids: Vec<String> = serde_json::from_str(token_ids_str)
-            .map_err(|e| anyhow::anyhow!("Failed to parse {} token IDs: {}", name, e))?;
+    /// Best bid/ask of one token, stamped with when we read it
+    async fn quote(&self, name: &str, side: &str, token_id: &str) -> TokenPrice {
+        let book = match fetch_orderbook(&self.api, token_id).await {
+            Ok(book) => Some(book),
+            Err(e) => {
+                warn!("⚠️  Failed to fetch {} {} prices: {}", name, side.trim(), e);
+                None
+            }
+        };
+        let observed_at_ms = now_ms() as u64;
+        let cached = self.cache.get(token_id).await;
 
-        if token_ids.len() < 2 {
-            return Err(anyhow::anyhow!("{} market has less than 2 tokens", name));
+        let price = token_price(token_id, book.as_ref(), cached.as_ref(), observed_at_ms);
+        if let (Some(b), Some(a)) = (price.bid, price.ask) {
+            info!("📊 {} {} | bid: {} | ask: {}", name, side, b, a);
         }
+        price
+    }
+}
 
-        // First token is typically UP (Yes/1), second is DOWN (No/0)
-        let up_token_id = &token_ids[0];
-        let down_token_id = &token_ids[1];
-
-        // Fetch prices for UP token
-        let (up_bid, up_ask) = match fetch_orderbook(&self.api, up_token_id).await {
-            Ok(book) => {
-                let best_bid = book
-                    .best_bid()
-                    .map(|(price, _size)| Decimal::from_f64_retain(price).unwrap_or(Decimal::ZERO));
-                let best_ask = book
-                    .best_ask()
-                    .map(|(price, _size)| Decimal::from_f64_retain(price).unwrap_or(Decimal::ZERO));
-
-                if let (Some(b), Some(a)) = (best_bid, best_ask) {
-                    info!("📊 {} UP   | bid: {} | ask: {}", name, b, a);
-                }
+/// Quote from the REST top of book. /price carries no depth, so the ask
+/// size comes from the WS ladder when it is a full book offering the same
+/// ask, and is unknown otherwise.
+fn token_price(
+    token_id: &str,
+    book: Option<&OrderBook>,
+    cached: Option<&CachedOrderbook>,
+    observed_at_ms: u64,
+) -> TokenPrice {
+    let to_decimal = |(price, _size): (f64, f64)| Decimal::from_f64(price);
+    let bid = book.and_then(|b| b.best_bid()).and_then(to_decimal);
+    let ask = book.and_then(|b| b.best_ask()).and_then(to_decimal);
+    let ask_size = ask.and_then(|a| cached?.ask_depth_at(a));
 
-                (best_bid, best_ask)
-            }
-            Err(e) => {
-                warn!("⚠️  Failed to fetch {} UP prices: {}", name, e);
-                (None, None)
-            }
-        };
+    TokenPrice {
+        token_id: token_id.to_string(),
+        bid,
+        ask,
+        ask_size,
+        observed_at_ms: Some(observed_at_ms),
+        book_ts_ms: cached.and_then(|c| c.exchange_ts_ms),
+        book_hash: cached.and_then(|c| c.hash.clone()),
+    }
+}
 
-        // Fetch prices for DOWN token
-        let (down_bid, down_ask) = match fetch_orderbook(&self.api, down_token_id).await {
-            Ok(book) => {
-                let best_bid = book
-                    .best_bid()
-                    .map(|(price, _size)| Decimal::from_f64_retain(price).unwrap_or(Decimal::ZERO));
-                let best_ask = book
-                    .best_ask()
-                    .map(|(price, _size)| Decimal::from_f64_retain(price).unwrap_or(Decimal::ZERO));
-
-                if let (Some(b), Some(a)) = (best_bid, best_ask) {
-                    info!("📊 {} DOWN | bid: {} | ask: {}", name, b, a);
-                }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::execution::sizing::{FixedPolicy, Sizer, SizingInput, SizingLimits};
+    use rust_decimal::prelude::ToPrimitive;
+    use rust_decimal_macros::dec;
 
-                (best_bid, best_ask)
-            }
-            Err(e) => {
-                warn!("⚠️  Failed to fetch {} DOWN prices: {}", name, e);
-                (None, None)
-            }
+    fn rest_book(bid: f64, ask: f64) -> OrderBook {
+        OrderBook {
+            bids: vec![(bid, 1.0)],
+            asks: vec![(ask, 1.0)],
+        }
+    }
+
+    fn cached(asks: Vec<(Decimal, Decimal)>, full_depth: bool) -> CachedOrderbook {
+        CachedOrderbook {
+            bids: vec![],
+            asks,
+            last_update_ms: 0,
+            exchange_ts_ms: Some(1),
+            hash: Some("h".into()),
+            full_depth,
+        }
+    }
+
+    /// Shares a $20 fixed stake buys of the pair quoted by `eth` and `btc`
+    fn sized(eth: &TokenPrice, btc: &TokenPrice) -> f64 {
+        let (eth_ask, btc_ask) = (eth.ask.unwrap(), btc.ask.unwrap());
+        let opportunity = ArbitrageOpportunity {
+            eth_up_token_id: eth.token_id.clone(),
+            btc_down_token_id: btc.token_id.clone(),
+            eth_depth: eth.ask_size,
+            btc_depth: btc.ask_size,
+            eth_book: eth.book_version(),
+            btc_book: btc.book_version(),
+            ..ArbitrageOpportunity::at(eth_ask, btc_ask)
         };
+        let sizer = Sizer::new(
+            Box::new(FixedPolicy { usdc: 20.0 }),
+            SizingLimits {
+                min_notional: 1.0,
+                max_notional: 100.0,
+            },
+        );
 
-        Ok(MarketData {
-            condition_id: market.condition_id.clone(),
-            market_name: name.to_string(),
-            up_token: Some(TokenPrice {
-                token_id: up_token_id.clone(),
-                bid: up_bid,
-                ask: up_ask,
-            }),
-            down_token: Some(TokenPrice {
-                token_id: down_token_id.clone(),
-                bid: down_bid,
-                ask: down_ask,
-            }),
-        })
+        sizer
+            .size(&SizingInput {
+                opportunity: &opportunity,
+                balance: 1000.0,
+                reserved: 0.0,
+                eth_depth: opportunity.eth_depth.and_then(|d| d.to_f64()),
+                btc_depth: opportunity.btc_depth.and_then(|d| d.to_f64()),
+            })
+            .eth
+    }
+
+    #[test]
+    fn quoted_depth_sizes_entries() {
+        let full = cached(vec![(dec!(0.45), dec!(30)), (dec!(0.46), dec!(500))], true);
+        let top_only = cached(vec![(dec!(0.45), dec!(1))], false);
+        let moved = cached(vec![(dec!(0.44), dec!(30))], true);
+
+        // (name, cached eth book, expected ask size, expected shares)
+        let cases = [
+            ("no ws book", None, None, 21.0),
+            ("top-of-book placeholder", Some(&top_only), None, 21.0),
+            ("ws ask moved", Some(&moved), None, 21.0),
+            ("full ladder", Some(&full), Some(dec!(30)), 21.0),
+        ];
+
+        let btc = token_price("btc", Some(&rest_book(0.49, 0.50)), None, 0);
+        for (name, cached, ask_size, shares) in cases {
+            let eth = token_price("eth", Some(&rest_book(0.44, 0.45)), cached, 0);
+            assert_eq!(eth.ask_size, ask_size, "{name}");
+            assert_eq!(sized(&eth, &btc), shares, "{name}");
+        }
+
+        let thin = cached(vec![(dec!(0.45), dec!(5))], true);
+        let eth = token_price("eth", Some(&rest_book(0.44, 0.45)), Some(&thin), 0);
+        assert_eq!(sized(&eth, &btc), 5.0, "thin book");
     }
 }
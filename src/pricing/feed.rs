@@ -0,0 +1,230 @@
+use crate::clock::Clock;
+use crate::pricing::{SpotHistory, SECS_PER_YEAR};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use flate2::read::MultiGzDecoder;
+use log::{info, warn};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+use std::sync::Arc;
+use tokio::time::{sleep, Duration};
+use tokio_util::sync::CancellationToken;
+
+// ==================================================
+// FEED
+// ==================================================
+
+/// One spot observation, e.g. {"ts_ms":1760000000000,"asset":"ETH","price":2512.3}
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpotTick {
+    pub ts_ms: u64,
+    pub asset: String,
+    pub price: f64,
+}
+
+/// Where spot prices come from. Like a market source, a feed paces
+/// itself — the pump just asks for the next tick.
+#[async_trait]
+pub trait SpotFeed: Send {
+    /// Next tick; Ok(None) once the feed is exhausted
+    async fn next_tick(&mut self) -> Result<Option<SpotTick>>;
+}
+
+/// Pump `feed` into `history` until it runs dry or `cancel` fires.
+pub async fn run_feed(
+    mut feed: Box<dyn SpotFeed>,
+    history: SpotHistory,
+    cancel: CancellationToken,
+) {
+    info!("📈 Spot feed starting...");
+
+    loop {
+        let tick = tokio::select! {
+            biased;
+            _ = cancel.cancelled() => break,
+            tick = feed.next_tick() => tick,
+        };
+
+        match tick {
+            Ok(Some(tick)) => history.push(&tick),
+            Ok(None) => {
+                info!("📈 Spot feed exhausted");
+                break;
+            }
+            Err(e) => warn!("📈 Spot feed error: {}", e),
+        }
+    }
+}
+
+/// SPOT_FEED_FILE=<path> replays a tick file, SPOT_FEED=mock runs the
+/// random-walk mock. None when neither is set.
+pub fn from_env(clock: Arc<dyn Clock>) -> Result<Option<Box<dyn SpotFeed>>> {
+    if let Ok(path) = env::var("SPOT_FEED_FILE") {
+        let feed = FileSpotFeed::load(&path)?;
+        info!("📈 Spot feed: {} ({} ticks)", path, feed.len());
+        return Ok(Some(Box::new(feed)));
+    }
+
+    if env::var("SPOT_FEED").as_deref() == Ok("mock") {
+        let feed = MockSpotFeed::from_env(clock);
+        info!("📈 Spot feed: mock random walk");
+        return Ok(Some(Box::new(feed)));
+    }
+
+    Ok(None)
+}
+
+// ==================================================
+// FILE / REPLAY
+// ==================================================
+
+/// Ticks from a JSONL file (gzipped if it ends in .gz), in time order.
+///
+/// Everything is handed over at once: the model only ever looks up prices
+/// as of its clock, so ticks ahead of a replay are never seen early.
+pub struct FileSpotFeed {
+    ticks: std::vec::IntoIter<SpotTick>,
+}
+
+impl FileSpotFeed {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let file = File::open(path).map_err(|e| anyhow!("{}: {}", path.display(), e))?;
+        let reader: Box<dyn Read> = if path.extension().is_some_and(|e| e == "gz") {
+            Box::new(MultiGzDecoder::new(file))
+        } else {
+            Box::new(file)
+        };
+
+        let mut ticks = Vec::new();
+        for line in BufReader::new(reader).lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str::<SpotTick>(&line) {
+                Ok(tick) => ticks.push(tick),
+                Err(e) => warn!("⚠️  Skipping bad tick in {}: {}", path.display(), e),
+            }
+        }
+        ticks.sort_by_key(|t| t.ts_ms);
+
+        Ok(Self {
+            ticks: ticks.into_iter(),
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.ticks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ticks.len() == 0
+    }
+}
+
+#[async_trait]
+impl SpotFeed for FileSpotFeed {
+    async fn next_tick(&mut self) -> Result<Option<SpotTick>> {
+        Ok(self.ticks.next())
+    }
+}
+
+// ==================================================
+// MOCK
+// ==================================================
+
+/// Driftless geometric random walk per asset, one tick per asset every
+/// `interval`, stamped from `clock`. For running the model without a feed.
+pub struct MockSpotFeed {
+    prices: Vec<(String, f64)>,
+    sigma: f64,
+    interval: Duration,
+    clock: Arc<dyn Clock>,
+    rng: StdRng,
+    next: usize,
+}
+
+impl MockSpotFeed {
+    pub fn new(
+        prices: Vec<(String, f64)>,
+        sigma: f64,
+        interval: Duration,
+        clock: Arc<dyn Clock>,
+        seed: u64,
+    ) -> Self {
+        Self {
+            prices,
+            sigma,
+            interval,
+            clock,
+            rng: StdRng::seed_from_u64(seed),
+            next: 0,
+        }
+    }
+
+    pub fn from_env(clock: Arc<dyn Clock>) -> Self {
+        let read = |key: &str, default: f64| {
+            env::var(key)
+                .ok()
+                .and_then(|v| v.parse::<f64>().ok())
+                .unwrap_or(default)
+        };
+
+        // Read MOCK_ETH_PRICE / MOCK_BTC_PRICE from env (default: 2500 / 60000)
+        let prices = vec![
+            ("ETH".to_string(), read("MOCK_ETH_PRICE", 2500.0)),
+            ("BTC".to_string(), read("MOCK_BTC_PRICE", 60000.0)),
+        ];
+
+        // Read MOCK_SPOT_VOL from env (default: 0.60 annualized)
+        let sigma = read("MOCK_SPOT_VOL", 0.60);
+
+        // Read MOCK_SPOT_INTERVAL_MS from env (default: 1000)
+        let interval = Duration::from_millis(read("MOCK_SPOT_INTERVAL_MS", 1000.0) as u64);
+
+        Self::new(prices, sigma, interval, clock, rand::random())
+    }
+
+    /// Standard normal draw (Box–Muller)
+    fn gaussian(&mut self) -> f64 {
+        let u1: f64 = self.rng.random::<f64>().max(f64::MIN_POSITIVE);
+        let u2: f64 = self.rng.random::<f64>();
+        (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+    }
+}
+
+#[async_trait]
+impl SpotFeed for MockSpotFeed {
+    async fn next_tick(&mut self) -> Result<Option<SpotTick>> {
+        if self.prices.is_empty() {
+            return Ok(None);
+        }
+
+        // One round of assets per interval
+        if self.next == self.prices.len() {
+            self.next = 0;
+            sleep(self.interval).await;
+        }
+
+        let dt = self.interval.as_secs_f64() / SECS_PER_YEAR;
+        let z = self.gaussian();
+        let sigma = self.sigma;
+
+        let (asset, price) = &mut self.prices[self.next];
+        *price *= (-0.5 * sigma * sigma * dt + sigma * dt.sqrt() * z).exp();
+        let tick = SpotTick {
+            ts_ms: self.clock.now_ms(),
+            asset: asset.clone(),
+            price: *price,
+        };
+
+        self.next += 1;
+        Ok(Some(tick))
+    }
+}
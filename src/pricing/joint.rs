@@ -0,0 +1,409 @@
+use crate::client::PolymarketClient;
+use crate::domain::{Outcome, PayoutOdds};
+use crate::recorder::{self, Record, RecordEvent};
+use crate::scheduler::{period_start, PERIOD_SECS};
+use anyhow::{anyhow, Result};
+use log::{info, warn};
+use std::collections::{HashMap, HashSet};
+use std::env;
+use std::sync::{Arc, RwLock};
+use tokio::time::{sleep, Duration};
+
+// ==================================================
+// JOINT DISTRIBUTION
+// ==================================================
+
+/// Probabilities of the four (ETH, BTC) direction pairs; sum to 1.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct JointOutcomes {
+    pub up_up: f64,
+    pub up_down: f64,
+    pub down_up: f64,
+    pub down_down: f64,
+}
+
+impl JointOutcomes {
+    pub fn eth_up(&self) -> f64 {
+        self.up_up + self.up_down
+    }
+
+    pub fn btc_up(&self) -> f64 {
+        self.up_up + self.down_up
+    }
+
+    /// Correlation of the two up/down indicators (phi coefficient)
+    pub fn phi(&self) -> f64 {
+        let (pe, pb) = (self.eth_up(), self.btc_up());
+        let spread = (pe * (1.0 - pe) * pb * (1.0 - pb)).sqrt();
+        if spread <= 0.0 {
+            return 0.0;
+        }
+        (self.up_up * self.down_down - self.up_down * self.down_up) / spread
+    }
+
+    /// Joint with the given marginals and indicator correlation, clamped to
+    /// the Fréchet bounds so every cell stays a probability.
+    pub fn from_marginals(eth_up: f64, btc_up: f64, phi: f64) -> Self {
+        let (pe, pb) = (eth_up.clamp(0.0, 1.0), btc_up.clamp(0.0, 1.0));
+        let spread = (pe * (1.0 - pe) * pb * (1.0 - pb)).sqrt();
+        let up_up = (pe * pb + phi * spread).clamp((pe + pb - 1.0).max(0.0), pe.min(pb));
+
+        Self {
+            up_up,
+            up_down: pe - up_up,
+            down_up: pb - up_up,
+            down_down: 1.0 - pe - pb + up_up,
+        }
+    }
+
+    fn p(&self, eth: Outcome, btc: Outcome) -> f64 {
+        match (eth, btc) {
+            (Outcome::Up, Outcome::Up) => self.up_up,
+            (Outcome::Up, Outcome::Down) => self.up_down,
+            (Outcome::Down, Outcome::Up) => self.down_up,
+            (Outcome::Down, Outcome::Down) => self.down_down,
+        }
+    }
+
+    /// Payout of one ETH `eth_side` + one BTC `btc_side` share: 2 if both
+    /// win, 1 if exactly one does, 0 if neither.
+    pub fn pair(&self, eth_side: Outcome, btc_side: Outcome) -> PairPayout {
+        let both = self.p(eth_side, btc_side);
+        let neither = self.p(eth_side.opposite(), btc_side.opposite());
+        let one = (1.0 - both - neither).max(0.0);
+
+        let ev = one + 2.0 * both;
+        let variance = (one + 4.0 * both - ev * ev).max(0.0);
+
+        PairPayout {
+            ev,
+            variance,
+            odds: PayoutOdds { both, one, neither },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PairPayout {
+    /// Expected payout per pair
+    pub ev: f64,
+    pub variance: f64,
+    pub odds: PayoutOdds,
+}
+
+// ==================================================
+// COUNTS
+// ==================================================
+
+/// Resolved (ETH, BTC) direction pairs
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JointCounts {
+    pub up_up: u32,
+    pub up_down: u32,
+    pub down_up: u32,
+    pub down_down: u32,
+}
+
+impl JointCounts {
+    pub fn total(&self) -> u32 {
+        self.up_up + self.up_down + self.down_up + self.down_down
+    }
+
+    fn add(&mut self, eth: Outcome, btc: Outcome) {
+        match (eth, btc) {
+            (Outcome::Up, Outcome::Up) => self.up_up += 1,
+            (Outcome::Up, Outcome::Down) => self.up_down += 1,
+            (Outcome::Down, Outcome::Up) => self.down_up += 1,
+            (Outcome::Down, Outcome::Down) => self.down_down += 1,
+        }
+    }
+
+    /// Posterior mean with `prior` pseudo-counts per cell
+    pub fn probabilities(&self, prior: f64) -> JointOutcomes {
+        let n = self.total() as f64 + 4.0 * prior;
+        let p = |c: u32| (c as f64 + prior) / n;
+
+        JointOutcomes {
+            up_up: p(self.up_up),
+            up_down: p(self.up_down),
+            down_up: p(self.down_up),
+            down_down: p(self.down_down),
+        }
+    }
+}
+
+// ==================================================
+// MODEL
+// ==================================================
+
+#[derive(Debug, Clone)]
+pub struct JointParams {
+    /// Pseudo-count per cell
+    pub prior: f64,
+    /// Resolved periods needed before the model is used
+    pub min_periods: u32,
+}
+
+impl JointParams {
+    pub fn from_env() -> Self {
+        // Read JOINT_PRIOR from env (default: 1.0)
+        let prior = env::var("JOINT_PRIOR")
+            .ok()
+            .and_then(|v| v.parse::<f64>().ok())
+            .unwrap_or(1.0)
+            .max(0.0);
+
+        // Read JOINT_MIN_PERIODS from env (default: 20)
+        let min_periods = env::var("JOINT_MIN_PERIODS")
+            .ok()
+            .and_then(|v| v.parse::<u32>().ok())
+            .unwrap_or(20);
+
+        Self { prior, min_periods }
+    }
+}
+
+#[derive(Default)]
+struct JointState {
+    counts: JointCounts,
+    /// ETH condition ids already counted
+    seen: HashSet<String>,
+}
+
+/// Historical joint distribution of ETH and BTC 15m directions, learned
+/// from resolved periods. Shared; every clone sees the same counts.
+#[derive(Clone)]
+pub struct JointModel {
+    state: Arc<RwLock<JointState>>,
+    params: JointParams,
+}
+
+impl JointModel {
+    pub fn new(params: JointParams) -> Self {
+        Self {
+            state: Arc::new(RwLock::new(JointState::default())),
+            params,
+        }
+    }
+
+    pub fn from_env() -> Self {
+        Self::new(JointParams::from_env())
+    }
+
+    pub fn counts(&self) -> JointCounts {
+        self.state.read().unwrap_or_else(|e| e.into_inner()).counts
+    }
+
+    /// Count one resolved period, keyed by its ETH market so a period seen
+    /// from two sources is counted once.
+    pub fn observe(&self, eth_condition_id: &str, eth: Outcome, btc: Outcome) {
+        let mut state = self.state.write().unwrap_or_else(|e| e.into_inner());
+        if state.seen.insert(eth_condition_id.to_string()) {
+            state.counts.add(eth, btc);
+        }
+    }
+
+    /// The estimated joint, once enough periods have resolved
+    pub fn estimate(&self) -> Option<JointOutcomes> {
+        let counts = self.counts();
+        (counts.total() >= self.params.min_periods).then(|| counts.probabilities(self.params.prior))
+    }
+
+    // ==================================================
+    // HISTORY SOURCES
+    // ==================================================
+
+    /// Seed from the recordings in JOINT_HISTORY_DIR (default: RECORD_DIR)
+    pub fn seed_from_env(&self) {
+        let Ok(dir) = env::var("JOINT_HISTORY_DIR").or_else(|_| env::var("RECORD_DIR")) else {
+            return;
+        };
+
+        match recorder::read_dir(&dir) {
+            Ok(records) => {
+                let added = self.observe_records(&records);
+                info!("🔗 Joint model: {} period(s) from {}", added, dir);
+            }
+            Err(e) => warn!("⚠️  Joint history in {} unreadable: {}", dir, e),
+        }
+    }
+
+    /// Count every period in a recording whose two markets both resolved.
+    pub fn observe_records(&self, records: &[Record]) -> usize {
+        let mut pairs: Vec<(String, String)> = Vec::new();
+        let mut winners: HashMap<&str, Outcome> = HashMap::new();
+
+        for record in records {
+            match &record.event {
+                RecordEvent::MarketMeta {
+                    eth_market,
+                    btc_market,
+                    ..
+                } => pairs.push((
+                    eth_market.condition_id.clone(),
+                    btc_market.condition_id.clone(),
+                )),
+                RecordEvent::Resolution {
+                    condition_id,
+                    winner_outcome,
+                    ..
+                } => {
+                    if let Some(outcome) = Outcome::from_label(winner_outcome) {
+                        winners.insert(condition_id, outcome);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let before = self.counts().total();
+        for (eth, btc) in &pairs {
+            if let (Some(&e), Some(&b)) = (winners.get(eth.as_str()), winners.get(btc.as_str())) {
+                self.observe(eth, e, b);
+            }
+        }
+        (self.counts().total() - before) as usize
+    }
+
+    /// Fetch the last `periods` resolved periods from Gamma and the CLOB.
+    pub async fn backfill(&self, api: &PolymarketClient, periods: u64, now: u64) -> usize {
+        let current = period_start(now);
+        let mut added = 0;
+
+        for i in 1..=periods {
+            let ts = current.saturating_sub(i * PERIOD_SECS);
+            match resolved_pair(api, ts).await {
+                Ok((eth_id, eth, btc)) => {
+                    let before = self.counts().total();
+                    self.observe(&eth_id, eth, btc);
+                    added += (self.counts().total() - before) as usize;
+                }
+                Err(e) => warn!("⚠️  Joint history for period {} unavailable: {}", ts, e),
+            }
+        }
+
+        let c = self.counts();
+        info!(
+            "🔗 Joint model: +{} period(s) from Gamma, {} total (UU {} / UD {} / DU {} / DD {})",
+            added,
+            c.total(),
+            c.up_up,
+            c.up_down,
+            c.down_up,
+            c.down_down
+        );
+        added
+    }
+
+    /// Count a just-finished period once both markets publish a winner.
+    pub async fn watch_period(
+        &self,
+        api: &PolymarketClient,
+        eth_condition_id: &str,
+        btc_condition_id: &str,
+        timeout: Duration,
+    ) {
+        let deadline = tokio::time::Instant::now() + timeout;
+
+        while tokio::time::Instant::now() < deadline {
+            let eth = winner(api, eth_condition_id).await;
+            let btc = winner(api, btc_condition_id).await;
+
+            if let (Ok(Some(eth)), Ok(Some(btc))) = (&eth, &btc) {
+                self.observe(eth_condition_id, *eth, *btc);
+                return;
+            }
+            sleep(Duration::from_secs(30)).await;
+        }
+
+        warn!(
+            "⚠️  {} / {} did not resolve in time — not added to joint model",
+            eth_condition_id, btc_condition_id
+        );
+    }
+}
+
+/// Winning outcome of a market, None while unresolved
+async fn winner(api: &PolymarketClient, condition_id: &str) -> Result<Option<Outcome>> {
+    let details = api.get_market_details(condition_id).await?;
+    Ok(details
+        .tokens
+        .iter()
+        .find(|t| t.winner)
+        .and_then(|t| Outcome::from_label(&t.outcome)))
+}
+
+async fn resolved_pair(api: &PolymarketClient, ts: u64) -> Result<(String, Outcome, Outcome)> {
+    let eth = api
+        .get_market_by_slug(&format!("eth-updown-15m-{}", ts))
+        .await?;
+    let btc = api
+        .get_market_by_slug(&format!("btc-updown-15m-{}", ts))
+        .await?;
+
+    let eth_winner = winner(api, &eth.condition_id)
+        .await?
+        .ok_or_else(|| anyhow!("{} unresolved", eth.slug))?;
+    let btc_winner = winner(api, &btc.condition_id)
+        .await?
+        .ok_or_else(|| anyhow!("{} unresolved", btc.slug))?;
+
+    Ok((eth.condition_id, eth_winner, btc_winner))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-12
+    }
+
+    fn cells(j: &JointOutcomes) -> [f64; 4] {
+        [j.up_up, j.up_down, j.down_up, j.down_down]
+    }
+
+    #[test]
+    fn from_marginals_clamps_to_the_frechet_bounds() {
+        let cases = [
+            // (eth_up, btc_up, phi, up_up)
+            (0.6, 0.5, 0.0, 0.3),
+            // Perfect correlation is capped at the smaller marginal...
+            (0.6, 0.5, 1.0, 0.5),
+            (0.6, 0.5, 5.0, 0.5),
+            // ...and perfect anti-correlation at pe + pb − 1
+            (0.6, 0.5, -1.0, 0.1),
+            (0.3, 0.4, -1.0, 0.0),
+        ];
+
+        for (eth_up, btc_up, phi, up_up) in cases {
+            let j = JointOutcomes::from_marginals(eth_up, btc_up, phi);
+            assert!(close(j.up_up, up_up), "phi {}: {:?}", phi, j);
+            assert!(close(j.eth_up(), eth_up) && close(j.btc_up(), btc_up));
+            assert!(cells(&j).iter().all(|&p| p >= -1e-12));
+            assert!(close(cells(&j).iter().sum(), 1.0));
+        }
+    }
+
+    #[test]
+    fn pair_payout_ev_and_variance() {
+        // Independent: ETH UP wins 0.6, BTC DOWN 0.5
+        let j = JointOutcomes::from_marginals(0.6, 0.5, 0.0);
+        let pair = j.pair(Outcome::Up, Outcome::Down);
+        assert!(close(pair.odds.both, 0.3));
+        assert!(close(pair.odds.neither, 0.2));
+        assert!(close(pair.odds.one, 0.5));
+        assert!(close(pair.ev, 1.1));
+        // E[X²] = 0.5 + 4 × 0.3 = 1.7
+        assert!(close(pair.variance, 1.7 - 1.1 * 1.1));
+
+        // Perfectly correlated coin flips: UP + DOWN always pays exactly 1
+        let j = JointOutcomes::from_marginals(0.5, 0.5, 1.0);
+        let hedge = j.pair(Outcome::Up, Outcome::Down);
+        assert!(close(hedge.ev, 1.0));
+        assert!(close(hedge.variance, 0.0));
+        let same = j.pair(Outcome::Up, Outcome::Up);
+        assert!(close(same.ev, 1.0));
+        assert!(close(same.variance, 1.0));
+    }
+}
@@ -0,0 +1,340 @@
+pub mod feed;
+pub mod joint;
+
+use crate::clock::Clock;
+use crate::pricing::feed::SpotTick;
+use crate::scheduler::PERIOD_SECS;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::env;
+use std::sync::{Arc, RwLock};
+
+/// Crypto trades around the clock
+pub const SECS_PER_YEAR: f64 = 365.0 * 24.0 * 3600.0;
+
+// ==================================================
+// SPOT HISTORY
+// ==================================================
+
+/// (ts_ms, price), oldest first
+pub type Ticks = Vec<(u64, f64)>;
+
+/// Spot ticks per asset, in time order. Lookups are always "as of" a
+/// time, so a replayed history never leaks the future.
+#[derive(Clone, Default)]
+pub struct SpotHistory {
+    inner: Arc<RwLock<HashMap<String, Ticks>>>,
+    /// How far behind the clock history is kept; None keeps everything
+    retention: Option<(u64, Arc<dyn Clock>)>,
+}
+
+impl SpotHistory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Drop ticks more than `secs` behind `clock`, keeping the last tick
+    /// before that so "as of" lookups at the edge still resolve. Call before
+    /// the history is shared.
+    pub fn with_retention(mut self, secs: u64, clock: Arc<dyn Clock>) -> Self {
+        self.retention = Some((secs * 1000, clock));
+        self
+    }
+
+    pub fn push(&self, tick: &SpotTick) {
+        if !(tick.price.is_finite() && tick.price > 0.0) {
+            return;
+        }
+
+        let mut map = self.inner.write().unwrap_or_else(|e| e.into_inner());
+        let ticks = map.entry(tick.asset.to_ascii_uppercase()).or_default();
+
+        // Feeds are almost always in order; keep it sorted when they're not
+        let at = ticks.partition_point(|&(ts, _)| ts <= tick.ts_ms);
+        ticks.insert(at, (tick.ts_ms, tick.price));
+
+        let Some((retention_ms, clock)) = &self.retention else {
+            return;
+        };
+
+        // Prune once the stale prefix is half the buffer, so each tick is
+        // moved a bounded number of times
+        let cutoff = clock.now_ms().saturating_sub(*retention_ms);
+        let stale = ticks
+            .partition_point(|&(ts, _)| ts < cutoff)
+            .saturating_sub(1);
+        if stale > 0 && stale * 2 >= ticks.len() {
+            ticks.drain(..stale);
+        }
+    }
+
+    /// Last price at or before `ts_ms`
+    pub fn price_at(&self, asset: &str, ts_ms: u64) -> Option<f64> {
+        let map = self.inner.read().unwrap_or_else(|e| e.into_inner());
+        let ticks = map.get(&asset.to_ascii_uppercase())?;
+        let at = ticks.partition_point(|&(ts, _)| ts <= ts_ms);
+        at.checked_sub(1).map(|i| ticks[i].1)
+    }
+
+    /// Ticks in `from_ms..=to_ms`
+    pub fn window(&self, asset: &str, from_ms: u64, to_ms: u64) -> Ticks {
+        self.with_window(asset, from_ms, to_ms, <[_]>::to_vec)
+    }
+
+    /// Run `f` over the ticks in `from_ms..=to_ms` without copying them
+    pub fn with_window<R>(
+        &self,
+        asset: &str,
+        from_ms: u64,
+        to_ms: u64,
+        f: impl FnOnce(&[(u64, f64)]) -> R,
+    ) -> R {
+        let map = self.inner.read().unwrap_or_else(|e| e.into_inner());
+        let Some(ticks) = map.get(&asset.to_ascii_uppercase()) else {
+            return f(&[]);
+        };
+        let start = ticks.partition_point(|&(ts, _)| ts < from_ms);
+        let end = ticks.partition_point(|&(ts, _)| ts <= to_ms);
+        f(&ticks[start..end.max(start)])
+    }
+}
+
+// ==================================================
+// VOLATILITY
+// ==================================================
+
+/// Annualized realized volatility of time-ordered (ts_ms, price) ticks:
+/// √(Σ log-return² / elapsed years). Handles uneven spacing.
+pub fn realized_vol(ticks: &[(u64, f64)]) -> Option<f64> {
+    if ticks.len() < 2 {
+        return None;
+    }
+
+    let elapsed_ms = ticks[ticks.len() - 1].0.saturating_sub(ticks[0].0);
+    if elapsed_ms == 0 {
+        return None;
+    }
+
+    let sum_sq: f64 = ticks
+        .windows(2)
+        .map(|w| (w[1].1 / w[0].1).ln().powi(2))
+        .sum();
+
+    let years = elapsed_ms as f64 / 1000.0 / SECS_PER_YEAR;
+    Some((sum_sq / years).sqrt())
+}
+
+// ==================================================
+// DIGITAL FAIR VALUE
+// ==================================================
+
+/// Standard normal CDF (Abramowitz & Stegun 26.2.17, |error| < 7.5e-8)
+pub fn norm_cdf(x: f64) -> f64 {
+    let t = 1.0 / (1.0 + 0.231_641_9 * x.abs());
+    let poly = t
+        * (0.319_381_530
+            + t * (-0.356_563_782
+                + t * (1.781_477_937 + t * (-1.821_255_978 + t * 1.330_274_429))));
+    let tail = (-0.5 * x * x).exp() / (2.0 * std::f64::consts::PI).sqrt() * poly;
+
+    if x >= 0.0 {
+        1.0 - tail
+    } else {
+        tail
+    }
+}
+
+/// Probability that spot finishes at or above `strike` after `tau_secs`,
+/// under a driftless lognormal with annualized vol `sigma` (N(d₂)).
+pub fn digital_up(spot: f64, strike: f64, sigma: f64, tau_secs: f64) -> f64 {
+    if tau_secs <= 0.0 || sigma <= 0.0 {
+        return if spot >= strike { 1.0 } else { 0.0 };
+    }
+
+    let tau = tau_secs / SECS_PER_YEAR;
+    let sd = sigma * tau.sqrt();
+    let d2 = ((spot / strike).ln() - 0.5 * sd * sd) / sd;
+    norm_cdf(d2)
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct FairValue {
+    /// Fair probability the market resolves UP (and DOWN = 1 − UP)
+    pub up: f64,
+    pub down: f64,
+    pub spot: f64,
+    /// Spot at the period start — the price UP has to beat
+    pub strike: f64,
+    pub sigma: f64,
+    pub tau_secs: f64,
+}
+
+// ==================================================
+// MODEL
+// ==================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PricingParams {
+    /// Lookback for realized vol
+    pub vol_window_secs: u64,
+    /// Used until the window has enough ticks
+    pub fallback_vol: f64,
+    /// Floor, so a quiet stretch doesn't price the outcome as certain
+    pub min_vol: f64,
+    /// Fair minus ask at which a token is flagged as mispriced
+    pub min_edge: f64,
+}
+
+impl PricingParams {
+    pub fn from_env() -> Self {
+        // Read VOL_WINDOW_SECS from env (default: 900)
+        let vol_window_secs = env::var("VOL_WINDOW_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(900);
+
+        // Read FALLBACK_ANNUAL_VOL from env (default: 0.60)
+        let fallback_vol = env::var("FALLBACK_ANNUAL_VOL")
+            .ok()
+            .and_then(|v| v.parse::<f64>().ok())
+            .unwrap_or(0.60);
+
+        // Read MIN_ANNUAL_VOL from env (default: 0.10)
+        let min_vol = env::var("MIN_ANNUAL_VOL")
+            .ok()
+            .and_then(|v| v.parse::<f64>().ok())
+            .unwrap_or(0.10);
+
+        // Read MISPRICING_MIN_EDGE from env (default: 0.05)
+        let min_edge = env::var("MISPRICING_MIN_EDGE")
+            .ok()
+            .and_then(|v| v.parse::<f64>().ok())
+            .unwrap_or(0.05);
+
+        Self {
+            vol_window_secs,
+            fallback_vol,
+            min_vol,
+            min_edge,
+        }
+    }
+}
+
+/// Fair UP/DOWN probabilities for the 15m markets from spot history.
+///
+/// The feed should track the market's resolution source; a different
+/// exchange's print can sit on the other side of the strike.
+#[derive(Clone)]
+pub struct PricingModel {
+    history: SpotHistory,
+    params: PricingParams,
+}
+
+impl PricingModel {
+    pub fn new(history: SpotHistory, params: PricingParams) -> Self {
+        Self { history, params }
+    }
+
+    pub fn from_env() -> Self {
+        Self::new(SpotHistory::new(), PricingParams::from_env())
+    }
+
+    /// Keep spot history for the vol lookback plus one period behind
+    /// `clock` — enough for the current window's strike — instead of forever
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        let secs = self.params.vol_window_secs + PERIOD_SECS;
+        self.history = self.history.with_retention(secs, clock);
+        self
+    }
+
+    pub fn history(&self) -> SpotHistory {
+        self.history.clone()
+    }
+
+    pub fn params(&self) -> &PricingParams {
+        &self.params
+    }
+
+    /// Realized vol over the lookback ending at `now_ms`, floored
+    pub fn sigma(&self, asset: &str, now_ms: u64) -> f64 {
+        let from = now_ms.saturating_sub(self.params.vol_window_secs * 1000);
+        self.history
+            .with_window(asset, from, now_ms, realized_vol)
+            .unwrap_or(self.params.fallback_vol)
+            .max(self.params.min_vol)
+    }
+
+    /// Fair value at `now_ms` of a window running `start_ms..end_ms`.
+    /// None until the feed has a price from before the window opened.
+    pub fn fair_value(
+        &self,
+        asset: &str,
+        start_ms: u64,
+        end_ms: u64,
+        now_ms: u64,
+    ) -> Option<FairValue> {
+        let strike = self.history.price_at(asset, start_ms)?;
+        let spot = self.history.price_at(asset, now_ms)?;
+        let sigma = self.sigma(asset, now_ms);
+        let tau_secs = end_ms.saturating_sub(now_ms) as f64 / 1000.0;
+
+        let up = digital_up(spot, strike, sigma, tau_secs);
+
+        Some(FairValue {
+            up,
+            down: 1.0 - up,
+            spot,
+            strike,
+            sigma,
+            tau_secs,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64, tol: f64) -> bool {
+        (a - b).abs() < tol
+    }
+
+    #[test]
+    fn norm_cdf_matches_known_values() {
+        assert!(close(norm_cdf(0.0), 0.5, 1e-7));
+        assert!(close(norm_cdf(1.96), 0.975_002, 1e-6));
+        assert!(close(norm_cdf(-1.0), 0.158_655, 1e-6));
+        for x in [0.3, 1.0, 2.5] {
+            assert!(close(norm_cdf(x) + norm_cdf(-x), 1.0, 1e-7));
+        }
+    }
+
+    #[test]
+    fn digital_up_steps_at_expiry() {
+        // No time or no vol left: the outcome is known
+        assert_eq!(digital_up(101.0, 100.0, 0.5, 0.0), 1.0);
+        assert_eq!(digital_up(100.0, 100.0, 0.5, 0.0), 1.0);
+        assert_eq!(digital_up(99.0, 100.0, 0.5, 0.0), 0.0);
+        assert_eq!(digital_up(99.0, 100.0, 0.0, 600.0), 0.0);
+
+        // At the money the vol drag leaves UP just under a coin flip
+        let atm = digital_up(100.0, 100.0, 0.5, 600.0);
+        assert!(atm < 0.5 && atm > 0.49);
+
+        // Further in the money, and closer to expiry, is surer
+        assert!(digital_up(101.0, 100.0, 0.5, 600.0) > atm);
+        assert!(digital_up(101.0, 100.0, 0.5, 60.0) > digital_up(101.0, 100.0, 0.5, 600.0));
+    }
+
+    #[test]
+    fn realized_vol_annualizes_log_returns() {
+        // ±1% every second
+        let ticks = [(0, 100.0), (1_000, 101.0), (2_000, 100.0), (3_000, 101.0)];
+        let per_tick = (101.0f64 / 100.0).ln();
+        let expected = per_tick * SECS_PER_YEAR.sqrt();
+        assert!(close(realized_vol(&ticks).unwrap(), expected, 1e-9));
+
+        assert_eq!(realized_vol(&ticks[..1]), None);
+        assert_eq!(realized_vol(&[(5, 100.0), (5, 101.0)]), None);
+    }
+}
@@ -0,0 +1,431 @@
+use crate::client::PolymarketClient;
+use crate::domain::{Market, MarketData};
+use crate::monitor::MarketSnapshot;
+use crate::scheduler::{period_start, PreparedPeriod, TokenParams};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use flate2::read::MultiGzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::env;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::{mpsc, oneshot};
+use tokio::time::{sleep, Duration};
+
+// ==================================================
+// RECORD FORMAT
+// ==================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RecordEvent {
+    MarketMeta {
+        period_start: u64,
+        ends_at: DateTime<Utc>,
+        eth_market: Box<Market>,
+        btc_market: Box<Market>,
+        token_params: HashMap<String, TokenParams>,
+    },
+    Snapshot {
+        eth_market: Box<MarketData>,
+        btc_market: Box<MarketData>,
+    },
+    WsDelta {
+        token_id: String,
+        payload: Value,
+    },
+    Resolution {
+        condition_id: String,
+        winner_token_id: String,
+        winner_outcome: String,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Record {
+    pub ts_ms: u64,
+    #[serde(flatten)]
+    pub event: RecordEvent,
+}
+
+// ==================================================
+// RECORDER HANDLE
+// ==================================================
+
+enum Command {
+    Record(Record),
+    Close(oneshot::Sender<()>),
+}
+
+/// Cheap, cloneable handle. Recording is a channel send; compression and
+/// disk I/O happen on a blocking writer thread.
+#[derive(Clone)]
+pub struct Recorder {
+    tx: mpsc::UnboundedSender<Command>,
+}
+
+impl Recorder {
+    pub fn start(dir: impl Into<PathBuf>, max_file_bytes: u64) -> Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+
+        info!("💾 Recording market data to {}", dir.display());
+
+        let (tx, rx) = mpsc::unbounded_channel();
+        tokio::task::spawn_blocking(move || writer_loop(dir, max_file_bytes, rx));
+
+        Ok(Self { tx })
+    }
+
+    /// Start recording into RECORD_DIR, if set. Files roll over per 15m
+    /// period and whenever one exceeds RECORD_MAX_FILE_MB (default: 50).
+    pub fn from_env() -> Result<Option<Self>> {
+        let Ok(dir) = env::var("RECORD_DIR") else {
+            return Ok(None);
+        };
+
+        let max_mb = env::var("RECORD_MAX_FILE_MB")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(50);
+
+        Self::start(dir, max_mb * 1024 * 1024).map(Some)
+    }
+
+    pub fn record(&self, event: RecordEvent) {
+        let record = Record {
+            ts_ms: Utc::now().timestamp_millis().max(0) as u64,
+            event,
+        };
+        // Writer gone means recording already failed loudly; stay quiet here
+        let _ = self.tx.send(Command::Record(record));
+    }
+
+    pub fn record_period(&self, period: &PreparedPeriod) {
+        self.record(RecordEvent::MarketMeta {
+            period_start: period.period_start,
+            ends_at: period.ends_at,
+            eth_market: Box::new(period.eth_market.clone()),
+            btc_market: Box::new(period.btc_market.clone()),
+            token_params: period.token_params.clone(),
+        });
+    }
+
+    pub fn record_snapshot(&self, snapshot: &MarketSnapshot) {
+        self.record(RecordEvent::Snapshot {
+            eth_market: Box::new(snapshot.eth_market.clone()),
+            btc_market: Box::new(snapshot.btc_market.clone()),
+        });
+    }
+
+    pub fn record_ws(&self, token_id: &str, payload: &Value) {
+        self.record(RecordEvent::WsDelta {
+            token_id: token_id.to_string(),
+            payload: payload.clone(),
+        });
+    }
+
+    /// Finish the current file (writes the gzip trailer) and stop.
+    pub async fn close(&self) {
+        let (done_tx, done_rx) = oneshot::channel();
+        if self.tx.send(Command::Close(done_tx)).is_ok() {
+            let _ = done_rx.await;
+        }
+    }
+
+    /// Poll the CLOB until `condition_id` resolves, then record the winner.
+    pub async fn watch_resolution(
+        &self,
+        api: Arc<PolymarketClient>,
+        condition_id: String,
+        timeout: Duration,
+    ) {
+        let deadline = tokio::time::Instant::now() + timeout;
+
+        while tokio::time::Instant::now() < deadline {
+            match api.get_market_details(&condition_id).await {
+                Ok(details) => {
+                    if let Some(winner) = details.tokens.iter().find(|t| t.winner) {
+                        self.record(RecordEvent::Resolution {
+                            condition_id,
+                            winner_token_id: winner.token_id.clone(),
+                            winner_outcome: winner.outcome.clone(),
+                        });
+                        return;
+                    }
+                }
+                Err(e) => warn!("⚠️  Resolution check for {} failed: {}", condition_id, e),
+            }
+            sleep(Duration::from_secs(30)).await;
+        }
+
+        warn!(
+            "⚠️  {} did not resolve in time — not recorded",
+            condition_id
+        );
+    }
+}
+
+// ==================================================
+// WRITER
+// ==================================================
+
+struct OpenFile {
+    period: u64,
+    part: u32,
+    bytes: u64,
+    encoder: GzEncoder<BufWriter<File>>,
+}
+
+/// Opens `part`, or the next part after it that doesn't exist yet: a
+/// restart mid-period must not truncate what was already recorded.
+fn open_file(dir: &Path, period: u64, mut part: u32) -> Result<OpenFile> {
+    let (path, file) = loop {
+        let path = dir.join(format!("{}-{:03}.jsonl.gz", period, part));
+        match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(file) => break (path, file),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => part += 1,
+            Err(e) => return Err(e.into()),
+        }
+    };
+    info!("💾 Recording → {}", path.display());
+
+    Ok(OpenFile {
+        period,
+        part,
+        bytes: 0,
+        encoder: GzEncoder::new(BufWriter::new(file), Compression::fast()),
+    })
+}
+
+fn writer_loop(dir: PathBuf, max_file_bytes: u64, mut rx: mpsc::UnboundedReceiver<Command>) {
+    let mut current: Option<OpenFile> = None;
+
+    while let Some(cmd) = rx.blocking_recv() {
+        let record = match cmd {
+            Command::Record(record) => record,
+            Command::Close(done) => {
+                if let Some(file) = current.take() {
+                    finish(file);
+                }
+                let _ = done.send(());
+                return;
+            }
+        };
+
+        let period = period_start(record.ts_ms / 1000);
+        let next_part = match &current {
+            Some(f) if f.period != period => Some(0),
+            Some(f) if f.bytes >= max_file_bytes => Some(f.part + 1),
+            Some(_) => None,
+            None => Some(0),
+        };
+
+        if let Some(part) = next_part {
+            if let Some(file) = current.take() {
+                finish(file);
+            }
+            match open_file(&dir, period, part) {
+                Ok(file) => current = Some(file),
+                Err(e) => {
+                    warn!("⚠️  Recorder cannot open file: {}", e);
+                    continue;
+                }
+            }
+        }
+
+        let Some(file) = current.as_mut() else {
+            continue;
+        };
+
+        match serde_json::to_string(&record) {
+            Ok(line) => {
+                file.bytes += line.len() as u64 + 1;
+                if let Err(e) = writeln!(file.encoder, "{}", line) {
+                    warn!("⚠️  Recorder write failed: {}", e);
+                }
+            }
+            Err(e) => warn!("⚠️  Recorder serialize failed: {}", e),
+        }
+    }
+
+    if let Some(file) = current.take() {
+        finish(file);
+    }
+}
+
+fn finish(file: OpenFile) {
+    if let Err(e) = file.encoder.finish().and_then(|mut w| w.flush()) {
+        warn!("⚠️  Recorder failed to finish file: {}", e);
+    }
+}
+
+// ==================================================
+// READER
+// ==================================================
+
+/// Recorded files in `dir`, in chronological order.
+pub fn list_files(dir: impl AsRef<Path>) -> Result<Vec<PathBuf>> {
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.to_string_lossy().ends_with(".jsonl.gz"))
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+/// Read one file. A truncated tail (crash mid-write) ends the file early
+/// instead of failing it.
+pub fn read_file(path: impl AsRef<Path>) -> Result<Vec<Record>> {
+    let path = path.as_ref();
+    let reader = BufReader::new(MultiGzDecoder::new(File::open(path)?));
+    let mut records = Vec::new();
+
+    for line in reader.lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(e) => {
+                warn!("⚠️  {} truncated: {}", path.display(), e);
+                break;
+            }
+        };
+        match serde_json::from_str::<Record>(&line) {
+            Ok(record) => records.push(record),
+            Err(e) => warn!("⚠️  Skipping bad record in {}: {}", path.display(), e),
+        }
+    }
+
+    Ok(records)
+}
+
+/// Every record under `dir`, sorted by timestamp.
+pub fn read_dir(dir: impl AsRef<Path>) -> Result<Vec<Record>> {
+    let mut records = Vec::new();
+    for path in list_files(dir)? {
+        records.extend(read_file(path)?);
+    }
+    records.sort_by_key(|r| r.ts_ms);
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// 15m-aligned, in ms
+    const PERIOD_MS: u64 = 1_800_000_000_000;
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("recorder-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn delta(ts_ms: u64) -> Record {
+        Record {
+            ts_ms,
+            event: RecordEvent::WsDelta {
+                token_id: "tok".into(),
+                payload: json!({ "bid": "0.45", "ask": "0.46" }),
+            },
+        }
+    }
+
+    /// Run a writer over `records` until they're all written
+    fn write(dir: &Path, max_file_bytes: u64, records: Vec<Record>) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        for record in records {
+            tx.send(Command::Record(record)).unwrap();
+        }
+        drop(tx);
+        writer_loop(dir.to_path_buf(), max_file_bytes, rx);
+    }
+
+    fn names(dir: &Path) -> Vec<String> {
+        list_files(dir)
+            .unwrap()
+            .iter()
+            .map(|p| p.file_name().unwrap().to_string_lossy().into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn records_survive_rotation_and_restart() {
+        let dir = scratch_dir("round-trip");
+        let period = PERIOD_MS / 1000;
+        let next = period + 900;
+
+        // One byte per file: every record after the first rotates
+        write(
+            &dir,
+            1,
+            vec![
+                delta(PERIOD_MS),
+                delta(PERIOD_MS + 1),
+                delta(PERIOD_MS + 2),
+                delta(PERIOD_MS + 900_000),
+            ],
+        );
+        // A restart mid-period appends the next free part
+        write(&dir, 1 << 20, vec![delta(PERIOD_MS + 3)]);
+
+        assert_eq!(
+            names(&dir),
+            vec![
+                format!("{}-000.jsonl.gz", period),
+                format!("{}-001.jsonl.gz", period),
+                format!("{}-002.jsonl.gz", period),
+                format!("{}-003.jsonl.gz", period),
+                format!("{}-000.jsonl.gz", next),
+            ]
+        );
+
+        let ts: Vec<u64> = read_dir(&dir).unwrap().iter().map(|r| r.ts_ms).collect();
+        assert_eq!(
+            ts,
+            vec![
+                PERIOD_MS,
+                PERIOD_MS + 1,
+                PERIOD_MS + 2,
+                PERIOD_MS + 3,
+                PERIOD_MS + 900_000
+            ]
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn truncated_tail_keeps_what_was_written() {
+        let dir = scratch_dir("truncated");
+        write(&dir, 1 << 20, vec![delta(PERIOD_MS), delta(PERIOD_MS + 1)]);
+        let path = list_files(&dir).unwrap().remove(0);
+
+        // A crash mid-write leaves half of a second gzip member behind
+        let mut member = GzEncoder::new(Vec::new(), Compression::fast());
+        writeln!(
+            member,
+            "{}",
+            serde_json::to_string(&delta(PERIOD_MS + 2)).unwrap()
+        )
+        .unwrap();
+        let member = member.finish().unwrap();
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(&member[..member.len() / 2]).unwrap();
+        drop(file);
+
+        let ts: Vec<u64> = read_file(&path).unwrap().iter().map(|r| r.ts_ms).collect();
+        assert_eq!(ts, vec![PERIOD_MS, PERIOD_MS + 1]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
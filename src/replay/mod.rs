@@ -0,0 +1,223 @@
+use crate::cache::PriceCache;
+use crate::clock::ReplayClock;
+use crate::monitor::{MarketSnapshot, MarketSource};
+use crate::recorder::{self, Record, RecordEvent};
+use crate::scheduler::PreparedPeriod;
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use log::{info, warn};
+use std::collections::HashMap;
+use std::env;
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::time::{sleep, Duration};
+
+// ==================================================
+// CONFIG
+// ==================================================
+
+#[derive(Debug, Clone)]
+pub struct ReplayConfig {
+    pub dir: String,
+    /// 1.0 = recorded pace, 10.0 = ten times faster, 0 = no waiting
+    pub speed: f64,
+}
+
+impl ReplayConfig {
+    /// Replay mode is on when REPLAY_DIR is set
+    pub fn from_env() -> Option<Self> {
+        let dir = env::var("REPLAY_DIR").ok()?;
+
+        // Read REPLAY_SPEED from env (default: 1.0)
+        let speed = env::var("REPLAY_SPEED")
+            .ok()
+            .and_then(|v| v.parse::<f64>().ok())
+            .unwrap_or(1.0)
+            .max(0.0);
+
+        Some(Self { dir, speed })
+    }
+
+    /// Scale a recorded gap to wall time at this speed
+    pub fn scale(&self, d: Duration) -> Duration {
+        if self.speed > 0.0 {
+            d.div_f64(self.speed)
+        } else {
+            Duration::ZERO
+        }
+    }
+}
+
+// ==================================================
+// SESSION
+// ==================================================
+
+/// Winning token of a recorded market resolution
+#[derive(Debug, Clone)]
+pub struct Winner {
+    pub token_id: String,
+    pub outcome: String,
+}
+
+/// One recorded period: its markets and everything recorded until the
+/// next period's metadata.
+pub struct ReplayPeriod {
+    pub period: PreparedPeriod,
+    pub records: Vec<Record>,
+}
+
+pub struct ReplaySession {
+    pub periods: Vec<ReplayPeriod>,
+    pub resolutions: HashMap<String, Winner>,
+}
+
+impl ReplaySession {
+    pub fn load(dir: &str) -> Result<Self> {
+        let records = recorder::read_dir(dir)?;
+
+        let mut periods: Vec<ReplayPeriod> = Vec::new();
+        let mut resolutions = HashMap::new();
+        let mut skipped = 0usize;
+
+        for record in records {
+            match &record.event {
+                RecordEvent::MarketMeta {
+                    period_start,
+                    ends_at,
+                    eth_market,
+                    btc_market,
+                    token_params,
+                } => {
+                    let period = PreparedPeriod {
+                        period_start: *period_start,
+                        eth_tokens: eth_market.up_down_tokens()?,
+                        btc_tokens: btc_market.up_down_tokens()?,
+                        eth_market: (**eth_market).clone(),
+                        btc_market: (**btc_market).clone(),
+                        ends_at: *ends_at,
+                        token_params: token_params.clone(),
+                        rewards: HashMap::new(),
+                    };
+                    periods.push(ReplayPeriod {
+                        period,
+                        records: vec![record],
+                    });
+                }
+                RecordEvent::Resolution {
+                    condition_id,
+                    winner_token_id,
+                    winner_outcome,
+                } => {
+                    resolutions.insert(
+                        condition_id.clone(),
+                        Winner {
+                            token_id: winner_token_id.clone(),
+                            outcome: winner_outcome.clone(),
+                        },
+                    );
+                }
+                _ => match periods.last_mut() {
+                    Some(p) => p.records.push(record),
+                    None => skipped += 1,
+                },
+            }
+        }
+
+        if skipped > 0 {
+            warn!(
+                "⚠️  Skipped {} record(s) before the first market metadata",
+                skipped
+            );
+        }
+        if periods.is_empty() {
+            return Err(anyhow!("No recorded periods in {}", dir));
+        }
+
+        info!(
+            "📼 Loaded {} period(s), {} resolution(s) from {}",
+            periods.len(),
+            resolutions.len(),
+            dir
+        );
+
+        Ok(Self {
+            periods,
+            resolutions,
+        })
+    }
+}
+
+// ==================================================
+// SOURCE
+// ==================================================
+
+/// Feeds one recorded period to the monitor: WS deltas go into the price
+/// cache, snapshots come out, and the replay clock follows the records.
+pub struct ReplaySource {
+    records: std::vec::IntoIter<Record>,
+    cache: PriceCache,
+    clock: Arc<ReplayClock>,
+    config: ReplayConfig,
+    last_ts: Option<u64>,
+    /// Period close from the metadata, for snapshots recorded without one
+    ends_at: Option<DateTime<Utc>>,
+}
+
+impl ReplaySource {
+    pub fn new(
+        records: Vec<Record>,
+        cache: PriceCache,
+        clock: Arc<ReplayClock>,
+        config: ReplayConfig,
+    ) -> Self {
+        Self {
+            records: records.into_iter(),
+            cache,
+            clock,
+            config,
+            last_ts: None,
+            ends_at: None,
+        }
+    }
+}
+
+#[async_trait]
+impl MarketSource for ReplaySource {
+    async fn next_snapshot(&mut self) -> Result<Option<MarketSnapshot>> {
+        for record in self.records.by_ref() {
+            if let Some(last) = self.last_ts {
+                let gap = Duration::from_millis(record.ts_ms.saturating_sub(last));
+                let wait = self.config.scale(gap);
+                if !wait.is_zero() {
+                    sleep(wait).await;
+                }
+            }
+            self.last_ts = Some(record.ts_ms);
+            self.clock.set(record.ts_ms);
+
+            match record.event {
+                RecordEvent::WsDelta { token_id, payload } => {
+                    self.cache.update_from_price_ws(&token_id, &payload).await;
+                }
+                RecordEvent::Snapshot {
+                    mut eth_market,
+                    mut btc_market,
+                } => {
+                    eth_market.end_time = eth_market.end_time.or(self.ends_at);
+                    btc_market.end_time = btc_market.end_time.or(self.ends_at);
+
+                    return Ok(Some(MarketSnapshot {
+                        eth_market: *eth_market,
+                        btc_market: *btc_market,
+                        timestamp: Instant::now(),
+                    }));
+                }
+                RecordEvent::MarketMeta { ends_at, .. } => self.ends_at = Some(ends_at),
+                RecordEvent::Resolution { .. } => {}
+            }
+        }
+
+        Ok(None)
+    }
+}
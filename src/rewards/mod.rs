@@ -0,0 +1,258 @@
+use crate::domain::Rewards;
+use chrono::NaiveDate;
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+/// One-sided liquidity scores a third of two-sided
+pub const SINGLE_SIDED_DIVISOR: f64 = 3.0;
+
+/// Midpoints outside this band only pay two-sided liquidity
+const TWO_SIDED_BAND: (f64, f64) = (0.10, 0.90);
+
+// ==================================================
+// PROGRAM
+// ==================================================
+
+/// A market's liquidity reward program
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RewardParams {
+    /// Furthest from the midpoint an order may rest and still score
+    pub max_spread: Decimal,
+    /// Smallest order that scores, in shares
+    pub min_size: Decimal,
+    /// USDC paid out per epoch (day) across all makers
+    pub daily_rate: Decimal,
+}
+
+impl RewardParams {
+    /// None when the market has no program
+    pub fn from_rewards(rewards: &Rewards) -> Option<Self> {
+        let daily_rate: Decimal = rewards
+            .rates
+            .as_ref()
+            .and_then(|r| r.as_array())
+            .map(|rates| {
+                rates
+                    .iter()
+                    .filter_map(|r| r.get("rewards_daily_rate"))
+                    .filter_map(|v| v.as_f64().or_else(|| v.as_str()?.parse().ok()))
+                    .filter_map(Decimal::from_f64)
+                    .sum()
+            })
+            .unwrap_or_default();
+
+        if rewards.max_spread <= Decimal::ZERO || daily_rate <= Decimal::ZERO {
+            return None;
+        }
+
+        Some(Self {
+            // Published in cents
+            max_spread: rewards.max_spread / dec!(100),
+            min_size: rewards.min_size,
+            daily_rate,
+        })
+    }
+
+    /// Lowest bid at `tick` that still scores against `mid`. A bid exactly
+    /// `max_spread` away scores zero, so the bound is strict.
+    pub fn min_bid(&self, mid: Decimal, tick: Decimal) -> Decimal {
+        let edge = mid - self.max_spread;
+        let bid = (edge / tick).ceil() * tick;
+        if bid == edge {
+            bid + tick
+        } else {
+            bid
+        }
+    }
+
+    /// ((v − s) / v)² × size for an order `s` from the midpoint; 0 outside
+    /// the max spread or under the min size.
+    pub fn order_score(&self, mid: Decimal, price: Decimal, size: Decimal) -> f64 {
+        let spread = (mid - price).abs();
+        if size < self.min_size || spread > self.max_spread {
+            return 0.0;
+        }
+
+        let v = self.max_spread.to_f64().unwrap_or(0.0);
+        let s = spread.to_f64().unwrap_or(0.0);
+        ((v - s) / v).powi(2) * size.to_f64().unwrap_or(0.0)
+    }
+
+    /// Score of resting bids on one outcome token
+    pub fn bids_score(&self, mid: Decimal, bids: &[(Decimal, Decimal)]) -> f64 {
+        bids.iter()
+            .map(|&(price, size)| self.order_score(mid, price, size))
+            .sum()
+    }
+}
+
+/// Two-sided score of a market from the bids on each outcome token (a bid
+/// on the complement is an ask on the market). `mid` is either token's.
+pub fn q_min(q_one: f64, q_two: f64, mid: Decimal) -> f64 {
+    let mid = mid.to_f64().unwrap_or(0.5);
+    let two_sided = q_one.min(q_two);
+
+    if (TWO_SIDED_BAND.0..=TWO_SIDED_BAND.1).contains(&mid) {
+        two_sided.max(q_one.max(q_two) / SINGLE_SIDED_DIVISOR)
+    } else {
+        two_sided
+    }
+}
+
+// ==================================================
+// TRACKER
+// ==================================================
+
+/// What we have earned in one market over one epoch
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RewardEstimate {
+    /// Our Q_min summed per minute sampled
+    pub score: f64,
+    /// Seconds our orders scored
+    pub scoring_secs: f64,
+    /// Estimated USDC earned
+    pub usdc: f64,
+}
+
+impl fmt::Display for RewardEstimate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "${:.4} (score {:.1}, {:.0}s scoring)",
+            self.usdc, self.score, self.scoring_secs
+        )
+    }
+}
+
+/// Liquidity rewards we are estimated to have earned, per epoch (UTC day)
+/// and market. Shared; every clone sees the same books.
+///
+/// The payout is our share of the market's Q_min each sample times the
+/// daily rate. Other makers' Q_min comes from the visible book, so it only
+/// approximates the exchange's sampling.
+#[derive(Clone, Default)]
+pub struct RewardTracker {
+    state: Arc<Mutex<HashMap<(NaiveDate, String), RewardEstimate>>>,
+}
+
+impl RewardTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Book `secs` of resting with score `ours` against everyone else's `others`
+    pub fn sample(
+        &self,
+        epoch: NaiveDate,
+        condition_id: &str,
+        params: &RewardParams,
+        ours: f64,
+        others: f64,
+        secs: f64,
+    ) {
+        if ours <= 0.0 || secs <= 0.0 {
+            return;
+        }
+
+        let share = ours / (ours + others.max(0.0));
+        let rate = params.daily_rate.to_f64().unwrap_or(0.0);
+
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let estimate = state.entry((epoch, condition_id.to_string())).or_default();
+        estimate.score += ours * secs / 60.0;
+        estimate.scoring_secs += secs;
+        estimate.usdc += share * rate * secs / 86_400.0;
+    }
+
+    /// Estimates per market for `epoch`
+    pub fn epoch(&self, epoch: NaiveDate) -> Vec<(String, RewardEstimate)> {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state
+            .iter()
+            .filter(|((day, _), _)| *day == epoch)
+            .map(|((_, condition_id), estimate)| (condition_id.clone(), *estimate))
+            .collect()
+    }
+
+    /// Estimated USDC earned over `epoch`
+    pub fn epoch_total(&self, epoch: NaiveDate) -> f64 {
+        self.epoch(epoch).iter().map(|(_, e)| e.usdc).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params() -> RewardParams {
+        RewardParams {
+            max_spread: dec!(0.03),
+            min_size: dec!(50),
+            daily_rate: dec!(100),
+        }
+    }
+
+    #[test]
+    fn min_bid_is_strictly_inside_the_max_spread() {
+        let cases = [
+            // (mid, tick, min bid)
+            (dec!(0.50), dec!(0.01), dec!(0.48)),
+            (dec!(0.505), dec!(0.01), dec!(0.48)),
+            (dec!(0.515), dec!(0.01), dec!(0.49)),
+            (dec!(0.50), dec!(0.001), dec!(0.471)),
+        ];
+        for (mid, tick, expected) in cases {
+            assert_eq!(params().min_bid(mid, tick), expected, "mid {}", mid);
+        }
+    }
+
+    #[test]
+    fn order_score_cases() {
+        let cases = [
+            // (price, size, score) against a 0.50 mid
+            (dec!(0.50), dec!(100), 100.0),
+            (dec!(0.48), dec!(100), 100.0 / 9.0),
+            (dec!(0.52), dec!(100), 100.0 / 9.0),
+            // Exactly max spread away scores nothing
+            (dec!(0.47), dec!(100), 0.0),
+            (dec!(0.46), dec!(100), 0.0),
+            (dec!(0.49), dec!(49), 0.0),
+        ];
+        for (price, size, expected) in cases {
+            let score = params().order_score(dec!(0.50), price, size);
+            assert!(
+                (score - expected).abs() < 1e-9,
+                "{} × {}: {}",
+                price,
+                size,
+                score
+            );
+        }
+    }
+
+    #[test]
+    fn q_min_cases() {
+        let cases = [
+            // (q_one, q_two, mid, q_min)
+            (90.0, 60.0, dec!(0.50), 60.0),
+            (90.0, 0.0, dec!(0.50), 30.0),
+            (90.0, 0.0, dec!(0.10), 30.0),
+            (90.0, 0.0, dec!(0.95), 0.0),
+            (90.0, 60.0, dec!(0.05), 60.0),
+        ];
+        for (q_one, q_two, mid, expected) in cases {
+            assert_eq!(
+                q_min(q_one, q_two, mid),
+                expected,
+                "{} / {} @ {}",
+                q_one,
+                q_two,
+                mid
+            );
+        }
+    }
+}
@@ -0,0 +1,672 @@
+use crate::client::PolymarketClient;
+use crate::clock::{self, Clock};
+use crate::config::Config;
+use crate::domain::order::Side;
+use crate::execution::errors::ClobError;
+use crate::monitor::MarketSnapshot;
+use crate::scheduler::period_start;
+use chrono::NaiveDate;
+use log::{info, warn};
+use rust_decimal::prelude::FromPrimitive;
+use rust_decimal::Decimal;
+use std::collections::{HashMap, HashSet};
+use std::env;
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::time::{sleep, Duration};
+
+// ==================================================
+// LIMITS
+// ==================================================
+
+#[derive(Debug, Clone)]
+pub struct RiskLimits {
+    pub max_trade_notional: Decimal,
+    pub max_period_notional: Decimal,
+    pub max_daily_notional: Decimal,
+    pub max_open_positions_per_market: usize,
+    pub max_daily_loss: Decimal,
+    pub kill_switch_file: Option<PathBuf>,
+}
+
+impl RiskLimits {
+    pub fn from_env() -> Self {
+        fn dec_env(key: &str, default: f64) -> Decimal {
+            let v = env::var(key)
+                .ok()
+                .and_then(|v| v.parse::<f64>().ok())
+                .unwrap_or(default);
+            Decimal::from_f64(v).unwrap_or_default()
+        }
+
+        Self {
+            // Per-trade cap is the existing MAX_TRADE_SIZE
+            max_trade_notional: Decimal::from_f64(Config::max_trade_size()).unwrap_or_default(),
+            max_period_notional: dec_env("MAX_PERIOD_NOTIONAL", 200.0),
+            max_daily_notional: dec_env("MAX_DAILY_NOTIONAL", 1000.0),
+            max_open_positions_per_market: env::var("MAX_OPEN_POSITIONS_PER_MARKET")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(3),
+            max_daily_loss: dec_env("MAX_DAILY_LOSS", 50.0),
+            kill_switch_file: Some(
+                env::var("KILL_SWITCH_FILE")
+                    .unwrap_or_else(|_| "KILL_SWITCH".to_string())
+                    .into(),
+            ),
+        }
+    }
+}
+
+// ==================================================
+// REJECTION REASONS
+// ==================================================
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RiskRejection {
+    KillSwitch,
+    TradeNotional {
+        requested: Decimal,
+        limit: Decimal,
+    },
+    PeriodNotional {
+        used: Decimal,
+        requested: Decimal,
+        limit: Decimal,
+    },
+    DailyNotional {
+        used: Decimal,
+        requested: Decimal,
+        limit: Decimal,
+    },
+    OpenPositions {
+        condition_id: String,
+        open: usize,
+        limit: usize,
+    },
+    DailyLoss {
+        pnl: Decimal,
+        limit: Decimal,
+    },
+    MarketClosed {
+        condition_id: String,
+    },
+}
+
+impl fmt::Display for RiskRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RiskRejection::KillSwitch => write!(f, "Kill switch engaged"),
+            RiskRejection::TradeNotional { requested, limit } => {
+                write!(f, "Trade notional ${} exceeds ${}", requested, limit)
+            }
+            RiskRejection::PeriodNotional {
+                used,
+                requested,
+                limit,
+            } => write!(
+                f,
+                "Period notional ${} + ${} exceeds ${}",
+                used, requested, limit
+            ),
+            RiskRejection::DailyNotional {
+                used,
+                requested,
+                limit,
+            } => write!(
+                f,
+                "Daily notional ${} + ${} exceeds ${}",
+                used, requested, limit
+            ),
+            RiskRejection::OpenPositions {
+                condition_id,
+                open,
+                limit,
+            } => write!(
+                f,
+                "Market {} has {} open positions (max {})",
+                condition_id, open, limit
+            ),
+            RiskRejection::DailyLoss { pnl, limit } => {
+                write!(f, "Daily PnL ${} breaches loss cap ${}", pnl, limit)
+            }
+            RiskRejection::MarketClosed { condition_id } => {
+                write!(f, "Market {} no longer accepts orders", condition_id)
+            }
+        }
+    }
+}
+
+impl std::error::Error for RiskRejection {}
+
+// ==================================================
+// ORDER INTENT
+// ==================================================
+
+#[derive(Debug, Clone)]
+pub struct OrderIntent {
+    pub condition_id: String,
+    pub token_id: String,
+    pub side: Side,
+    pub price: Decimal,
+    pub size: Decimal,
+}
+
+impl OrderIntent {
+    pub fn notional(&self) -> Decimal {
+        self.price * self.size
+    }
+}
+
+// ==================================================
+// STATE
+// ==================================================
+
+#[derive(Debug, Clone, Default)]
+struct Position {
+    condition_id: String,
+    size: Decimal,
+    cost: Decimal,
+    mark: Option<Decimal>,
+}
+
+#[derive(Debug)]
+struct RiskState {
+    day: NaiveDate,
+    period: u64,
+    period_notional: Decimal,
+    daily_notional: Decimal,
+    realized_pnl: Decimal,
+    positions: HashMap<String, Position>,
+    next_hold: u64,
+    /// Hold id → notional passed by `check` but not yet booked
+    holds: HashMap<u64, Decimal>,
+    /// Markets the CLOB reported closed; no new entries go to them
+    closed_markets: HashSet<String>,
+}
+
+impl RiskState {
+    fn new(clock: &dyn Clock) -> Self {
+        Self {
+            day: clock.now().date_naive(),
+            period: current_period(clock),
+            period_notional: Decimal::ZERO,
+            daily_notional: Decimal::ZERO,
+            realized_pnl: Decimal::ZERO,
+            positions: HashMap::new(),
+            next_hold: 0,
+            holds: HashMap::new(),
+            closed_markets: HashSet::new(),
+        }
+    }
+
+    /// Reset period / daily counters when the clock has moved on
+    fn roll(&mut self, clock: &dyn Clock) {
+        let period = current_period(clock);
+        if period != self.period {
+            self.period = period;
+            self.period_notional = Decimal::ZERO;
+        }
+
+        let today = clock.now().date_naive();
+        if today != self.day {
+            self.day = today;
+            self.daily_notional = Decimal::ZERO;
+            self.realized_pnl = Decimal::ZERO;
+            self.closed_markets.clear();
+        }
+    }
+
+    fn unrealized_pnl(&self) -> Decimal {
+        self.positions
+            .values()
+            .filter_map(|p| p.mark.map(|m| p.size * m - p.cost))
+            .sum()
+    }
+
+    fn held_notional(&self) -> Decimal {
+        self.holds.values().copied().sum()
+    }
+
+    /// Tokens with shares held in `condition_id`
+    fn open_positions(&self, condition_id: &str) -> usize {
+        self.positions
+            .values()
+            .filter(|p| p.condition_id == condition_id && p.size > Decimal::ZERO)
+            .count()
+    }
+
+    fn reduces_position(&self, o: &OrderIntent) -> bool {
+        o.side == Side::Sell
+            && self
+                .positions
+                .get(&o.token_id)
+                .is_some_and(|p| p.size >= o.size)
+    }
+}
+
+fn current_period(clock: &dyn Clock) -> u64 {
+    period_start(clock.now_ms() / 1000)
+}
+
+// ==================================================
+// RISK MANAGER
+// ==================================================
+
+/// Gatekeeper every order passes through before it reaches the CLOB.
+pub struct RiskManager {
+    limits: RiskLimits,
+    state: Mutex<RiskState>,
+    killed: AtomicBool,
+    clock: Arc<dyn Clock>,
+}
+
+impl RiskManager {
+    pub fn new(limits: RiskLimits) -> Self {
+        let clock = clock::system();
+
+        Self {
+            limits,
+            state: Mutex::new(RiskState::new(clock.as_ref())),
+            killed: AtomicBool::new(false),
+            clock,
+        }
+    }
+
+    /// Period and daily windows follow `clock` instead of the wall clock.
+    /// Call before any order is checked — it starts the windows afresh.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.state = Mutex::new(RiskState::new(clock.as_ref()));
+        self.clock = clock;
+        self
+    }
+
+    pub fn limits(&self) -> &RiskLimits {
+        &self.limits
+    }
+
+    pub fn engage_kill_switch(&self) {
+        warn!("🛑 Kill switch engaged — no new risk will be taken");
+        self.killed.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_killed(&self) -> bool {
+        self.killed.load(Ordering::SeqCst)
+            || self
+                .limits
+                .kill_switch_file
+                .as_ref()
+                .is_some_and(|p| p.exists())
+    }
+
+    /// Check a group of orders (e.g. both legs of one trade) as a unit.
+    ///
+    /// Sells that only reduce an existing position always pass, so unwinds
+    /// are never blocked by the limits they exist to respect. Passing
+    /// orders hold their notional against the period and daily limits until
+    /// the returned hold drops, so concurrent trades can't both fit.
+    pub fn check(self: &Arc<Self>, orders: &[OrderIntent]) -> Result<RiskHold, RiskRejection> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.roll(self.clock.as_ref());
+
+        if orders.iter().all(|o| state.reduces_position(o)) {
+            return Ok(self.hold(&mut state, Decimal::ZERO));
+        }
+
+        if self.is_killed() {
+            return Err(RiskRejection::KillSwitch);
+        }
+
+        let requested: Decimal = orders.iter().map(OrderIntent::notional).sum();
+
+        if requested > self.limits.max_trade_notional {
+            return Err(RiskRejection::TradeNotional {
+                requested,
+                limit: self.limits.max_trade_notional,
+            });
+        }
+
+        let held = state.held_notional();
+
+        if state.period_notional + held + requested > self.limits.max_period_notional {
+            return Err(RiskRejection::PeriodNotional {
+                used: state.period_notional + held,
+                requested,
+                limit: self.limits.max_period_notional,
+            });
+        }
+
+        if state.daily_notional + held + requested > self.limits.max_daily_notional {
+            return Err(RiskRejection::DailyNotional {
+                used: state.daily_notional + held,
+                requested,
+                limit: self.limits.max_daily_notional,
+            });
+        }
+
+        for o in orders.iter().filter(|o| o.side == Side::Buy) {
+            if state.closed_markets.contains(&o.condition_id) {
+                return Err(RiskRejection::MarketClosed {
+                    condition_id: o.condition_id.clone(),
+                });
+            }
+
+            // Adding to a token already held opens nothing new
+            if state.positions.contains_key(&o.token_id) {
+                continue;
+            }
+
+            let open = state.open_positions(&o.condition_id);
+            if open >= self.limits.max_open_positions_per_market {
+                return Err(RiskRejection::OpenPositions {
+                    condition_id: o.condition_id.clone(),
+                    open,
+                    limit: self.limits.max_open_positions_per_market,
+                });
+            }
+        }
+
+        let pnl = state.realized_pnl + state.unrealized_pnl();
+        if pnl <= -self.limits.max_daily_loss {
+            return Err(RiskRejection::DailyLoss {
+                pnl,
+                limit: self.limits.max_daily_loss,
+            });
+        }
+
+        Ok(self.hold(&mut state, requested))
+    }
+
+    fn hold(self: &Arc<Self>, state: &mut RiskState, notional: Decimal) -> RiskHold {
+        state.next_hold += 1;
+        let id = state.next_hold;
+        if notional > Decimal::ZERO {
+            state.holds.insert(id, notional);
+        }
+
+        RiskHold {
+            risk: self.clone(),
+            id,
+        }
+    }
+
+    fn release(&self, id: u64) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.holds.remove(&id);
+    }
+
+    /// Book an accepted order against the limits and positions.
+    pub fn record_fill(&self, order: &OrderIntent) {
+        self.book_fill(None, order);
+    }
+
+    fn book_fill(&self, hold: Option<u64>, order: &OrderIntent) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.roll(self.clock.as_ref());
+
+        match order.side {
+            Side::Buy => {
+                if let Some(held) = hold.and_then(|id| state.holds.get_mut(&id)) {
+                    *held = (*held - order.notional()).max(Decimal::ZERO);
+                }
+                state.period_notional += order.notional();
+                state.daily_notional += order.notional();
+
+                let pos = state.positions.entry(order.token_id.clone()).or_default();
+                pos.condition_id = order.condition_id.clone();
+                pos.size += order.size;
+                pos.cost += order.notional();
+            }
+            Side::Sell => {
+                let Some(pos) = state.positions.get_mut(&order.token_id) else {
+                    return;
+                };
+
+                let size = order.size.min(pos.size);
+                let avg_cost = if pos.size > Decimal::ZERO {
+                    pos.cost / pos.size
+                } else {
+                    Decimal::ZERO
+                };
+                let realized = size * (order.price - avg_cost);
+
+                pos.size -= size;
+                pos.cost -= size * avg_cost;
+                let closed = pos.size <= Decimal::ZERO;
+
+                state.realized_pnl += realized;
+
+                if closed {
+                    state.positions.remove(&order.token_id);
+                }
+            }
+        }
+    }
+
+    /// Learn from an order the CLOB turned down: a closed market takes no
+    /// more entries, and broken credentials stop trading altogether.
+    pub fn record_rejection(&self, order: &OrderIntent, error: &ClobError) {
+        if error.is_fatal() {
+            warn!("🚫 CLOB refuses our orders ({}) — stopping", error);
+            self.engage_kill_switch();
+            return;
+        }
+
+        if let ClobError::MarketClosed(_) = error {
+            warn!("🚫 Market {} closed — no new entries", order.condition_id);
+            let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+            state.closed_markets.insert(order.condition_id.clone());
+        }
+    }
+
+    /// Mark open positions to the best bid of the latest snapshot.
+    pub fn mark_snapshot(&self, snapshot: &MarketSnapshot) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+
+        let tokens = [
+            &snapshot.eth_market.up_token,
+            &snapshot.eth_market.down_token,
+            &snapshot.btc_market.up_token,
+            &snapshot.btc_market.down_token,
+        ];
+
+        for token in tokens.into_iter().flatten() {
+            if let Some(pos) = state.positions.get_mut(&token.token_id) {
+                pos.mark = token.bid.or(pos.mark);
+            }
+        }
+    }
+
+    /// Close every position in `condition_id` at its resolution payout —
+    /// 1 per share of the winner, 0 otherwise. Returns the realized PnL.
+    pub fn settle(&self, condition_id: &str, winner_token_id: &str) -> Decimal {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.roll(self.clock.as_ref());
+
+        let tokens: Vec<String> = state
+            .positions
+            .iter()
+            .filter(|(_, p)| p.condition_id == condition_id)
+            .map(|(t, _)| t.clone())
+            .collect();
+
+        let mut realized = Decimal::ZERO;
+        for token_id in tokens {
+            let Some(pos) = state.positions.remove(&token_id) else {
+                continue;
+            };
+            let payout = if token_id == winner_token_id {
+                pos.size
+            } else {
+                Decimal::ZERO
+            };
+            realized += payout - pos.cost;
+        }
+
+        state.realized_pnl += realized;
+        if realized != Decimal::ZERO {
+            info!("⚖️  {} settled — realized ${}", condition_id, realized);
+        }
+        realized
+    }
+
+    /// Poll until `condition_id` resolves (or `timeout` passes), then settle.
+    pub async fn settle_when_resolved(
+        &self,
+        api: &PolymarketClient,
+        condition_id: &str,
+        timeout: Duration,
+    ) {
+        let deadline = tokio::time::Instant::now() + timeout;
+
+        while tokio::time::Instant::now() < deadline {
+            match api.get_market_details(condition_id).await {
+                Ok(details) => {
+                    if let Some(winner) = details.tokens.iter().find(|t| t.winner) {
+                        self.settle(condition_id, &winner.token_id);
+                        return;
+                    }
+                }
+                Err(e) => warn!("⚠️  Resolution check for {} failed: {}", condition_id, e),
+            }
+            sleep(Duration::from_secs(30)).await;
+        }
+
+        warn!(
+            "⚠️  {} did not resolve in time — risk positions left open",
+            condition_id
+        );
+    }
+
+    /// Realized + unrealized PnL for the current day
+    pub fn daily_pnl(&self) -> Decimal {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.roll(self.clock.as_ref());
+        state.realized_pnl + state.unrealized_pnl()
+    }
+}
+
+/// Notional a passed check holds against the period and daily limits.
+/// Fills booked through it draw it down; the rest is released when it drops.
+pub struct RiskHold {
+    risk: Arc<RiskManager>,
+    id: u64,
+}
+
+impl RiskHold {
+    /// Book a fill of the checked orders, moving its notional off the hold
+    pub fn record_fill(&self, order: &OrderIntent) {
+        self.risk.book_fill(Some(self.id), order);
+    }
+}
+
+impl Drop for RiskHold {
+    fn drop(&mut self) {
+        self.risk.release(self.id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ReplayClock;
+    use rust_decimal_macros::dec;
+
+    fn buy(condition_id: &str, token_id: &str, price: Decimal, size: Decimal) -> OrderIntent {
+        OrderIntent {
+            condition_id: condition_id.into(),
+            token_id: token_id.into(),
+            side: Side::Buy,
+            price,
+            size,
+        }
+    }
+
+    fn risk(clock: Arc<ReplayClock>, max_period_notional: Decimal) -> Arc<RiskManager> {
+        Arc::new(
+            RiskManager::new(RiskLimits {
+                max_trade_notional: dec!(100),
+                max_period_notional,
+                max_daily_notional: dec!(1000),
+                max_open_positions_per_market: 1,
+                max_daily_loss: dec!(50),
+                kill_switch_file: None,
+            })
+            .with_clock(clock),
+        )
+    }
+
+    #[test]
+    fn settled_positions_leave_the_book_at_their_payout() {
+        let clock = Arc::new(ReplayClock::new(1_800_000_000_000));
+        let risk = risk(clock.clone(), dec!(1000));
+
+        risk.record_fill(&buy("eth", "eth-up", dec!(0.40), dec!(100)));
+        risk.record_fill(&buy("btc", "btc-down", dec!(0.55), dec!(100)));
+
+        // ETH lost, BTC won: -40 + 45
+        assert_eq!(risk.settle("eth", "eth-down"), dec!(-40));
+        assert_eq!(risk.settle("btc", "btc-down"), dec!(45));
+        assert_eq!(risk.daily_pnl(), dec!(5));
+
+        // Nothing left to mark, and the next day starts clean
+        clock.set(1_800_000_000_000 + 86_400_000);
+        assert_eq!(risk.daily_pnl(), Decimal::ZERO);
+        assert!(risk
+            .check(&[buy("eth", "eth-up", dec!(0.40), dec!(10))])
+            .is_ok());
+    }
+
+    #[test]
+    fn checks_hold_notional_until_booked_or_dropped() {
+        let clock = Arc::new(ReplayClock::new(1_800_000_000_000));
+        let risk = risk(clock, dec!(100));
+        let order = buy("eth", "eth-up", dec!(0.50), dec!(120));
+
+        // Two trades in flight can't both take the period's room
+        let first = risk.check(std::slice::from_ref(&order)).unwrap();
+        assert!(matches!(
+            risk.check(&[buy("btc", "btc-down", dec!(0.50), dec!(120))]),
+            Err(RiskRejection::PeriodNotional { .. })
+        ));
+
+        // A partial fill moves its part off the hold; the rest frees on drop
+        first.record_fill(&buy("eth", "eth-up", dec!(0.50), dec!(40)));
+        drop(first);
+        assert!(risk
+            .check(&[buy("btc", "btc-down", dec!(0.50), dec!(160))])
+            .is_ok());
+        assert!(risk
+            .check(&[buy("btc", "btc-down", dec!(0.50), dec!(161))])
+            .is_err());
+    }
+
+    #[test]
+    fn open_positions_count_tokens_not_fills() {
+        let clock = Arc::new(ReplayClock::new(1_800_000_000_000));
+        let risk = risk(clock, dec!(1000));
+
+        // Partial fills of one quote are one position
+        for _ in 0..3 {
+            risk.record_fill(&buy("eth", "eth-up", dec!(0.40), dec!(10)));
+        }
+        assert!(risk
+            .check(&[buy("eth", "eth-up", dec!(0.40), dec!(10))])
+            .is_ok());
+        assert!(matches!(
+            risk.check(&[buy("eth", "eth-down", dec!(0.55), dec!(10))]),
+            Err(RiskRejection::OpenPositions { open: 1, .. })
+        ));
+
+        // Closing the position frees the market
+        risk.record_fill(&OrderIntent {
+            side: Side::Sell,
+            ..buy("eth", "eth-up", dec!(0.45), dec!(30))
+        });
+        assert!(risk
+            .check(&[buy("eth", "eth-down", dec!(0.55), dec!(10))])
+            .is_ok());
+    }
+}
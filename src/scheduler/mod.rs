@@ -0,0 +1,294 @@
+use crate::client::PolymarketClient;
+use crate::domain::*;
+use crate::rewards::RewardParams;
+use crate::ws::WsSubscriber;
+use anyhow::Result;
+use chrono::{DateTime, TimeZone, Utc};
+use log::{info, warn};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::env;
+use std::sync::Arc;
+use tokio::time::{sleep, Duration};
+
+pub const PERIOD_SECS: u64 = 900;
+
+// ===============================
+// TIME HELPERS
+// ===============================
+
+pub fn period_start(ts: u64) -> u64 {
+    (ts / PERIOD_SECS) * PERIOD_SECS
+}
+
+// ===============================
+// PREPARED PERIOD
+// ===============================
+
+/// Per-token trading parameters fetched ahead of the period.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenParams {
+    pub tick_size: Decimal,
+    /// Taker fee rate, signed into every order as `feeRateBps`
+    pub fee_rate_bps: u32,
+    #[serde(default)]
+    pub maker_fee_rate_bps: u32,
+}
+
+/// ETH/BTC market pair for one 15m period, discovered and warmed up.
+#[derive(Debug, Clone)]
+pub struct PreparedPeriod {
+    pub period_start: u64,
+    pub eth_market: Market,
+    pub btc_market: Market,
+    pub eth_tokens: UpDownTokens,
+    pub btc_tokens: UpDownTokens,
+    pub ends_at: DateTime<Utc>,
+    pub token_params: HashMap<String, TokenParams>,
+    /// Condition id → liquidity reward program, for markets that have one
+    pub rewards: HashMap<String, RewardParams>,
+}
+
+impl PreparedPeriod {
+    pub fn token_ids(&self) -> Vec<String> {
+        vec![
+            self.eth_tokens.up.token_id.clone(),
+            self.eth_tokens.down.token_id.clone(),
+            self.btc_tokens.up.token_id.clone(),
+            self.btc_tokens.down.token_id.clone(),
+        ]
+    }
+}
+
+// ===============================
+// SCHEDULER
+// ===============================
+
+/// Discovers the next period's markets before rollover, so the swap at the
+/// boundary costs nothing but a pointer change.
+pub struct PeriodScheduler {
+    api: Arc<PolymarketClient>,
+    ws: Option<WsSubscriber>,
+    preroll: Duration,
+    retry_interval: Duration,
+}
+
+impl PeriodScheduler {
+    pub fn new(api: Arc<PolymarketClient>, ws: Option<WsSubscriber>) -> Self {
+        // Read PREROLL_SECS from env (default: 60)
+        let preroll_secs = env::var("PREROLL_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(60);
+
+        Self {
+            api,
+            ws,
+            preroll: Duration::from_secs(preroll_secs),
+            retry_interval: Duration::from_secs(5),
+        }
+    }
+
+    /// Prepare the pair trading right now (startup / missed pre-roll).
+    pub async fn prepare_current(&self) -> Result<PreparedPeriod> {
+        let now = Utc::now().timestamp().max(0) as u64;
+        let mut seen = HashSet::new();
+
+        let eth = discover_market(&self.api, "ETH", "eth", now, &mut seen).await?;
+        seen.insert(eth.condition_id.clone());
+        let btc = discover_market(&self.api, "BTC", "btc", now, &mut seen).await?;
+
+        // Discovery can fall back to an earlier period that is still open;
+        // label the pair by the period it closes, not by the wall clock
+        let start = eth
+            .end_time()
+            .map(|end| period_start((end.timestamp().max(0) as u64).saturating_sub(PERIOD_SECS)))
+            .unwrap_or_else(|| period_start(now));
+
+        self.warm_up(start, eth, btc).await
+    }
+
+    /// Drop the WS subscriptions of a period that has ended.
+    pub fn release(&self, period: &PreparedPeriod) {
+        if let Some(ws) = &self.ws {
+            ws.unsubscribe(period.token_ids());
+        }
+    }
+
+    /// Retry `prepare_current` until a pair that has not yet ended is found.
+    pub async fn await_current(&self) -> PreparedPeriod {
+        loop {
+            match self.prepare_current().await {
+                Ok(p) if p.ends_at > Utc::now() => return p,
+                Ok(p) => warn!("⚠️  Period {} already ended — retrying", p.period_start),
+                Err(e) => warn!("⚠️  Discovery failed: {} — retrying", e),
+            }
+            sleep(self.retry_interval).await;
+        }
+    }
+
+    /// Sleep until the pre-roll point of `active`, then keep trying to
+    /// discover the following period until it is found or the boundary passes.
+    pub async fn preroll_next(&self, active: &PreparedPeriod) -> Option<PreparedPeriod> {
+        let next_start = active.period_start + PERIOD_SECS;
+        let preroll = chrono::Duration::from_std(self.preroll).unwrap_or_default();
+
+        sleep_until(active.ends_at - preroll).await;
+        info!("⏭️  Pre-rolling period {}", next_start);
+
+        while Utc::now() < active.ends_at {
+            match self.prepare_period(next_start).await {
+                Ok(next) => return Some(next),
+                Err(e) => warn!("⚠️  Pre-roll discovery for {} failed: {}", next_start, e),
+            }
+            sleep(self.retry_interval).await;
+        }
+
+        warn!("⚠️  Pre-roll missed the boundary for period {}", next_start);
+        None
+    }
+
+    /// Discover the pair whose slugs carry exactly `period_start`.
+    pub async fn prepare_period(&self, period_start: u64) -> Result<PreparedPeriod> {
+        let eth = self
+            .api
+            .get_market_by_slug(&format!("eth-updown-15m-{}", period_start))
+            .await?;
+        let btc = self
+            .api
+            .get_market_by_slug(&format!("btc-updown-15m-{}", period_start))
+            .await?;
+
+        self.warm_up(period_start, eth, btc).await
+    }
+
+    async fn warm_up(&self, period_start: u64, eth: Market, btc: Market) -> Result<PreparedPeriod> {
+        let eth_tokens = eth.up_down_tokens()?;
+        let btc_tokens = btc.up_down_tokens()?;
+
+        let ends_at = eth.end_time().unwrap_or_else(|| {
+            warn!(
+                "⚠️  {} has no parseable end_date_iso — using slug period",
+                eth.slug
+            );
+            Utc.timestamp_opt((period_start + PERIOD_SECS) as i64, 0)
+                .single()
+                .unwrap_or_else(Utc::now)
+        });
+
+        let mut period = PreparedPeriod {
+            period_start,
+            eth_market: eth,
+            btc_market: btc,
+            eth_tokens,
+            btc_tokens,
+            ends_at,
+            token_params: HashMap::new(),
+            rewards: HashMap::new(),
+        };
+
+        if let Some(ws) = &self.ws {
+            ws.subscribe(period.token_ids());
+        }
+
+        // Market-level fees back up the per-token fee endpoint
+        let mut details = HashMap::new();
+        for (market, tokens) in [
+            (&period.eth_market, &period.eth_tokens),
+            (&period.btc_market, &period.btc_tokens),
+        ] {
+            match self.api.get_market_details(&market.condition_id).await {
+                Ok(d) => {
+                    if let Some(rewards) = RewardParams::from_rewards(&d.rewards) {
+                        period.rewards.insert(market.condition_id.clone(), rewards);
+                    }
+                    details.insert(tokens.up.token_id.clone(), d.clone());
+                    details.insert(tokens.down.token_id.clone(), d);
+                }
+                Err(e) => warn!("⚠️  Market details for {} unavailable: {}", market.slug, e),
+            }
+        }
+
+        for token_id in period.token_ids() {
+            match self
+                .fetch_token_params(&token_id, details.get(&token_id))
+                .await
+            {
+                Ok(params) => {
+                    period.token_params.insert(token_id, params);
+                }
+                Err(e) => warn!("⚠️  Token params for {} unavailable: {}", token_id, e),
+            }
+        }
+
+        info!(
+            "✅ Period {} ready: {} / {} (ends {})",
+            period.period_start, period.eth_market.slug, period.btc_market.slug, period.ends_at
+        );
+
+        Ok(period)
+    }
+
+    async fn fetch_token_params(
+        &self,
+        token_id: &str,
+        details: Option<&MarketDetails>,
+    ) -> Result<TokenParams> {
+        let fee_rate_bps = match (self.api.get_fee_rate_bps(token_id).await, details) {
+            (Ok(bps), _) => bps,
+            (Err(e), Some(d)) => {
+                warn!(
+                    "⚠️  Fee rate for {} unavailable ({}) — using market taker fee",
+                    token_id, e
+                );
+                d.taker_base_fee.to_u32().unwrap_or(0)
+            }
+            (Err(e), None) => return Err(e),
+        };
+
+        Ok(TokenParams {
+            tick_size: self.api.get_tick_size(token_id).await?,
+            fee_rate_bps,
+            maker_fee_rate_bps: details.and_then(|d| d.maker_base_fee.to_u32()).unwrap_or(0),
+        })
+    }
+}
+
+pub async fn sleep_until(at: DateTime<Utc>) {
+    if let Ok(wait) = (at - Utc::now()).to_std() {
+        sleep(wait).await;
+    }
+}
+
+// ===============================
+// MARKET DISCOVERY
+// ===============================
+
+async fn discover_market(
+    api: &PolymarketClient,
+    name: &str,
+    prefix: &str,
+    now: u64,
+    seen: &mut HashSet<String>,
+) -> Result<Market> {
+    let base = period_start(now);
+
+    for i in 0..=3 {
+        let ts = base - i * PERIOD_SECS;
+        let slug = format!("{}-updown-15m-{}", prefix, ts);
+
+        if let Ok(market) = api.get_market_by_slug(&slug).await {
+            if !seen.contains(&market.condition_id) && market.active {
+                let tokens = market.up_down_tokens()?;
+                info!("Found {} market: {}", name, market.slug);
+                info!("   UP:   {}", tokens.up.token_id);
+                info!("   DOWN: {}", tokens.down.token_id);
+                return Ok(market);
+            }
+        }
+    }
+
+    anyhow::bail!("No active {} market found", name)
+}
@@ -0,0 +1,262 @@
+use crate::clock::{self, Clock};
+use crate::domain::ArbitrageOpportunity;
+use crate::journal::{JournalEvent, TradeJournal};
+use crate::scheduler::period_start;
+use log::info;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::env;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+// ==================================================
+// SUPPRESSION REASONS
+// ==================================================
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Suppressed {
+    /// Same tokens, prices and books as the last admitted entry
+    Duplicate {
+        fingerprint: u64,
+    },
+    Cooldown {
+        remaining_ms: u64,
+    },
+    PeriodCap {
+        entries: u32,
+        limit: u32,
+    },
+}
+
+impl Suppressed {
+    fn kind(&self) -> &'static str {
+        match self {
+            Suppressed::Duplicate { .. } => "duplicate",
+            Suppressed::Cooldown { .. } => "cooldown",
+            Suppressed::PeriodCap { .. } => "period_cap",
+        }
+    }
+}
+
+impl fmt::Display for Suppressed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Suppressed::Duplicate { fingerprint } => {
+                write!(f, "Duplicate opportunity {:016x}", fingerprint)
+            }
+            Suppressed::Cooldown { remaining_ms } => {
+                write!(f, "Pair cooling down for {}ms", remaining_ms)
+            }
+            Suppressed::PeriodCap { entries, limit } => {
+                write!(f, "Period entry cap reached ({}/{})", entries, limit)
+            }
+        }
+    }
+}
+
+// ==================================================
+// FINGERPRINT
+// ==================================================
+
+pub fn pair_key(o: &ArbitrageOpportunity) -> String {
+    format!("{}/{}", o.eth_up_token_id, o.btc_down_token_id)
+}
+
+/// Tokens + quoted prices + exchange book versions. Two snapshots of an
+/// unchanged book hash equal; a book that changed and came back to the
+/// same prices does not.
+pub fn fingerprint(o: &ArbitrageOpportunity) -> u64 {
+    let mut h = DefaultHasher::new();
+    o.eth_up_token_id.hash(&mut h);
+    o.btc_down_token_id.hash(&mut h);
+    o.eth_up_price.normalize().hash(&mut h);
+    o.btc_down_price.normalize().hash(&mut h);
+    o.eth_book.hash(&mut h);
+    o.btc_book.hash(&mut h);
+    h.finish()
+}
+
+// ==================================================
+// FILTER
+// ==================================================
+
+#[derive(Default)]
+struct FilterState {
+    period: u64,
+    entries: u32,
+    /// Fingerprint of the last admitted entry per pair; repeats stay
+    /// suppressed for the period, until the prices or books change
+    last_fingerprint: HashMap<String, u64>,
+    /// Unix ms of the last admitted entry per pair
+    last_entry: HashMap<String, u64>,
+    last_suppression: HashMap<String, &'static str>,
+}
+
+/// Sits between the detector and the trader and drops repeats of an
+/// opportunity that was already acted on.
+pub struct OpportunityFilter {
+    cooldown: Duration,
+    max_entries_per_period: u32,
+    journal: Arc<TradeJournal>,
+    state: Mutex<FilterState>,
+    clock: Arc<dyn Clock>,
+}
+
+impl OpportunityFilter {
+    pub fn new(
+        cooldown: Duration,
+        max_entries_per_period: u32,
+        journal: Arc<TradeJournal>,
+    ) -> Self {
+        Self {
+            cooldown,
+            max_entries_per_period,
+            journal,
+            state: Mutex::new(FilterState::default()),
+            clock: clock::system(),
+        }
+    }
+
+    /// Cooldowns and period caps follow `clock` instead of the wall clock
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn from_env(journal: Arc<TradeJournal>) -> Self {
+        let (cooldown, max_entries) = Self::settings_from_env();
+
+        info!("🧹 Opportunity filter initialized:");
+        info!("   Pair cooldown: {}ms", cooldown.as_millis());
+        info!("   Max entries per period: {}", max_entries);
+
+        Self::new(cooldown, max_entries, journal)
+    }
+
+    /// (pair cooldown, max entries per period) as configured in the env
+    pub fn settings_from_env() -> (Duration, u32) {
+        // Read OPPORTUNITY_COOLDOWN_MS from env (default: 30000)
+        let cooldown_ms = env::var("OPPORTUNITY_COOLDOWN_MS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(30_000);
+
+        // Read MAX_ENTRIES_PER_PERIOD from env (default: 3)
+        let max_entries = env::var("MAX_ENTRIES_PER_PERIOD")
+            .ok()
+            .and_then(|v| v.parse::<u32>().ok())
+            .unwrap_or(3);
+
+        (Duration::from_millis(cooldown_ms), max_entries)
+    }
+
+    /// Admit an opportunity for execution, or say why it was suppressed.
+    ///
+    /// An admitted opportunity counts as an entry immediately, whether or
+    /// not the trade later succeeds — a failing quote must not be hammered.
+    pub fn admit(&self, o: &ArbitrageOpportunity) -> Result<(), Suppressed> {
+        self.admit_at(o, self.clock.now_ms())
+    }
+
+    /// `admit` against an explicit clock (unix ms), for simulated time.
+    pub fn admit_at(&self, o: &ArbitrageOpportunity, now_ms: u64) -> Result<(), Suppressed> {
+        let key = pair_key(o);
+        let fp = fingerprint(o);
+
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+
+        let period = period_start(now_ms / 1000);
+        if period != state.period {
+            state.period = period;
+            state.entries = 0;
+            // Pairs are per period: nothing from the last one can repeat
+            state.last_fingerprint.clear();
+            state.last_entry.clear();
+            state.last_suppression.clear();
+        }
+
+        let verdict = if state.last_fingerprint.get(&key) == Some(&fp) {
+            Err(Suppressed::Duplicate { fingerprint: fp })
+        } else if let Some(remaining) = state.last_entry.get(&key).and_then(|&t| {
+            self.cooldown
+                .checked_sub(Duration::from_millis(now_ms.saturating_sub(t)))
+        }) {
+            Err(Suppressed::Cooldown {
+                remaining_ms: remaining.as_millis() as u64,
+            })
+        } else if state.entries >= self.max_entries_per_period {
+            Err(Suppressed::PeriodCap {
+                entries: state.entries,
+                limit: self.max_entries_per_period,
+            })
+        } else {
+            Ok(())
+        };
+
+        match &verdict {
+            Ok(()) => {
+                state.entries += 1;
+                state.last_fingerprint.insert(key.clone(), fp);
+                state.last_entry.insert(key.clone(), now_ms);
+                state.last_suppression.remove(&key);
+
+                self.journal.record(JournalEvent::OpportunityAdmitted {
+                    pair: key,
+                    fingerprint: format!("{:016x}", fp),
+                    entry: state.entries,
+                    phase: o.phase,
+                });
+            }
+            Err(reason) => {
+                // Journal each suppression streak once, not every tick
+                if state.last_suppression.insert(key.clone(), reason.kind()) != Some(reason.kind())
+                {
+                    self.journal.record(JournalEvent::OpportunitySuppressed {
+                        pair: key,
+                        reason: reason.to_string(),
+                    });
+                }
+            }
+        }
+
+        verdict
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn opportunity(eth_book: &str) -> ArbitrageOpportunity {
+        ArbitrageOpportunity {
+            eth_book: Some(eth_book.into()),
+            btc_book: Some("b@1".into()),
+            ..ArbitrageOpportunity::at(dec!(0.45), dec!(0.50))
+        }
+    }
+
+    #[test]
+    fn unchanged_book_stays_suppressed_after_cooldown() {
+        let filter = OpportunityFilter::new(
+            Duration::from_millis(1_000),
+            10,
+            Arc::new(TradeJournal::discard()),
+        );
+        let start = 1_800_000_000_000;
+
+        assert_eq!(filter.admit_at(&opportunity("a@1"), start), Ok(()));
+
+        // Well past the cooldown, the same book is still the same trade
+        let fp = fingerprint(&opportunity("a@1"));
+        assert_eq!(
+            filter.admit_at(&opportunity("a@1"), start + 5_000),
+            Err(Suppressed::Duplicate { fingerprint: fp })
+        );
+
+        // A new book version is a new opportunity
+        assert_eq!(filter.admit_at(&opportunity("a@2"), start + 5_000), Ok(()));
+    }
+}
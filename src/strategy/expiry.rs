@@ -0,0 +1,70 @@
+use crate::domain::time::TradingPhase;
+use crate::scheduler::PERIOD_SECS;
+use serde::{Deserialize, Serialize};
+use std::env;
+
+/// Phase boundaries of the 15-minute window, in seconds.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExpiryRules {
+    /// No entries this long after the window opens
+    pub opening_secs: i64,
+    /// Entries this close to expiry need `closing_min_profit`
+    pub closing_secs: i64,
+    pub closing_min_profit: f64,
+    /// No entries at all this close to expiry
+    pub no_trade_secs: i64,
+}
+
+impl ExpiryRules {
+    pub fn from_env() -> Self {
+        // Read OPENING_NO_ENTRY_SECS from env (default: 30)
+        let opening_secs = env::var("OPENING_NO_ENTRY_SECS")
+            .ok()
+            .and_then(|v| v.parse::<i64>().ok())
+            .unwrap_or(30);
+
+        // Read CLOSING_PHASE_SECS from env (default: 180)
+        let closing_secs = env::var("CLOSING_PHASE_SECS")
+            .ok()
+            .and_then(|v| v.parse::<i64>().ok())
+            .unwrap_or(180);
+
+        // Read CLOSING_MIN_PROFIT from env (default: 0.03)
+        let closing_min_profit = env::var("CLOSING_MIN_PROFIT")
+            .ok()
+            .and_then(|v| v.parse::<f64>().ok())
+            .unwrap_or(0.03);
+
+        // Read NO_TRADE_SECS from env (default: 30)
+        let no_trade_secs = env::var("NO_TRADE_SECS")
+            .ok()
+            .and_then(|v| v.parse::<i64>().ok())
+            .unwrap_or(30);
+
+        Self {
+            opening_secs,
+            closing_secs,
+            closing_min_profit,
+            no_trade_secs,
+        }
+    }
+
+    /// Phase of a window with `remaining` seconds left. An unknown expiry
+    /// is treated as mid-window.
+    pub fn phase(&self, remaining: Option<i64>) -> TradingPhase {
+        let Some(remaining) = remaining else {
+            return TradingPhase::Open;
+        };
+        let elapsed = PERIOD_SECS as i64 - remaining;
+
+        if remaining <= self.no_trade_secs {
+            TradingPhase::NoTrade
+        } else if elapsed < self.opening_secs {
+            TradingPhase::Opening
+        } else if remaining <= self.closing_secs {
+            TradingPhase::Closing
+        } else {
+            TradingPhase::Open
+        }
+    }
+}
@@ -0,0 +1,70 @@
+use crate::scheduler::TokenParams;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+// ==================================================
+// FEE FORMULA
+// ==================================================
+
+/// USDC-equivalent fee per share at `price`: rate × min(p, 1 − p).
+///
+/// The CTF exchange charges this in outcome tokens on buys and in USDC on
+/// sells; either way it is worth the same per share.
+pub fn fee_per_share(price: Decimal, fee_rate_bps: u32) -> Decimal {
+    Decimal::from(fee_rate_bps) / dec!(10000) * price.min(Decimal::ONE - price).max(Decimal::ZERO)
+}
+
+// ==================================================
+// RATES FOR THE ACTIVE MARKETS
+// ==================================================
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TokenFees {
+    pub taker_bps: u32,
+    pub maker_bps: u32,
+}
+
+/// Per-token fee rates, shared by the detector (edge after fees) and the
+/// trader (the `feeRateBps` it signs). Loaded from each period's params.
+#[derive(Clone, Default)]
+pub struct FeeRates {
+    inner: Arc<RwLock<HashMap<String, TokenFees>>>,
+}
+
+impl FeeRates {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn load(&self, params: &HashMap<String, TokenParams>) {
+        let mut map = self.inner.write().unwrap_or_else(|e| e.into_inner());
+        for (token_id, p) in params {
+            map.insert(
+                token_id.clone(),
+                TokenFees {
+                    taker_bps: p.fee_rate_bps,
+                    maker_bps: p.maker_fee_rate_bps,
+                },
+            );
+        }
+    }
+
+    pub fn get(&self, token_id: &str) -> Option<TokenFees> {
+        self.inner
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(token_id)
+            .copied()
+    }
+
+    /// Taker rate, 0 for a token whose params were never fetched
+    pub fn taker_bps(&self, token_id: &str) -> u32 {
+        self.get(token_id).map(|f| f.taker_bps).unwrap_or(0)
+    }
+
+    pub fn maker_bps(&self, token_id: &str) -> u32 {
+        self.get(token_id).map(|f| f.maker_bps).unwrap_or(0)
+    }
+}
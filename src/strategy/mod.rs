@@ -1,23 +1,46 @@
+pub mod dedup;
+pub mod expiry;
+pub mod fees;
+
+use crate::clock::{self, Clock};
+use crate::domain::time::{seconds_remaining, TradingPhase};
 use crate::domain::*;
 use crate::monitor::MarketSnapshot;
+use crate::pricing::joint::{JointModel, JointOutcomes, PairPayout};
+use crate::pricing::{FairValue, PricingModel};
+use crate::scheduler::PERIOD_SECS;
+use crate::strategy::expiry::ExpiryRules;
+use crate::strategy::fees::{fee_per_share, FeeRates};
+use chrono::{DateTime, Utc};
 use log::info;
 use rust_decimal::prelude::FromPrimitive;
 use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
 use std::env;
+use std::sync::Arc;
 
-#[derive(Clone)]
-pub struct ArbitrageDetector {
-    min_profit_threshold: Decimal,
-    max_sum_threshold: Decimal,
-    min_reasonable_price: Decimal,
-    max_reasonable_price: Decimal,
-    min_total_cost: Decimal,
+/// Detector thresholds. Kept separate from the detector so backtests can
+/// build one per parameter set without touching the environment.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DetectorParams {
+    pub min_profit_threshold: f64,
+    pub max_sum: f64,
+    pub min_reasonable_price: f64,
+    pub max_reasonable_price: f64,
+    pub min_total_cost: f64,
+    /// Oldest quote a leg may act on
+    pub max_quote_age_ms: u64,
+    /// Furthest apart the two legs may have been read
+    pub max_leg_skew_ms: u64,
+    /// Edge given up per unit of payout variance
+    pub risk_aversion: f64,
+    pub expiry: ExpiryRules,
 }
 
-impl ArbitrageDetector {
-    pub fn new(min_profit_threshold: f64) -> Self {
+impl DetectorParams {
+    pub fn from_env(min_profit_threshold: f64) -> Self {
         // Read ARBITRAGE_MAX_SUM from env (default: 0.99)
         let max_sum = env::var("ARBITRAGE_MAX_SUM")
             .ok()
@@ -42,64 +65,209 @@ impl ArbitrageDetector {
             .and_then(|v| v.parse::<f64>().ok())
             .unwrap_or(0.50);
 
+        // Read MAX_QUOTE_AGE_MS from env (default: 3000)
+        let max_quote_age_ms = env::var("MAX_QUOTE_AGE_MS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(3000);
+
+        // Read MAX_LEG_SKEW_MS from env (default: 1500)
+        let max_leg_skew_ms = env::var("MAX_LEG_SKEW_MS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(1500);
+
+        // Read PAIR_RISK_AVERSION from env (default: 0.5)
+        let risk_aversion = env::var("PAIR_RISK_AVERSION")
+            .ok()
+            .and_then(|v| v.parse::<f64>().ok())
+            .unwrap_or(0.5);
+
+        Self {
+            min_profit_threshold,
+            max_sum,
+            min_reasonable_price: min_reasonable,
+            max_reasonable_price: max_reasonable,
+            min_total_cost: min_total,
+            max_quote_age_ms,
+            max_leg_skew_ms,
+            risk_aversion,
+            expiry: ExpiryRules::from_env(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct ArbitrageDetector {
+    min_profit_threshold: Decimal,
+    max_sum_threshold: Decimal,
+    min_reasonable_price: Decimal,
+    max_reasonable_price: Decimal,
+    min_total_cost: Decimal,
+    closing_min_profit: Decimal,
+    max_quote_age_ms: u64,
+    max_leg_skew_ms: u64,
+    risk_aversion: Decimal,
+    expiry: ExpiryRules,
+    fees: FeeRates,
+    pricing: Option<PricingModel>,
+    joint: Option<JointModel>,
+    clock: Arc<dyn Clock>,
+}
+
+impl ArbitrageDetector {
+    pub fn new(min_profit_threshold: f64) -> Self {
+        let params = DetectorParams::from_env(min_profit_threshold);
+
         info!("🎯 Arbitrage Detector Initialized:");
         info!(
             "   Min profit threshold: {:.2}%",
-            min_profit_threshold * 100.0
+            params.min_profit_threshold * 100.0
         );
-        info!("   Max sum threshold: ${:.4}", max_sum);
-        info!("   Min reasonable price: ${:.4}", min_reasonable);
-        info!("   Max reasonable price: ${:.4}", max_reasonable);
-        info!("   Min total cost: ${:.4}", min_total);
+        info!("   Max sum threshold: ${:.4}", params.max_sum);
+        info!(
+            "   Min reasonable price: ${:.4}",
+            params.min_reasonable_price
+        );
+        info!(
+            "   Max reasonable price: ${:.4}",
+            params.max_reasonable_price
+        );
+        info!("   Min total cost: ${:.4}", params.min_total_cost);
+        info!(
+            "   Max quote age: {}ms, max leg skew: {}ms",
+            params.max_quote_age_ms, params.max_leg_skew_ms
+        );
+        info!("   Pair risk aversion: {}", params.risk_aversion);
+        info!(
+            "   Phases: no entries first {}s / last {}s, min profit {:.2}% in last {}s",
+            params.expiry.opening_secs,
+            params.expiry.no_trade_secs,
+            params.expiry.closing_min_profit * 100.0,
+            params.expiry.closing_secs
+        );
+
+        Self::with_params(&params)
+    }
 
+    pub fn with_params(params: &DetectorParams) -> Self {
         Self {
-            min_profit_threshold: Decimal::from_f64(min_profit_threshold).unwrap_or(dec!(0.01)),
-            max_sum_threshold: Decimal::from_f64(max_sum).unwrap_or(dec!(0.99)),
-            min_reasonable_price: Decimal::from_f64(min_reasonable).unwrap_or(dec!(0.15)),
-            max_reasonable_price: Decimal::from_f64(max_reasonable).unwrap_or(dec!(0.95)),
-            min_total_cost: Decimal::from_f64(min_total).unwrap_or(dec!(0.50)),
+            min_profit_threshold: Decimal::from_f64(params.min_profit_threshold)
+                .unwrap_or(dec!(0.01)),
+            max_sum_threshold: Decimal::from_f64(params.max_sum).unwrap_or(dec!(0.99)),
+            min_reasonable_price: Decimal::from_f64(params.min_reasonable_price)
+                .unwrap_or(dec!(0.15)),
+            max_reasonable_price: Decimal::from_f64(params.max_reasonable_price)
+                .unwrap_or(dec!(0.95)),
+            min_total_cost: Decimal::from_f64(params.min_total_cost).unwrap_or(dec!(0.50)),
+            closing_min_profit: Decimal::from_f64(params.expiry.closing_min_profit)
+                .unwrap_or(dec!(0.03)),
+            max_quote_age_ms: params.max_quote_age_ms,
+            max_leg_skew_ms: params.max_leg_skew_ms,
+            risk_aversion: Decimal::from_f64(params.risk_aversion).unwrap_or(dec!(0.5)),
+            expiry: params.expiry.clone(),
+            fees: FeeRates::new(),
+            pricing: None,
+            joint: None,
+            clock: clock::system(),
         }
     }
 
+    /// Net taker fees out of every edge using these rates
+    pub fn with_fees(mut self, fees: FeeRates) -> Self {
+        self.fees = fees;
+        self
+    }
+
+    /// Price every snapshot against spot fair value and flag mispricings
+    pub fn with_pricing(mut self, pricing: PricingModel) -> Self {
+        self.pricing = Some(pricing);
+        self
+    }
+
+    /// Gate on the pair's expected payout under the historical ETH/BTC
+    /// joint distribution, less a variance penalty, instead of 1 − cost
+    pub fn with_joint(mut self, joint: JointModel) -> Self {
+        self.joint = Some(joint);
+        self
+    }
+
+    /// Measure time to expiry on `clock` instead of the wall clock
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
     /// Core strategy:
     /// 1) ETH UP  + BTC DOWN
     /// 2) ETH DOWN + BTC UP
     ///
     /// Execute ONLY when total cost < max_sum_threshold and profit >= min_profit_threshold
     /// Apply safety filters to prevent rug/fake pricing
+    /// Nothing is entered while the window is opening or about to close
     pub fn detect_opportunities(&self, snapshot: &MarketSnapshot) -> Vec<ArbitrageOpportunity> {
         let mut opportunities = Vec::new();
 
-        let eth_up = snapshot.eth_market.up_token.as_ref();
-        let eth_down = snapshot.eth_market.down_token.as_ref();
-        let btc_up = snapshot.btc_market.up_token.as_ref();
-        let btc_down = snapshot.btc_market.down_token.as_ref();
-
         // ===============================
-        // PAIR 1: ETH UP + BTC DOWN
+        // TIME TO EXPIRY: the earlier of the two markets decides
         // ===============================
-        if let (Some(eth), Some(btc)) = (eth_up, btc_down) {
-            if let Some(o) = self.check_pair(
-                eth,
-                btc,
-                &snapshot.eth_market.condition_id,
-                &snapshot.btc_market.condition_id,
-            ) {
-                opportunities.push(o);
-            }
+        let ends_at = [snapshot.eth_market.end_time, snapshot.btc_market.end_time]
+            .into_iter()
+            .flatten()
+            .min();
+        let remaining = ends_at.map(|t| seconds_remaining(t, self.clock.now()));
+        let phase = self.expiry.phase(remaining);
+
+        if !phase.allows_entry() {
+            info!(
+                "⏳ {} phase ({}s to expiry) — no entries",
+                phase,
+                remaining.unwrap_or_default()
+            );
+            return opportunities;
         }
 
         // ===============================
+        // FAIR VALUE: only with a spot feed
+        // ===============================
+        let eth_fair = self.fair_value(&snapshot.eth_market);
+        let btc_fair = self.fair_value(&snapshot.btc_market);
+        self.flag_mispricings(&snapshot.eth_market, eth_fair);
+        self.flag_mispricings(&snapshot.btc_market, btc_fair);
+
+        let eth = &snapshot.eth_market;
+        let btc = &snapshot.btc_market;
+
+        // ===============================
+        // PAIR 1: ETH UP + BTC DOWN
         // PAIR 2: ETH DOWN + BTC UP
         // ===============================
-        if let (Some(eth), Some(btc)) = (eth_down, btc_up) {
-            if let Some(o) = self.check_pair(
-                eth,
-                btc,
-                &snapshot.eth_market.condition_id,
-                &snapshot.btc_market.condition_id,
-            ) {
-                opportunities.push(o);
+        for (eth_side, btc_side) in [(Outcome::Up, Outcome::Down), (Outcome::Down, Outcome::Up)] {
+            let eth_token = match eth_side {
+                Outcome::Up => eth.up_token.as_ref(),
+                Outcome::Down => eth.down_token.as_ref(),
+            };
+            let btc_token = match btc_side {
+                Outcome::Up => btc.up_token.as_ref(),
+                Outcome::Down => btc.down_token.as_ref(),
+            };
+
+            let ctx = PairContext {
+                eth_condition_id: &eth.condition_id,
+                btc_condition_id: &btc.condition_id,
+                eth_side,
+                btc_side,
+                phase,
+                ends_at,
+                eth_fair,
+                btc_fair,
+                observed_at: snapshot.timestamp,
+            };
+
+            if let (Some(a), Some(b)) = (eth_token, btc_token) {
+                if let Some(o) = self.check_pair(a, b, &ctx) {
+                    opportunities.push(o);
+                }
             }
         }
 
@@ -125,8 +293,7 @@ impl ArbitrageDetector {
         &self,
         token_a: &TokenPrice,
         token_b: &TokenPrice,
-        eth_condition_id: &str,
-        btc_condition_id: &str,
+        ctx: &PairContext,
     ) -> Option<ArbitrageOpportunity> {
         // BUY prices (what we pay)
         let price_a = token_a.ask?;
@@ -141,6 +308,16 @@ impl ArbitrageDetector {
 
         let total_cost = price_a + price_b;
 
+        // ===============================
+        // SAFETY FILTER #0: Stale or half-stale quotes
+        // User configurable via MAX_QUOTE_AGE_MS / MAX_LEG_SKEW_MS
+        // Quotes without an observation time are not checked
+        // ===============================
+        if let Err(reason) = self.check_freshness(token_a, token_b) {
+            info!("   ❌ Rejected: {}", reason);
+            return None;
+        }
+
         // ===============================
         // SAFETY FILTER #1: Both prices too low (rug pricing)
         // User configurable via MIN_REASONABLE_PRICE
@@ -192,16 +369,46 @@ impl ArbitrageDetector {
         // ===============================
         // PROFIT CHECK: Expected profit vs minimum threshold
         // User configurable via MIN_PROFIT_THRESHOLD
+        // Net of the taker fee on both legs
+        // Raised to CLOSING_MIN_PROFIT near expiry
+        // With a joint model the pair pays 0, 1 or 2, not always 1:
+        // gate on its expected payout less PAIR_RISK_AVERSION × variance
         // ===============================
-        let expected_profit = dec!(1.0) - total_cost;
+        let fees = fee_per_share(price_a, self.fees.taker_bps(&token_a.token_id))
+            + fee_per_share(price_b, self.fees.taker_bps(&token_b.token_id));
+
+        // The pair pays 0, 1 or 2: without a model of how often, there is
+        // no expected payout to gate on
+        let Some(payout) = self.pair_payout(ctx) else {
+            info!("   ❌ Rejected: no payout model yet (no fair values, joint history too short)");
+            return None;
+        };
+        let expected_payout = Decimal::from_f64(payout.ev)?;
+        let payout_variance = Decimal::from_f64(payout.variance).map(|v| v.round_dp(6));
+        let expected_profit = expected_payout - total_cost - fees;
+        let risk_adjusted =
+            expected_profit - self.risk_aversion * payout_variance.unwrap_or_default();
 
-        if expected_profit < self.min_profit_threshold {
+        let min_profit = match ctx.phase {
+            TradingPhase::Closing => self.min_profit_threshold.max(self.closing_min_profit),
+            _ => self.min_profit_threshold,
+        };
+
+        info!(
+            "   🔗 Pair payout EV {:.4}, variance {:.4}, P(both lose) {:.2}%",
+            payout.ev,
+            payout.variance,
+            payout.odds.neither * 100.0
+        );
+
+        if risk_adjusted < min_profit {
             info!(
-                "   ❌ Rejected: Expected profit ${:.4} ({:.2}%) < threshold ${:.4} ({:.2}%)",
-                expected_profit,
-                expected_profit.to_f64().unwrap() * 100.0,
-                self.min_profit_threshold,
-                self.min_profit_threshold.to_f64().unwrap() * 100.0
+                "   ❌ Rejected: Risk-adjusted profit ${:.4} ({:.2}%) < {} threshold ${:.4} ({:.2}%)",
+                risk_adjusted,
+                risk_adjusted.to_f64().unwrap() * 100.0,
+                ctx.phase,
+                min_profit,
+                min_profit.to_f64().unwrap() * 100.0
             );
             return None;
         }
@@ -213,15 +420,27 @@ impl ArbitrageDetector {
         info!("      Price A: ${:.4}", price_a);
         info!("      Price B: ${:.4}", price_b);
         info!("      Total Cost: ${:.4}", total_cost);
+        info!("      Fees: ${:.4}", fees);
         info!(
             "      Expected Profit: ${:.4} ({:.2}%)",
             expected_profit,
             expected_profit.to_f64().unwrap() * 100.0
         );
+        info!("      Risk-adjusted: ${:.4}", risk_adjusted);
+
+        // Model payout when both legs have a spot fair value
+        let fair_value = match (ctx.eth_fair, ctx.btc_fair) {
+            (Some(e), Some(b)) => {
+                let fair = side_probability(&e, ctx.eth_side) + side_probability(&b, ctx.btc_side);
+                info!("      📐 Fair payout ${:.4}", fair);
+                Decimal::from_f64(fair).map(|v| v.round_dp(4))
+            }
+            _ => None,
+        };
 
         Some(ArbitrageOpportunity {
-            eth_condition_id: eth_condition_id.to_string(),
-            btc_condition_id: btc_condition_id.to_string(),
+            eth_condition_id: ctx.eth_condition_id.to_string(),
+            btc_condition_id: ctx.btc_condition_id.to_string(),
 
             // these are the two tokens we BUY
             eth_up_token_id: token_a.token_id.clone(),
@@ -231,7 +450,125 @@ impl ArbitrageDetector {
             btc_down_price: price_b,
 
             total_cost,
+            fees,
             expected_profit,
+
+            phase: ctx.phase,
+            ends_at: ctx.ends_at,
+            fair_value,
+            payout_variance,
+            payout_odds: Some(payout.odds),
+            eth_depth: token_a.ask_size,
+            btc_depth: token_b.ask_size,
+            observed_at: Some(ctx.observed_at),
+            eth_book: token_a.book_version(),
+            btc_book: token_b.book_version(),
         })
     }
+
+    /// Payout distribution of the pair: spot fair values as marginals when
+    /// there are both (independent until the history can say otherwise),
+    /// the history's joint when there are not, None when there is neither.
+    fn pair_payout(&self, ctx: &PairContext) -> Option<PairPayout> {
+        let history = self.joint.as_ref().and_then(|j| j.estimate());
+
+        let joint = match (ctx.eth_fair, ctx.btc_fair) {
+            (Some(e), Some(b)) => {
+                let phi = history.map_or(0.0, |h| h.phi());
+                JointOutcomes::from_marginals(e.up, b.up, phi)
+            }
+            _ => history?,
+        };
+
+        Some(joint.pair(ctx.eth_side, ctx.btc_side))
+    }
+
+    fn check_freshness(&self, token_a: &TokenPrice, token_b: &TokenPrice) -> Result<(), String> {
+        let now_ms = self.clock.now_ms();
+
+        for token in [token_a, token_b] {
+            if let Some(age) = token.age_ms(now_ms).filter(|&a| a > self.max_quote_age_ms) {
+                return Err(format!(
+                    "Quote for {} is {}ms old (max {}ms, book {})",
+                    token.token_id,
+                    age,
+                    self.max_quote_age_ms,
+                    token.book_hash.as_deref().unwrap_or("?")
+                ));
+            }
+        }
+
+        if let (Some(a), Some(b)) = (token_a.observed_at_ms, token_b.observed_at_ms) {
+            let skew = a.abs_diff(b);
+            if skew > self.max_leg_skew_ms {
+                return Err(format!(
+                    "Legs read {}ms apart (max {}ms)",
+                    skew, self.max_leg_skew_ms
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Model fair value of a market right now, if its window is known
+    fn fair_value(&self, market: &MarketData) -> Option<FairValue> {
+        let pricing = self.pricing.as_ref()?;
+        let end_ms = market.end_time?.timestamp_millis().max(0) as u64;
+        let start_ms = end_ms.saturating_sub(PERIOD_SECS * 1000);
+
+        pricing.fair_value(&market.market_name, start_ms, end_ms, self.clock.now_ms())
+    }
+
+    /// Log tokens whose ask sits below, or bid above, fair value by at
+    /// least MISPRICING_MIN_EDGE
+    fn flag_mispricings(&self, market: &MarketData, fair: Option<FairValue>) {
+        let (Some(pricing), Some(fair)) = (&self.pricing, fair) else {
+            return;
+        };
+        let min_edge = pricing.params().min_edge;
+
+        for (side, token, p) in [
+            ("UP", &market.up_token, fair.up),
+            ("DOWN", &market.down_token, fair.down),
+        ] {
+            let Some(token) = token else {
+                continue;
+            };
+            let ask = token.ask.and_then(|a| a.to_f64());
+            let bid = token.bid.and_then(|b| b.to_f64());
+
+            if let Some(ask) = ask.filter(|&a| p - a >= min_edge) {
+                info!(
+                    "📐 {} {} underpriced: ask {:.4} < fair {:.4} (spot {:.2} vs open {:.2}, σ {:.0}%, {:.0}s left)",
+                    market.market_name, side, ask, p, fair.spot, fair.strike, fair.sigma * 100.0, fair.tau_secs
+                );
+            } else if let Some(bid) = bid.filter(|&b| b - p >= min_edge) {
+                info!(
+                    "📐 {} {} overpriced: bid {:.4} > fair {:.4} (spot {:.2} vs open {:.2}, σ {:.0}%, {:.0}s left)",
+                    market.market_name, side, bid, p, fair.spot, fair.strike, fair.sigma * 100.0, fair.tau_secs
+                );
+            }
+        }
+    }
+}
+
+/// What `check_pair` knows about a pair besides its two quotes
+struct PairContext<'a> {
+    eth_condition_id: &'a str,
+    btc_condition_id: &'a str,
+    eth_side: Outcome,
+    btc_side: Outcome,
+    phase: TradingPhase,
+    ends_at: Option<DateTime<Utc>>,
+    eth_fair: Option<FairValue>,
+    btc_fair: Option<FairValue>,
+    observed_at: std::time::Instant,
+}
+
+fn side_probability(fair: &FairValue, side: Outcome) -> f64 {
+    match side {
+        Outcome::Up => fair.up,
+        Outcome::Down => fair.down,
+    }
 }
@@ -1,8 +1,15 @@
 use anyhow::{anyhow, Result};
+use ethers::abi::{self, ParamType, Token};
 use ethers::prelude::*;
+use ethers::utils::keccak256;
+use log::{info, warn};
 use rust_decimal::prelude::FromPrimitive;
 use rust_decimal::Decimal;
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::env;
+use std::sync::{Arc, RwLock};
+use tokio::time::{sleep, Duration, Instant};
+use tokio_util::sync::CancellationToken;
 
 // ================================
 // USDC ABI (balanceOf only)
@@ -38,17 +45,416 @@ pub async fn get_usdc_balance(rpc_url: &str, usdc_contract: &str, wallet: &str)
 }
 
 // ================================
-// OPTIONAL: STATEFUL TRACKER
+// EVENT-DRIVEN TRACKER
 // ================================
+
+const USDC_ADDRESS: &str = "0x2791Bca1f2de4661ED88A30C99A7a9449Aa84174";
+const CTF_ADDRESS: &str = "0x4D97DCd97eC945f40cF65F87097ACe5EA0476045";
+
+const TRANSFER: &str = "Transfer(address,address,uint256)";
+const TRANSFER_SINGLE: &str = "TransferSingle(address,address,address,uint256,uint256)";
+const TRANSFER_BATCH: &str = "TransferBatch(address,address,address,uint256[],uint256[])";
+
+/// Most blocks fetched per log query
+const MAX_BLOCK_RANGE: u64 = 2_000;
+
+abigen!(
+    CtfBalance,
+    r#"[
+        function balanceOf(address owner, uint256 id) view returns (uint256)
+    ]"#
+);
+
+#[derive(Default)]
+struct Balances {
+    /// Raw USDC (6 decimals)
+    usdc: U256,
+    /// CTF position id → raw shares (6 decimals)
+    tokens: HashMap<U256, U256>,
+    /// Last block whose logs are applied
+    block: u64,
+}
+
+/// Live USDC and outcome-token balances of the proxy wallet, kept from
+/// USDC `Transfer` and CTF `TransferSingle` / `TransferBatch` logs so
+/// reading them never costs an RPC round trip.
+///
+/// Seeded with `balanceOf` at a block, then moved forward by polling logs
+/// from there — over HTTP, or any other `Middleware` such as a WS
+/// provider. A periodic re-seed corrects drift from missed or reorged
+/// logs. Clones share the same balances.
 #[derive(Clone)]
-pub struct BalanceTracker {
-    pub last_balance: Decimal,
+pub struct BalanceTracker<M = Provider<Http>> {
+    provider: Arc<M>,
+    wallet: Address,
+    usdc: Address,
+    ctf: Address,
+    state: Arc<RwLock<Balances>>,
+    poll_interval: Duration,
+    resync_interval: Duration,
+}
+
+impl BalanceTracker<Provider<Http>> {
+    /// HTTP tracker on RPC_URL for PROXY_WALLET
+    pub fn from_env() -> Result<Self> {
+        let rpc_url = env::var("RPC_URL").map_err(|_| anyhow!("RPC_URL not set"))?;
+        let wallet = env::var("PROXY_WALLET").map_err(|_| anyhow!("PROXY_WALLET not set"))?;
+        let provider = Provider::<Http>::try_from(rpc_url.as_str())
+            .map_err(|e| anyhow!("RPC error: {}", e))?;
+
+        // Read BALANCE_POLL_MS from env (default: 2000)
+        let poll_ms = env::var("BALANCE_POLL_MS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(2000);
+
+        // Read BALANCE_RESYNC_SECS from env (default: 300)
+        let resync_secs = env::var("BALANCE_RESYNC_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(300);
+
+        Ok(
+            Self::new(Arc::new(provider), wallet.parse()?).with_intervals(
+                Duration::from_millis(poll_ms),
+                Duration::from_secs(resync_secs),
+            ),
+        )
+    }
 }
 
-impl BalanceTracker {
-    pub fn new() -> Self {
+impl<M: Middleware + 'static> BalanceTracker<M> {
+    pub fn new(provider: Arc<M>, wallet: Address) -> Self {
         Self {
-            last_balance: Decimal::ZERO,
+            provider,
+            wallet,
+            usdc: USDC_ADDRESS.parse().expect("valid USDC address"),
+            ctf: CTF_ADDRESS.parse().expect("valid CTF address"),
+            state: Arc::new(RwLock::new(Balances::default())),
+            poll_interval: Duration::from_secs(2),
+            resync_interval: Duration::from_secs(300),
+        }
+    }
+
+    pub fn with_intervals(mut self, poll: Duration, resync: Duration) -> Self {
+        self.poll_interval = poll;
+        self.resync_interval = resync;
+        self
+    }
+
+    // ================================
+    // READS (NO RPC)
+    // ================================
+
+    pub fn usdc(&self) -> Decimal {
+        to_decimal(self.read().usdc)
+    }
+
+    /// Shares of an outcome token, once it has been seen or seeded
+    pub fn token_balance(&self, token_id: &str) -> Option<Decimal> {
+        let id = U256::from_dec_str(token_id).ok()?;
+        self.read().tokens.get(&id).copied().map(to_decimal)
+    }
+
+    /// Last block applied; 0 before the first seed
+    pub fn block(&self) -> u64 {
+        self.read().block
+    }
+
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, Balances> {
+        self.state.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn write(&self) -> std::sync::RwLockWriteGuard<'_, Balances> {
+        self.state.write().unwrap_or_else(|e| e.into_inner())
+    }
+
+    // ================================
+    // SEEDING
+    // ================================
+
+    /// Read USDC and every known token with `balanceOf` at the head block,
+    /// and follow logs from there.
+    pub async fn seed(&self) -> Result<()> {
+        let head = self.head().await?;
+        let at = BlockId::Number(head.into());
+
+        let usdc = UsdcContract::new(self.usdc, self.provider.clone())
+            .balance_of(self.wallet)
+            .block(at)
+            .call()
+            .await?;
+
+        let ids: Vec<U256> = self.read().tokens.keys().copied().collect();
+        let ctf = CtfBalance::new(self.ctf, self.provider.clone());
+        let mut tokens = HashMap::new();
+        for id in ids {
+            tokens.insert(id, ctf.balance_of(self.wallet, id).block(at).call().await?);
+        }
+
+        let mut state = self.write();
+        state.usdc = usdc;
+        state.tokens.extend(tokens);
+        state.block = head;
+        Ok(())
+    }
+
+    /// Start following `token_id`, seeding its current balance.
+    pub async fn track_token(&self, token_id: &str) -> Result<()> {
+        let id = U256::from_dec_str(token_id).map_err(|e| anyhow!("{}: {}", token_id, e))?;
+        if self.read().tokens.contains_key(&id) {
+            return Ok(());
+        }
+
+        // Seed at the block already applied so later logs aren't counted twice
+        let block = self.block();
+        if block == 0 {
+            return Err(anyhow!("Balance tracker not seeded"));
+        }
+        let balance = CtfBalance::new(self.ctf, self.provider.clone())
+            .balance_of(self.wallet, id)
+            .block(BlockId::Number(block.into()))
+            .call()
+            .await?;
+
+        self.write().tokens.entry(id).or_insert(balance);
+        Ok(())
+    }
+
+    async fn head(&self) -> Result<u64> {
+        Ok(self
+            .provider
+            .get_block_number()
+            .await
+            .map_err(|e| anyhow!("block number: {}", e))?
+            .as_u64())
+    }
+
+    // ================================
+    // LOG POLLING
+    // ================================
+
+    /// Apply the logs of the blocks since the last poll. Returns how many
+    /// transfers touched the wallet.
+    pub async fn poll(&self) -> Result<usize> {
+        if self.block() == 0 {
+            return Err(anyhow!("Balance tracker not seeded"));
+        }
+        let from = self.block() + 1;
+        let head = self.head().await?;
+        if head < from {
+            return Ok(0);
+        }
+        let to = head.min(from + MAX_BLOCK_RANGE - 1);
+
+        let usdc = Filter::new()
+            .address(self.usdc)
+            .event(TRANSFER)
+            .from_block(from)
+            .to_block(to);
+        let ctf = Filter::new()
+            .address(self.ctf)
+            .events([TRANSFER_SINGLE, TRANSFER_BATCH])
+            .from_block(from)
+            .to_block(to);
+
+        // A log can't match two positions at once, so out and in are
+        // separate queries; a self-transfer shows up in both and nets out
+        let usdc_out = self.logs(usdc.clone().topic1(self.wallet)).await?;
+        let usdc_in = self.logs(usdc.topic2(self.wallet)).await?;
+        let ctf_out = self.logs(ctf.clone().topic2(self.wallet)).await?;
+        let ctf_in = self.logs(ctf.topic3(self.wallet)).await?;
+
+        let count = usdc_out.len() + usdc_in.len() + ctf_out.len() + ctf_in.len();
+
+        let mut state = self.write();
+        if state.block + 1 != from {
+            // Re-seeded while we were fetching; these logs are already in
+            return Ok(0);
+        }
+        apply_logs(&mut state, &usdc_out, &usdc_in, &ctf_out, &ctf_in);
+        state.block = to;
+
+        Ok(count)
+    }
+
+    async fn logs(&self, filter: Filter) -> Result<Vec<Log>> {
+        self.provider
+            .get_logs(&filter)
+            .await
+            .map_err(|e| anyhow!("get_logs: {}", e))
+    }
+
+    /// Seed, then poll until `cancel` fires, re-seeding every resync interval.
+    pub async fn run(self, cancel: CancellationToken) {
+        if let Err(e) = self.seed().await {
+            warn!("⚠️  Balance tracker seed failed: {}", e);
+        }
+        info!(
+            "👛 Tracking {:?} from block {} — USDC {}",
+            self.wallet,
+            self.block(),
+            self.usdc()
+        );
+
+        let mut last_seed = Instant::now();
+        loop {
+            tokio::select! {
+                biased;
+                _ = cancel.cancelled() => break,
+                _ = sleep(self.poll_interval) => {}
+            }
+
+            if self.block() == 0 || last_seed.elapsed() >= self.resync_interval {
+                match self.seed().await {
+                    Ok(()) => last_seed = Instant::now(),
+                    Err(e) => warn!("⚠️  Balance re-seed failed: {}", e),
+                }
+                continue;
+            }
+
+            match self.poll().await {
+                Ok(0) => {}
+                Ok(n) => info!("👛 {} transfer(s) — USDC {}", n, self.usdc()),
+                Err(e) => warn!("⚠️  Balance log poll failed: {}", e),
+            }
         }
     }
 }
+
+fn word(data: &[u8], i: usize) -> U256 {
+    data.get(i * 32..(i + 1) * 32)
+        .map(U256::from_big_endian)
+        .unwrap_or_default()
+}
+
+/// Apply a poll window's transfers in chain order, so a receive and the
+/// send that spends it clip at zero no sooner than the chain did.
+fn apply_logs(
+    state: &mut Balances,
+    usdc_out: &[Log],
+    usdc_in: &[Log],
+    ctf_out: &[Log],
+    ctf_in: &[Log],
+) {
+    // (log, is CTF, incoming); a self-transfer credits before it debits
+    let mut transfers: Vec<(&Log, bool, bool)> = Vec::new();
+    transfers.extend(usdc_in.iter().map(|l| (l, false, true)));
+    transfers.extend(usdc_out.iter().map(|l| (l, false, false)));
+    transfers.extend(ctf_in.iter().map(|l| (l, true, true)));
+    transfers.extend(ctf_out.iter().map(|l| (l, true, false)));
+    transfers.sort_by_key(|(l, _, _)| (l.block_number, l.log_index));
+
+    for (log, ctf, incoming) in transfers {
+        if ctf {
+            apply_ctf(state, log, incoming);
+        } else {
+            apply_usdc(state, log, incoming);
+        }
+    }
+}
+
+fn apply_usdc(state: &mut Balances, log: &Log, incoming: bool) {
+    let value = word(&log.data, 0);
+    state.usdc = if incoming {
+        state.usdc.saturating_add(value)
+    } else {
+        state.usdc.saturating_sub(value)
+    };
+}
+
+fn apply_ctf(state: &mut Balances, log: &Log, incoming: bool) {
+    let single = H256::from(keccak256(TRANSFER_SINGLE));
+
+    let moves: Vec<(U256, U256)> = if log.topics.first() == Some(&single) {
+        vec![(word(&log.data, 0), word(&log.data, 1))]
+    } else {
+        let array = || ParamType::Array(Box::new(ParamType::Uint(256)));
+        match abi::decode(&[array(), array()], &log.data) {
+            Ok(tokens) => {
+                let uints = |t: &Token| -> Vec<U256> {
+                    t.clone()
+                        .into_array()
+                        .unwrap_or_default()
+                        .into_iter()
+                        .filter_map(Token::into_uint)
+                        .collect()
+                };
+                uints(&tokens[0])
+                    .into_iter()
+                    .zip(uints(&tokens[1]))
+                    .collect()
+            }
+            Err(e) => {
+                warn!(
+                    "⚠️  Undecodable TransferBatch in {:?}: {}",
+                    log.transaction_hash, e
+                );
+                return;
+            }
+        }
+    };
+
+    for (id, value) in moves {
+        let balance = state.tokens.entry(id).or_default();
+        *balance = if incoming {
+            balance.saturating_add(value)
+        } else {
+            balance.saturating_sub(value)
+        };
+    }
+}
+
+/// Raw 6-decimal amount as a Decimal
+fn to_decimal(raw: U256) -> Decimal {
+    Decimal::from_u128(raw.low_u128())
+        .map(|d| d / Decimal::from(1_000_000u64))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log(block: u64, index: u64, data: Vec<u8>) -> Log {
+        Log {
+            block_number: Some(block.into()),
+            log_index: Some(index.into()),
+            data: data.into(),
+            ..Default::default()
+        }
+    }
+
+    fn words(values: &[u64]) -> Vec<u8> {
+        values
+            .iter()
+            .flat_map(|&v| {
+                let mut w = [0u8; 32];
+                U256::from(v).to_big_endian(&mut w);
+                w
+            })
+            .collect()
+    }
+
+    #[test]
+    fn transfers_in_one_window_apply_in_chain_order() {
+        let mut state = Balances::default();
+        let single = H256::from(keccak256(TRANSFER_SINGLE));
+        let ctf = |block, index, value| Log {
+            topics: vec![single],
+            ..log(block, index, words(&[7, value]))
+        };
+
+        // Receive 10 USDC, then send it on, fetched out-first
+        let usdc_in = [log(5, 0, words(&[10_000_000]))];
+        let usdc_out = [log(5, 3, words(&[10_000_000]))];
+        // Buy 4 shares, then unwind them a block later
+        let ctf_in = [ctf(5, 1, 4_000_000)];
+        let ctf_out = [ctf(6, 0, 4_000_000)];
+
+        apply_logs(&mut state, &usdc_out, &usdc_in, &ctf_out, &ctf_in);
+
+        assert_eq!(state.usdc, U256::zero());
+        assert_eq!(state.tokens.get(&U256::from(7)), Some(&U256::zero()));
+    }
+}
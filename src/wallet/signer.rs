@@ -60,3 +60,48 @@ pub struct ClobOrder {
     pub nonce: U256,
     pub expiration: U256,
 }
+
+impl ClobOrder {
+    /// The id the CLOB gives this order: its EIP-712 hash. Known before
+    /// any response, so an order whose response was lost can be looked up.
+    pub fn order_id(&self) -> Option<String> {
+        use ethers::types::transaction::eip712::Eip712 as _;
+
+        self.encode_eip712()
+            .ok()
+            .map(|hash| format!("0x{}", hex::encode(hash)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn order_id_is_the_signed_digest() {
+        let signer = WalletSigner::new(
+            "0x4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318",
+            137,
+        )
+        .unwrap();
+        let order = ClobOrder {
+            salt: U256::from(42),
+            maker: signer.address(),
+            signer: signer.address(),
+            taker: Address::zero(),
+            token_id: H256::repeat_byte(7),
+            maker_amount: U256::from(4_500_000),
+            taker_amount: U256::from(10_000_000),
+            side: 0,
+            fee_rate_bps: U256::zero(),
+            nonce: U256::zero(),
+            expiration: U256::zero(),
+        };
+
+        let id = order.order_id().unwrap();
+        let digest: H256 = id.parse().unwrap();
+        let sig = signer.sign_order(&order).await.unwrap();
+
+        assert_eq!(sig.recover(digest).unwrap(), signer.address());
+    }
+}
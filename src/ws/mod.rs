@@ -1,35 +1,110 @@
 use crate::cache::PriceCache;
 use crate::client::PolymarketClient;
+use crate::recorder::Recorder;
 use futures_util::{SinkExt, StreamExt};
 use log::{info, warn};
 use serde_json::{json, Value};
 use std::sync::Arc;
+use tokio::sync::mpsc;
 use tokio::time::{interval, sleep, Duration};
 use tokio_tungstenite::{connect_async, tungstenite::Message};
 use url::Url;
 
-pub async fn start_ws(
+// ==================================================
+// SUBSCRIPTION HANDLE
+// ==================================================
+
+/// A change to the running task's token list
+pub enum Subscription {
+    Add(Vec<String>),
+    Remove(Vec<String>),
+}
+
+/// Handle for changing the token subscriptions of a running WS task.
+#[derive(Clone)]
+pub struct WsSubscriber {
+    tx: mpsc::UnboundedSender<Subscription>,
+}
+
+impl WsSubscriber {
+    pub fn subscribe(&self, token_ids: Vec<String>) {
+        if self.tx.send(Subscription::Add(token_ids)).is_err() {
+            warn!("⚠️ WS task gone — subscription dropped");
+        }
+    }
+
+    /// Stop following tokens, e.g. those of an expired period, so they are
+    /// not resubscribed on every reconnect.
+    pub fn unsubscribe(&self, token_ids: Vec<String>) {
+        if self.tx.send(Subscription::Remove(token_ids)).is_err() {
+            warn!("⚠️ WS task gone — unsubscription dropped");
+        }
+    }
+}
+
+/// Spawn the WS task and return a handle for subscribing more tokens later.
+pub fn spawn_ws(
     ws_url: String,
     cache: PriceCache,
     token_ids: Vec<String>,
     api: Arc<PolymarketClient>,
+    recorder: Option<Recorder>,
+) -> WsSubscriber {
+    let (tx, rx) = mpsc::unbounded_channel();
+    tokio::spawn(start_ws(ws_url, cache, token_ids, api, rx, recorder));
+    WsSubscriber { tx }
+}
+
+pub async fn start_ws(
+    ws_url: String,
+    cache: PriceCache,
+    mut token_ids: Vec<String>,
+    api: Arc<PolymarketClient>,
+    mut new_tokens: mpsc::UnboundedReceiver<Subscription>,
+    recorder: Option<Recorder>,
 ) {
     loop {
         info!("🔌 Connecting to CLOB WebSocket");
         let api_clone = api.clone();
 
-        if let Err(e) = connect_and_stream(&ws_url, &cache, &token_ids, api_clone).await {
+        if let Err(e) = connect_and_stream(
+            &ws_url,
+            &cache,
+            &mut token_ids,
+            api_clone,
+            &mut new_tokens,
+            recorder.as_ref(),
+        )
+        .await
+        {
             warn!("⚠️ WS error: {} — reconnecting in 2s", e);
             sleep(Duration::from_secs(2)).await;
         }
     }
 }
 
+fn subscribe_message(token_ids: &[String]) -> Message {
+    channel_message("subscribe", token_ids)
+}
+
+fn channel_message(kind: &str, token_ids: &[String]) -> Message {
+    let sub = json!({
+        "type": kind,
+        "channels": [{
+            "name": "market",
+            "token_ids": token_ids
+        }]
+    });
+    Message::Text(sub.to_string())
+}
+
 async fn connect_and_stream(
     ws_url: &str,
     cache: &PriceCache,
-    token_ids: &Vec<String>,
+    token_ids: &mut Vec<String>,
     api: Arc<PolymarketClient>,
+    new_tokens: &mut mpsc::UnboundedReceiver<Subscription>,
+    recorder: Option<&Recorder>,
 ) -> anyhow::Result<()> {
     let (ws, _) = connect_async(Url::parse(ws_url)?).await?;
     let (mut write, mut read) = ws.split();
@@ -44,14 +119,9 @@ async fn connect_and_stream(
     write.send(Message::Text(auth.to_string())).await?;
 
     // ---------- SUBSCRIBE ----------
-    let sub = json!({
-        "type": "subscribe",
-        "channels": [{
-            "name": "market",
-            "token_ids": token_ids
-        }]
-    });
-    write.send(Message::Text(sub.to_string())).await?;
+    if !token_ids.is_empty() {
+        write.send(subscribe_message(token_ids)).await?;
+    }
 
     info!("📡 WS connected & subscribed");
 
@@ -64,6 +134,34 @@ async fn connect_and_stream(
                     .send(Message::Text(json!({"type":"ping"}).to_string()))
                     .await;
             }
+            Some(change) = new_tokens.recv() => match change {
+                Subscription::Add(added) => {
+                    let added: Vec<String> = added
+                        .into_iter()
+                        .filter(|t| !token_ids.contains(t))
+                        .collect();
+
+                    if !added.is_empty() {
+                        // Remember first so a failed send is resubscribed on reconnect
+                        token_ids.extend(added.iter().cloned());
+                        write.send(subscribe_message(&added)).await?;
+                        info!("📡 WS subscribed {} more token(s)", added.len());
+                    }
+                }
+                Subscription::Remove(removed) => {
+                    let removed: Vec<String> = removed
+                        .into_iter()
+                        .filter(|t| token_ids.contains(t))
+                        .collect();
+
+                    if !removed.is_empty() {
+                        // Forget first so a reconnect never brings them back
+                        token_ids.retain(|t| !removed.contains(t));
+                        write.send(channel_message("unsubscribe", &removed)).await?;
+                        info!("📡 WS unsubscribed {} token(s)", removed.len());
+                    }
+                }
+            },
             msg = read.next() => {
                 let msg = msg.ok_or_else(|| anyhow::anyhow!("WS closed"))??;
 
@@ -71,6 +169,9 @@ async fn connect_and_stream(
                     if let Ok(v) = serde_json::from_str::<Value>(&txt) {
                         if let Some(token_id) = v.get("token_id").and_then(|t| t.as_str()) {
                             cache.update_from_price_ws(token_id, &v).await;
+                            if let Some(recorder) = recorder {
+                                recorder.record_ws(token_id, &v);
+                            }
                         }
                     }
                 }